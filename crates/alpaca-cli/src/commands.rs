@@ -1,5 +1,36 @@
-use alpaca_sdk::AlpacaClient;
-use anyhow::Result;
+use alpaca_sdk::types::{
+    AlpacaOrderRequest, AlpacaReplaceOrderRequest, AlpacaSnapshot, BarsRequest, TimeBound,
+    TimeFrame,
+};
+use alpaca_sdk::{AlpacaClient, AlpacaConfig, AlpacaError, AlpacaStream, MarketDataFeed};
+use anyhow::{anyhow, bail, Result};
+use rust_decimal::Decimal;
+
+/// Normalize a ticker argument: trim and uppercase it, rejecting empty
+/// values and characters no Alpaca symbol uses. Allows `.` and `-` share
+/// classes (`BRK.B`), crypto pairs (`BTC/USD`) and option contracts.
+pub fn parse_symbol(value: &str) -> Result<String> {
+    let symbol = value.trim().to_ascii_uppercase();
+    if symbol.is_empty() {
+        bail!("symbol must not be empty");
+    }
+    if symbol.len() > 21 {
+        bail!("`{value}` is too long to be a symbol");
+    }
+    if let Some(c) = symbol
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '/')))
+    {
+        bail!("`{value}` is not a valid symbol (unexpected `{c}`)");
+    }
+    Ok(symbol)
+}
+
+/// Parse a `YYYY-MM-DD` date argument.
+pub fn parse_date(value: &str) -> Result<chrono::NaiveDate> {
+    chrono::NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+        .map_err(|e| anyhow!("`{value}` is not a date like 2024-06-03 ({e})"))
+}
 
 pub async fn account(client: &AlpacaClient) -> Result<serde_json::Value> {
     let account = client.get_account().await?;
@@ -21,20 +52,321 @@ pub async fn quote(client: &AlpacaClient, symbol: &str) -> Result<serde_json::Va
     Ok(serde_json::to_value(quote)?)
 }
 
+pub async fn trade(client: &AlpacaClient, symbol: &str) -> Result<serde_json::Value> {
+    let trade = client.get_latest_trade(symbol).await?;
+    Ok(serde_json::to_value(trade)?)
+}
+
+/// The full snapshot, or with `compact` a one-row summary of last/mid price
+/// and the change since the previous daily close.
+pub async fn snapshot(
+    client: &AlpacaClient,
+    symbol: &str,
+    compact: bool,
+) -> Result<serde_json::Value> {
+    let snapshot = client.get_snapshot(symbol).await?;
+    if compact {
+        Ok(snapshot_summary(symbol, &snapshot))
+    } else {
+        Ok(serde_json::to_value(snapshot)?)
+    }
+}
+
+fn snapshot_summary(symbol: &str, snapshot: &AlpacaSnapshot) -> serde_json::Value {
+    let prev_close = snapshot.prev_daily_bar.as_ref().map(|bar| bar.close);
+    let close = snapshot
+        .last_price()
+        .or_else(|| snapshot.daily_bar.as_ref().map(|bar| bar.close));
+    let change = close.zip(prev_close).map(|(close, prev)| close - prev);
+    let change_pct = change
+        .zip(prev_close)
+        .and_then(|(change, prev)| change.checked_div(prev))
+        .map(|ratio| (ratio * Decimal::ONE_HUNDRED).round_dp(2));
+    serde_json::json!({
+        "symbol": symbol,
+        "last": snapshot.last_price(),
+        "mid": snapshot.mid_price(),
+        "spread": snapshot.spread(),
+        "prev_close": prev_close,
+        "change": change,
+        "change_pct": change_pct,
+    })
+}
+
 pub async fn bars(
     client: &AlpacaClient,
     symbol: &str,
-    start: chrono::NaiveDate,
-    end: chrono::NaiveDate,
-    timeframe: &str,
+    start: TimeBound,
+    end: TimeBound,
+    timeframe: TimeFrame,
 ) -> Result<serde_json::Value> {
     let bars = client
-        .get_bars(symbol, start, end, timeframe, None, None, None)
-        .await?;
+        .get_bars(symbol, &BarsRequest::new(start, end, timeframe))
+        .await
+        .map_err(|e| match e {
+            AlpacaError::Config(msg) => anyhow!("invalid --start/--end: {msg}"),
+            other => other.into(),
+        })?;
     Ok(serde_json::to_value(bars)?)
 }
 
+pub async fn assets(
+    client: &AlpacaClient,
+    status: Option<&str>,
+    asset_class: Option<&str>,
+) -> Result<serde_json::Value> {
+    let assets = client.get_assets(status, asset_class).await?;
+    Ok(serde_json::to_value(assets)?)
+}
+
+pub async fn asset(client: &AlpacaClient, symbol: &str) -> Result<serde_json::Value> {
+    let asset = client.get_asset(symbol).await?;
+    Ok(serde_json::to_value(asset)?)
+}
+
+pub async fn calendar(
+    client: &AlpacaClient,
+    start: Option<chrono::NaiveDate>,
+    end: Option<chrono::NaiveDate>,
+) -> Result<serde_json::Value> {
+    let days = client.get_calendar(start, end).await?;
+    Ok(serde_json::to_value(days)?)
+}
+
 pub async fn clock(client: &AlpacaClient) -> Result<serde_json::Value> {
     let clock = client.get_clock().await?;
     Ok(serde_json::to_value(clock)?)
 }
+
+/// Build the request for the `order` command, checking that limit-style
+/// orders carry a limit price.
+pub fn order_request(
+    symbol: &str,
+    qty: i32,
+    side: &str,
+    order_type: &str,
+    time_in_force: &str,
+    limit_price: Option<Decimal>,
+    extended_hours: bool,
+) -> Result<AlpacaOrderRequest> {
+    if matches!(order_type, "limit" | "stop_limit") && limit_price.is_none() {
+        bail!("--limit-price is required for {order_type} orders");
+    }
+    Ok(AlpacaOrderRequest {
+        symbol: symbol.to_string(),
+        qty,
+        side: side.to_string(),
+        order_type: order_type.to_string(),
+        time_in_force: time_in_force.to_string(),
+        limit_price,
+        stop_price: None,
+        trail_percent: None,
+        trail_price: None,
+        extended_hours,
+        client_order_id: None,
+        order_class: None,
+        position_intent: None,
+    })
+}
+
+pub async fn order(
+    client: &AlpacaClient,
+    request: &AlpacaOrderRequest,
+) -> Result<serde_json::Value> {
+    let order = client
+        .submit_order(
+            &request.symbol,
+            request.qty,
+            &request.side,
+            &request.order_type,
+            &request.time_in_force,
+            request.limit_price,
+            request.extended_hours,
+        )
+        .await?;
+    Ok(serde_json::to_value(order)?)
+}
+
+pub async fn cancel(client: &AlpacaClient, order_id: &str) -> Result<serde_json::Value> {
+    client.cancel_order(order_id).await?;
+    Ok(serde_json::json!({ "canceled": order_id }))
+}
+
+pub async fn cancel_all(client: &AlpacaClient) -> Result<serde_json::Value> {
+    let statuses = client.cancel_all_orders().await?;
+    Ok(serde_json::json!({
+        "canceled": statuses.len(),
+        "orders": statuses,
+    }))
+}
+
+/// Build the request for the `replace` command; only the fields given are sent.
+pub fn replace_request(
+    qty: Option<i32>,
+    limit_price: Option<Decimal>,
+    time_in_force: Option<&str>,
+) -> Result<AlpacaReplaceOrderRequest> {
+    if qty.is_none() && limit_price.is_none() && time_in_force.is_none() {
+        bail!("nothing to replace: pass at least one of --qty, --limit-price, --tif");
+    }
+    Ok(AlpacaReplaceOrderRequest {
+        qty,
+        limit_price,
+        time_in_force: time_in_force.map(|s| s.to_string()),
+    })
+}
+
+pub async fn replace(
+    client: &AlpacaClient,
+    order_id: &str,
+    request: &AlpacaReplaceOrderRequest,
+) -> Result<serde_json::Value> {
+    let order = client
+        .replace_order(
+            order_id,
+            request.qty,
+            request.limit_price,
+            request.time_in_force.as_deref(),
+        )
+        .await?;
+    Ok(serde_json::to_value(order)?)
+}
+
+/// Symbols to subscribe to on each channel for the `watch` command.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct WatchSubscription {
+    pub trades: Vec<String>,
+    pub quotes: Vec<String>,
+    pub bars: Vec<String>,
+}
+
+impl WatchSubscription {
+    /// Map `--symbols` onto the selected channels, defaulting to trades when
+    /// no channel flag is given.
+    pub fn new(symbols: &[String], trades: bool, quotes: bool, bars: bool) -> Self {
+        let trades = trades || !(quotes || bars);
+        let pick = |enabled: bool| {
+            if enabled {
+                symbols.to_vec()
+            } else {
+                Vec::new()
+            }
+        };
+        Self {
+            trades: pick(trades),
+            quotes: pick(quotes),
+            bars: pick(bars),
+        }
+    }
+}
+
+fn as_refs(symbols: &[String]) -> Vec<&str> {
+    symbols.iter().map(String::as_str).collect()
+}
+
+/// Stream market data to stdout, one JSON message per line, until Ctrl-C.
+pub async fn watch(
+    config: &AlpacaConfig,
+    feed: MarketDataFeed,
+    subscription: &WatchSubscription,
+) -> Result<()> {
+    let mut stream = AlpacaStream::connect_market_data_with_reconnect(config, feed).await?;
+    stream
+        .subscribe(
+            &as_refs(&subscription.trades),
+            &as_refs(&subscription.quotes),
+            &as_refs(&subscription.bars),
+        )
+        .await?;
+
+    loop {
+        tokio::select! {
+            msg = stream.recv() => match msg {
+                Some(msg) => println!("{}", serde_json::to_string(&msg?)?),
+                None => break,
+            },
+            _ = tokio::signal::ctrl_c() => {
+                stream.close().await?;
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alpaca_sdk::testing::mock_client;
+    use alpaca_sdk::MockTransport;
+    use reqwest::Method;
+
+    fn query(transport: &MockTransport, index: usize) -> Vec<(String, String)> {
+        transport.requests()[index].query.clone()
+    }
+
+    #[tokio::test]
+    async fn snapshot_compact_summarizes_prices() {
+        let (client, transport) = mock_client(MockTransport::new().respond(
+            Method::GET,
+            "/v2/stocks/SPY/snapshot",
+            200,
+            r#"{
+                "latestTrade": {"t":"2024-06-03T15:00:00Z","p":525.5,"s":100,"x":"V","i":1,"z":"B"},
+                "latestQuote": {"t":"2024-06-03T15:00:00Z","bp":525.4,"bs":1,"bx":"V","ap":525.6,"as":2,"ax":"V","z":"B"},
+                "dailyBar": {"t":"2024-06-03T04:00:00Z","o":520,"h":526,"l":519,"c":525.5,"v":1000},
+                "prevDailyBar": {"t":"2024-05-31T04:00:00Z","o":515,"h":521,"l":514,"c":520,"v":900}
+            }"#,
+        ));
+
+        let summary = snapshot(&client, "SPY", true).await.unwrap();
+        assert_eq!(summary["last"], "525.5");
+        assert_eq!(summary["mid"], "525.5");
+        assert_eq!(summary["prev_close"], "520");
+        assert_eq!(summary["change"], "5.5");
+        assert_eq!(summary["change_pct"], "1.06");
+
+        let full = snapshot(&client, "SPY", false).await.unwrap();
+        assert!(full.get("latestQuote").is_some());
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn reference_data_commands_map_to_sdk_calls() {
+        let body = r#"{"id":"a1","class":"us_equity","exchange":"NASDAQ","symbol":"AAPL",
+            "status":"active","tradable":true,"marginable":true,"shortable":true}"#;
+        let (client, transport) = mock_client(
+            MockTransport::new()
+                .respond(Method::GET, "/v2/assets", 200, &format!("[{body}]"))
+                .respond(Method::GET, "/v2/assets/AAPL", 200, body)
+                .respond(
+                    Method::GET,
+                    "/v2/calendar",
+                    200,
+                    r#"[{"date":"2024-11-29","open":"09:30","close":"13:00"}]"#,
+                ),
+        );
+
+        let listed = assets(&client, Some("active"), Some("us_equity"))
+            .await
+            .unwrap();
+        assert_eq!(listed[0]["symbol"], "AAPL");
+        assert_eq!(
+            query(&transport, 0),
+            [
+                ("status".to_string(), "active".to_string()),
+                ("asset_class".to_string(), "us_equity".to_string()),
+            ]
+        );
+
+        assert_eq!(asset(&client, "AAPL").await.unwrap()["exchange"], "NASDAQ");
+
+        let start = chrono::NaiveDate::from_ymd_opt(2024, 11, 25);
+        let days = calendar(&client, start, None).await.unwrap();
+        assert_eq!(days[0]["close"], "13:00");
+        assert_eq!(
+            query(&transport, 2),
+            [("start".to_string(), "2024-11-25".to_string())]
+        );
+    }
+}
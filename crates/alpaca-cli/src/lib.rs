@@ -1 +1,3 @@
 pub mod commands;
+pub mod output;
+pub mod profile;
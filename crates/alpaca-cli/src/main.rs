@@ -1,10 +1,23 @@
 use alpaca_cli::commands;
+use alpaca_cli::output::{self, OutputFormat};
+use alpaca_cli::profile::{self, ConfigFile};
+use alpaca_sdk::types::{TimeBound, TimeFrame};
+use alpaca_sdk::{AlpacaClient, AlpacaConfig, MarketDataFeed};
 use anyhow::Result;
 use clap::{Parser, Subcommand};
+use rust_decimal::Decimal;
 
 #[derive(Parser)]
 #[command(name = "alpaca", about = "CLI for the Alpaca Trading API")]
 struct Cli {
+    /// Output format
+    #[arg(long, short, global = true, value_enum, default_value_t = OutputFormat::Json)]
+    output: OutputFormat,
+
+    /// Credentials profile from ~/.config/alpaca/config.toml (or $ALPACA_PROFILE)
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -24,24 +37,152 @@ enum Commands {
     /// Get latest quote for a symbol
     Quote {
         /// Stock symbol
+        #[arg(value_parser = commands::parse_symbol)]
+        symbol: String,
+    },
+    /// Get the latest trade for a symbol
+    Trade {
+        /// Stock symbol
+        #[arg(value_parser = commands::parse_symbol)]
+        symbol: String,
+    },
+    /// Get a snapshot (latest trade, quote and bars); `--output table` shows a summary
+    Snapshot {
+        /// Stock symbol
+        #[arg(value_parser = commands::parse_symbol)]
         symbol: String,
     },
     /// Get historical bars for a symbol
     Bars {
         /// Stock symbol
+        #[arg(value_parser = commands::parse_symbol)]
         symbol: String,
         /// Start date (YYYY-MM-DD)
-        #[arg(long)]
-        start: String,
+        #[arg(long, value_parser = commands::parse_date)]
+        start: chrono::NaiveDate,
         /// End date (YYYY-MM-DD)
+        #[arg(long, value_parser = commands::parse_date)]
+        end: chrono::NaiveDate,
+        /// Start time of day in UTC (HH:MM[:SS]); narrows --start to an instant
+        #[arg(long)]
+        start_time: Option<chrono::NaiveTime>,
+        /// End time of day in UTC (HH:MM[:SS]); narrows --end to an instant
         #[arg(long)]
-        end: String,
-        /// Timeframe (1Min, 5Min, 15Min, 1Hour, 1Day)
+        end_time: Option<chrono::NaiveTime>,
+        /// Timeframe (e.g. 1Min, 5Min, 15Min, 1Hour, 1Day, 1Week, 1Month)
         #[arg(long, default_value = "1Day")]
-        timeframe: String,
+        timeframe: TimeFrame,
+    },
+    /// List assets
+    Assets {
+        /// Filter by status (active, inactive)
+        #[arg(long)]
+        status: Option<String>,
+        /// Filter by asset class (us_equity, us_option, crypto)
+        #[arg(long = "class")]
+        asset_class: Option<String>,
+    },
+    /// Show a single asset
+    Asset {
+        /// Symbol or asset ID
+        #[arg(value_parser = commands::parse_symbol)]
+        symbol: String,
+    },
+    /// Show trading days with their open and close times
+    Calendar {
+        /// First day (YYYY-MM-DD)
+        #[arg(long, value_parser = commands::parse_date)]
+        start: Option<chrono::NaiveDate>,
+        /// Last day (YYYY-MM-DD)
+        #[arg(long, value_parser = commands::parse_date)]
+        end: Option<chrono::NaiveDate>,
     },
     /// Get market clock
     Clock,
+    /// Submit an order
+    Order {
+        /// Stock symbol
+        #[arg(long, value_parser = commands::parse_symbol)]
+        symbol: String,
+        /// Number of shares
+        #[arg(long)]
+        qty: i32,
+        /// Order side
+        #[arg(long, value_parser = ["buy", "sell"])]
+        side: String,
+        /// Order type (market, limit, stop, stop_limit)
+        #[arg(long = "type", default_value = "market")]
+        order_type: String,
+        /// Limit price, required for limit orders
+        #[arg(long)]
+        limit_price: Option<Decimal>,
+        /// Time in force (day, gtc, opg, cls, ioc, fok)
+        #[arg(long, default_value = "day")]
+        tif: String,
+        /// Allow execution in pre/post-market sessions
+        #[arg(long)]
+        extended_hours: bool,
+        /// Print the request body instead of submitting it
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Cancel an open order
+    Cancel {
+        /// Order ID
+        order_id: String,
+    },
+    /// Cancel all open orders
+    CancelAll,
+    /// Replace (modify) an open order
+    Replace {
+        /// Order ID
+        order_id: String,
+        /// New quantity
+        #[arg(long)]
+        qty: Option<i32>,
+        /// New limit price
+        #[arg(long)]
+        limit_price: Option<Decimal>,
+        /// New time in force
+        #[arg(long)]
+        tif: Option<String>,
+    },
+    /// Stream live market data until Ctrl-C
+    Watch {
+        /// Comma-separated symbols
+        #[arg(long, value_delimiter = ',', required = true, value_parser = commands::parse_symbol)]
+        symbols: Vec<String>,
+        /// Subscribe to trades (the default if no channel is chosen)
+        #[arg(long)]
+        trades: bool,
+        /// Subscribe to quotes
+        #[arg(long)]
+        quotes: bool,
+        /// Subscribe to minute bars
+        #[arg(long)]
+        bars: bool,
+        /// Data feed (iex, sip, delayed_sip, boats, test)
+        #[arg(long, default_value = "iex")]
+        feed: MarketDataFeed,
+    },
+}
+
+/// Config from the selected profile, or from env vars when none is selected.
+fn config(profile: Option<&str>) -> Result<AlpacaConfig> {
+    let file = match profile::default_path() {
+        Some(path) => ConfigFile::load(&path)?,
+        None => None,
+    };
+    let env = std::env::var(profile::PROFILE_ENV).ok();
+    profile::resolve(profile, env.as_deref(), file.as_ref())
+}
+
+/// Combine a `--start`/`--end` date with an optional UTC time of day.
+fn time_bound(date: chrono::NaiveDate, time: Option<chrono::NaiveTime>) -> TimeBound {
+    match time {
+        Some(time) => date.and_time(time).and_utc().into(),
+        None => date.into(),
+    }
 }
 
 #[tokio::main]
@@ -51,28 +192,398 @@ async fn main() -> Result<()> {
         .init();
 
     let cli = Cli::parse();
-    let config = alpaca_sdk::AlpacaConfig::from_env()
-        .map_err(|e| anyhow::anyhow!("Missing env var: {e}"))?;
-    let client = alpaca_sdk::AlpacaClient::new(config)?;
+    let profile = cli.profile.as_deref();
+    let client = || -> Result<AlpacaClient> { Ok(AlpacaClient::new(config(profile)?)?) };
+    let columns = match cli.command {
+        Commands::Positions => Some(output::POSITION_COLUMNS),
+        Commands::Orders { .. } => Some(output::ORDER_COLUMNS),
+        Commands::Assets { .. } => Some(output::ASSET_COLUMNS),
+        Commands::Calendar { .. } => Some(output::CALENDAR_COLUMNS),
+        _ => None,
+    };
 
     let result = match cli.command {
-        Commands::Account => commands::account(&client).await?,
-        Commands::Positions => commands::positions(&client).await?,
-        Commands::Orders { status } => commands::orders(&client, status.as_deref()).await?,
-        Commands::Quote { symbol } => commands::quote(&client, &symbol).await?,
+        Commands::Account => commands::account(&client()?).await?,
+        Commands::Positions => commands::positions(&client()?).await?,
+        Commands::Orders { status } => commands::orders(&client()?, status.as_deref()).await?,
+        Commands::Quote { symbol } => commands::quote(&client()?, &symbol).await?,
+        Commands::Trade { symbol } => commands::trade(&client()?, &symbol).await?,
+        Commands::Snapshot { symbol } => {
+            let compact = cli.output == OutputFormat::Table;
+            commands::snapshot(&client()?, &symbol, compact).await?
+        }
         Commands::Bars {
             symbol,
             start,
             end,
+            start_time,
+            end_time,
             timeframe,
         } => {
-            let start_date = start.parse::<chrono::NaiveDate>()?;
-            let end_date = end.parse::<chrono::NaiveDate>()?;
-            commands::bars(&client, &symbol, start_date, end_date, &timeframe).await?
+            let start = time_bound(start, start_time);
+            let end = time_bound(end, end_time);
+            commands::bars(&client()?, &symbol, start, end, timeframe).await?
+        }
+        Commands::Assets {
+            status,
+            asset_class,
+        } => commands::assets(&client()?, status.as_deref(), asset_class.as_deref()).await?,
+        Commands::Asset { symbol } => commands::asset(&client()?, &symbol).await?,
+        Commands::Calendar { start, end } => commands::calendar(&client()?, start, end).await?,
+        Commands::Clock => commands::clock(&client()?).await?,
+        Commands::Order {
+            symbol,
+            qty,
+            side,
+            order_type,
+            limit_price,
+            tif,
+            extended_hours,
+            dry_run,
+        } => {
+            let request = commands::order_request(
+                &symbol,
+                qty,
+                &side,
+                &order_type,
+                &tif,
+                limit_price,
+                extended_hours,
+            )?;
+            if dry_run {
+                serde_json::to_value(&request)?
+            } else {
+                commands::order(&client()?, &request).await?
+            }
+        }
+        Commands::Cancel { order_id } => commands::cancel(&client()?, &order_id).await?,
+        Commands::CancelAll => commands::cancel_all(&client()?).await?,
+        Commands::Replace {
+            order_id,
+            qty,
+            limit_price,
+            tif,
+        } => {
+            let request = commands::replace_request(qty, limit_price, tif.as_deref())?;
+            commands::replace(&client()?, &order_id, &request).await?
+        }
+        Commands::Watch {
+            symbols,
+            trades,
+            quotes,
+            bars,
+            feed,
+        } => {
+            let subscription = commands::WatchSubscription::new(&symbols, trades, quotes, bars);
+            return commands::watch(&config(profile)?, feed, &subscription).await;
         }
-        Commands::Clock => commands::clock(&client).await?,
     };
 
-    println!("{}", serde_json::to_string_pretty(&result)?);
+    println!("{}", output::render(&result, cli.output, columns)?);
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_order(args: &[&str]) -> Result<alpaca_sdk::types::AlpacaOrderRequest> {
+        let cli = Cli::try_parse_from(args)?;
+        match cli.command {
+            Commands::Order {
+                symbol,
+                qty,
+                side,
+                order_type,
+                limit_price,
+                tif,
+                extended_hours,
+                ..
+            } => commands::order_request(
+                &symbol,
+                qty,
+                &side,
+                &order_type,
+                &tif,
+                limit_price,
+                extended_hours,
+            ),
+            _ => panic!("expected order command"),
+        }
+    }
+
+    #[test]
+    fn order_args_build_limit_request() {
+        let request = parse_order(&[
+            "alpaca",
+            "order",
+            "--symbol",
+            "AAPL",
+            "--qty",
+            "10",
+            "--side",
+            "buy",
+            "--type",
+            "limit",
+            "--limit-price",
+            "150.00",
+            "--tif",
+            "day",
+            "--dry-run",
+        ])
+        .unwrap();
+        assert_eq!(request.symbol, "AAPL");
+        assert_eq!(request.qty, 10);
+        assert_eq!(request.side, "buy");
+        assert_eq!(request.order_type, "limit");
+        assert_eq!(request.time_in_force, "day");
+        assert_eq!(request.limit_price, Some(Decimal::new(15000, 2)));
+        assert!(!request.extended_hours);
+    }
+
+    #[test]
+    fn order_args_default_to_market_day() {
+        let request = parse_order(&[
+            "alpaca", "order", "--symbol", "SPY", "--qty", "1", "--side", "sell",
+        ])
+        .unwrap();
+        assert_eq!(request.order_type, "market");
+        assert_eq!(request.time_in_force, "day");
+        assert_eq!(request.limit_price, None);
+    }
+
+    #[test]
+    fn order_args_reject_bad_input() {
+        // Unknown side is rejected by clap.
+        assert!(parse_order(&[
+            "alpaca", "order", "--symbol", "SPY", "--qty", "1", "--side", "hold",
+        ])
+        .is_err());
+        // Limit orders need a limit price.
+        assert!(parse_order(&[
+            "alpaca", "order", "--symbol", "SPY", "--qty", "1", "--side", "buy", "--type", "limit",
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn cancel_commands_parse() {
+        let cli = Cli::try_parse_from(["alpaca", "cancel", "order-123"]).unwrap();
+        assert!(matches!(cli.command, Commands::Cancel { order_id } if order_id == "order-123"));
+
+        let cli = Cli::try_parse_from(["alpaca", "cancel-all"]).unwrap();
+        assert!(matches!(cli.command, Commands::CancelAll));
+    }
+
+    #[test]
+    fn replace_sends_only_given_flags() {
+        let cli = Cli::try_parse_from([
+            "alpaca",
+            "replace",
+            "order-123",
+            "--limit-price",
+            "151.25",
+            "--tif",
+            "gtc",
+        ])
+        .unwrap();
+        let Commands::Replace {
+            order_id,
+            qty,
+            limit_price,
+            tif,
+        } = cli.command
+        else {
+            panic!("expected replace command");
+        };
+        assert_eq!(order_id, "order-123");
+
+        let request = commands::replace_request(qty, limit_price, tif.as_deref()).unwrap();
+        let body = serde_json::to_value(&request).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({"limit_price": "151.25", "time_in_force": "gtc"})
+        );
+
+        assert!(commands::replace_request(None, None, None).is_err());
+    }
+
+    #[test]
+    fn watch_args_map_to_subscription_and_feed() {
+        let cli = Cli::try_parse_from([
+            "alpaca",
+            "watch",
+            "--symbols",
+            "AAPL,TSLA",
+            "--quotes",
+            "--trades",
+            "--feed",
+            "sip",
+        ])
+        .unwrap();
+        let Commands::Watch {
+            symbols,
+            trades,
+            quotes,
+            bars,
+            feed,
+        } = cli.command
+        else {
+            panic!("expected watch command");
+        };
+        assert_eq!(feed, MarketDataFeed::Sip);
+        assert_eq!(
+            commands::WatchSubscription::new(&symbols, trades, quotes, bars),
+            commands::WatchSubscription {
+                trades: vec!["AAPL".into(), "TSLA".into()],
+                quotes: vec!["AAPL".into(), "TSLA".into()],
+                bars: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn watch_defaults_to_trades_on_iex() {
+        let cli = Cli::try_parse_from(["alpaca", "watch", "--symbols", "SPY"]).unwrap();
+        let Commands::Watch {
+            symbols,
+            trades,
+            quotes,
+            bars,
+            feed,
+        } = cli.command
+        else {
+            panic!("expected watch command");
+        };
+        assert_eq!(feed, MarketDataFeed::Iex);
+        let subscription = commands::WatchSubscription::new(&symbols, trades, quotes, bars);
+        assert_eq!(subscription.trades, vec!["SPY"]);
+        assert!(subscription.quotes.is_empty());
+
+        assert!(
+            Cli::try_parse_from(["alpaca", "watch", "--symbols", "SPY", "--feed", "otc"]).is_err()
+        );
+    }
+
+    #[test]
+    fn bars_times_narrow_dates_to_instants() {
+        let cli = Cli::try_parse_from([
+            "alpaca",
+            "bars",
+            "AAPL",
+            "--start",
+            "2024-06-03",
+            "--end",
+            "2024-06-03",
+            "--start-time",
+            "13:30",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Bars {
+                start,
+                end,
+                start_time,
+                end_time,
+                ..
+            } => {
+                let start = time_bound(start, start_time);
+                let end = time_bound(end, end_time);
+                assert_eq!(start.to_string(), "2024-06-03T13:30:00Z");
+                assert_eq!(end.to_string(), "2024-06-03");
+            }
+            _ => panic!("expected bars command"),
+        }
+    }
+
+    #[test]
+    fn symbols_are_normalized_and_validated() {
+        let cli = Cli::try_parse_from(["alpaca", "quote", " aapl "]).unwrap();
+        assert!(matches!(cli.command, Commands::Quote { symbol } if symbol == "AAPL"));
+
+        let cli = Cli::try_parse_from(["alpaca", "watch", "--symbols", "brk.b,btc/usd"]).unwrap();
+        let Commands::Watch { symbols, .. } = cli.command else {
+            panic!("expected watch command");
+        };
+        assert_eq!(symbols, ["BRK.B", "BTC/USD"]);
+
+        for bad in ["", "AA PL", "$TSLA"] {
+            let err = Cli::try_parse_from(["alpaca", "quote", bad])
+                .err()
+                .unwrap_or_else(|| panic!("{bad:?} should be rejected"));
+            assert!(err.to_string().contains("<SYMBOL>"), "{err}");
+        }
+    }
+
+    #[test]
+    fn bad_bars_date_names_the_argument() {
+        let err = Cli::try_parse_from([
+            "alpaca",
+            "bars",
+            "SPY",
+            "--start",
+            "2024-13-01",
+            "--end",
+            "2024-06-30",
+        ])
+        .err()
+        .unwrap()
+        .to_string();
+        assert!(err.contains("--start"), "{err}");
+        assert!(err.contains("2024-13-01"), "{err}");
+
+        let cli = Cli::try_parse_from([
+            "alpaca",
+            "bars",
+            "spy",
+            "--start",
+            "2024-06-03",
+            "--end",
+            "2024-06-30",
+        ])
+        .unwrap();
+        let Commands::Bars { symbol, start, .. } = cli.command else {
+            panic!("expected bars command");
+        };
+        assert_eq!(symbol, "SPY");
+        assert_eq!(start, chrono::NaiveDate::from_ymd_opt(2024, 6, 3).unwrap());
+    }
+
+    #[test]
+    fn reference_data_commands_parse() {
+        let cli = Cli::try_parse_from([
+            "alpaca",
+            "assets",
+            "--status",
+            "active",
+            "--class",
+            "us_equity",
+        ])
+        .unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Assets { status, asset_class }
+                if status.as_deref() == Some("active") && asset_class.as_deref() == Some("us_equity")
+        ));
+
+        let cli = Cli::try_parse_from(["alpaca", "asset", "aapl"]).unwrap();
+        assert!(matches!(cli.command, Commands::Asset { symbol } if symbol == "AAPL"));
+
+        let cli = Cli::try_parse_from(["alpaca", "calendar", "--start", "2024-11-25"]).unwrap();
+        let Commands::Calendar { start, end } = cli.command else {
+            panic!("expected calendar command");
+        };
+        assert_eq!(start, chrono::NaiveDate::from_ymd_opt(2024, 11, 25));
+        assert_eq!(end, None);
+    }
+
+    #[test]
+    fn market_data_commands_parse() {
+        let cli = Cli::try_parse_from(["alpaca", "trade", "tsla"]).unwrap();
+        assert!(matches!(cli.command, Commands::Trade { symbol } if symbol == "TSLA"));
+
+        let cli = Cli::try_parse_from(["alpaca", "snapshot", "SPY", "--output", "table"]).unwrap();
+        assert_eq!(cli.output, OutputFormat::Table);
+        assert!(matches!(cli.command, Commands::Snapshot { symbol } if symbol == "SPY"));
+    }
+}
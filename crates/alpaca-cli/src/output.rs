@@ -0,0 +1,181 @@
+use anyhow::Result;
+use clap::ValueEnum;
+use serde_json::Value;
+
+/// Output format for command results.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Json,
+    Csv,
+    Table,
+}
+
+/// Most relevant columns for `positions` output.
+pub const POSITION_COLUMNS: &[&str] = &[
+    "symbol",
+    "side",
+    "qty",
+    "avg_entry_price",
+    "current_price",
+    "market_value",
+    "unrealized_pl",
+];
+
+/// Most relevant columns for `orders` output.
+pub const ORDER_COLUMNS: &[&str] = &[
+    "id",
+    "symbol",
+    "side",
+    "type",
+    "qty",
+    "filled_qty",
+    "limit_price",
+    "status",
+    "submitted_at",
+];
+
+/// Most relevant columns for `assets` output.
+pub const ASSET_COLUMNS: &[&str] = &[
+    "symbol",
+    "name",
+    "class",
+    "exchange",
+    "status",
+    "tradable",
+    "shortable",
+    "fractionable",
+];
+
+/// Most relevant columns for `calendar` output.
+pub const CALENDAR_COLUMNS: &[&str] = &["date", "open", "close"];
+
+/// Render a command result.
+///
+/// Arrays of objects become one row per element; a single object becomes one
+/// row. `columns` selects and orders the fields; `None` uses every field of
+/// the first row.
+pub fn render(value: &Value, format: OutputFormat, columns: Option<&[&str]>) -> Result<String> {
+    match format {
+        OutputFormat::Json => Ok(serde_json::to_string_pretty(value)?),
+        OutputFormat::Csv => {
+            let (header, rows) = tabulate(value, columns);
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            writer.write_record(&header)?;
+            for row in rows {
+                writer.write_record(&row)?;
+            }
+            Ok(String::from_utf8(writer.into_inner()?)?)
+        }
+        OutputFormat::Table => {
+            let (header, rows) = tabulate(value, columns);
+            Ok(render_table(&header, &rows))
+        }
+    }
+}
+
+fn tabulate(value: &Value, columns: Option<&[&str]>) -> (Vec<String>, Vec<Vec<String>>) {
+    let items: Vec<&Value> = match value {
+        Value::Array(items) => items.iter().collect(),
+        other => vec![other],
+    };
+
+    let header: Vec<String> = match columns {
+        Some(columns) => columns.iter().map(|c| c.to_string()).collect(),
+        None => match items.first() {
+            Some(Value::Object(map)) => map.keys().cloned().collect(),
+            _ => vec!["value".to_string()],
+        },
+    };
+
+    let rows = items
+        .iter()
+        .map(|item| match item {
+            Value::Object(map) => header
+                .iter()
+                .map(|key| map.get(key).map(cell).unwrap_or_default())
+                .collect(),
+            other => vec![cell(other)],
+        })
+        .collect();
+
+    (header, rows)
+}
+
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn render_table(header: &[String], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = header.iter().map(|h| h.len()).collect();
+    for row in rows {
+        for (width, value) in widths.iter_mut().zip(row) {
+            *width = (*width).max(value.chars().count());
+        }
+    }
+
+    let format_row = |row: &[String]| {
+        row.iter()
+            .zip(&widths)
+            .map(|(value, width)| format!("{value:<width$}"))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    let mut lines = vec![
+        format_row(header),
+        widths
+            .iter()
+            .map(|w| "-".repeat(*w))
+            .collect::<Vec<_>>()
+            .join("  "),
+    ];
+    lines.extend(rows.iter().map(|row| format_row(row)));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn positions() -> Value {
+        serde_json::json!([
+            {"asset_id": "a", "symbol": "AAPL", "side": "long", "qty": "10", "avg_entry_price": "150.00",
+             "current_price": "155.00", "market_value": "1550.00", "unrealized_pl": "50.00", "exchange": "NASDAQ"},
+            {"asset_id": "b", "symbol": "TSLA", "side": "short", "qty": "-5", "avg_entry_price": "200.00",
+             "current_price": null, "market_value": null, "unrealized_pl": null, "exchange": "NASDAQ"}
+        ])
+    }
+
+    #[test]
+    fn csv_has_header_and_one_row_per_position() {
+        let csv = render(&positions(), OutputFormat::Csv, Some(POSITION_COLUMNS)).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[0],
+            "symbol,side,qty,avg_entry_price,current_price,market_value,unrealized_pl"
+        );
+        assert_eq!(lines[1], "AAPL,long,10,150.00,155.00,1550.00,50.00");
+        assert_eq!(lines[2], "TSLA,short,-5,200.00,,,");
+    }
+
+    #[test]
+    fn table_aligns_columns() {
+        let table = render(&positions(), OutputFormat::Table, Some(&["symbol", "qty"])).unwrap();
+        assert_eq!(table, "symbol  qty\n------  ---\nAAPL    10\nTSLA    -5");
+    }
+
+    #[test]
+    fn single_object_uses_its_fields() {
+        let value = serde_json::json!({"is_open": true, "timestamp": "2024-06-03T10:00:00Z"});
+        let csv = render(&value, OutputFormat::Csv, None).unwrap();
+        assert_eq!(csv, "is_open,timestamp\ntrue,2024-06-03T10:00:00Z\n");
+    }
+}
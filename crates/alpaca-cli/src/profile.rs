@@ -0,0 +1,167 @@
+//! Named credential profiles from `~/.config/alpaca/config.toml`:
+//!
+//! ```toml
+//! default_profile = "paper"
+//!
+//! [profiles.paper]
+//! api_key_id = "PK..."
+//! api_secret_key = "..."
+//!
+//! [profiles.live]
+//! api_key_id = "AK..."
+//! api_secret_key = "..."
+//! paper = false
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use alpaca_sdk::AlpacaConfig;
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Deserialize;
+
+/// Environment variable naming the profile when `--profile` isn't given.
+pub const PROFILE_ENV: &str = "ALPACA_PROFILE";
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ConfigFile {
+    /// Profile used when neither `--profile` nor `ALPACA_PROFILE` is set.
+    #[serde(default)]
+    pub default_profile: Option<String>,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Profile {
+    pub api_key_id: String,
+    pub api_secret_key: String,
+    /// Trade against the paper endpoint (the default) or live.
+    #[serde(default = "default_paper")]
+    pub paper: bool,
+    #[serde(default)]
+    pub trading_base_url: Option<String>,
+    #[serde(default)]
+    pub market_data_base_url: Option<String>,
+}
+
+fn default_paper() -> bool {
+    true
+}
+
+impl ConfigFile {
+    pub fn parse(contents: &str) -> Result<Self> {
+        Ok(toml::from_str(contents)?)
+    }
+
+    /// Read the config file at `path`; `None` if it doesn't exist.
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => Self::parse(&contents)
+                .with_context(|| format!("invalid config file {}", path.display()))
+                .map(Some),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e).with_context(|| format!("cannot read {}", path.display())),
+        }
+    }
+}
+
+impl Profile {
+    pub fn to_config(&self) -> Result<AlpacaConfig> {
+        let mut builder = AlpacaConfig::builder()
+            .api_key(&self.api_key_id)
+            .api_secret(&self.api_secret_key)
+            .paper(self.paper);
+        if let Some(url) = &self.trading_base_url {
+            builder = builder.trading_base_url(url);
+        }
+        if let Some(url) = &self.market_data_base_url {
+            builder = builder.market_data_base_url(url);
+        }
+        Ok(builder.build()?)
+    }
+}
+
+/// `$XDG_CONFIG_HOME/alpaca/config.toml`, else `~/.config/alpaca/config.toml`.
+pub fn default_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("alpaca").join("config.toml"))
+}
+
+/// Pick the profile name: `--profile`, then `ALPACA_PROFILE`, then the
+/// file's `default_profile`.
+pub fn select_profile<'a>(
+    flag: Option<&'a str>,
+    env: Option<&'a str>,
+    file: Option<&'a ConfigFile>,
+) -> Option<&'a str> {
+    flag.or(env)
+        .or_else(|| file.and_then(|f| f.default_profile.as_deref()))
+}
+
+/// Build the CLI's config from the selected profile, falling back to
+/// [`AlpacaConfig::from_env`] when no profile is selected.
+pub fn resolve(
+    flag: Option<&str>,
+    env: Option<&str>,
+    file: Option<&ConfigFile>,
+) -> Result<AlpacaConfig> {
+    let Some(name) = select_profile(flag, env, file) else {
+        return AlpacaConfig::from_env().map_err(|e| anyhow!("Missing env var: {e}"));
+    };
+    let Some(profile) = file.and_then(|f| f.profiles.get(name)) else {
+        bail!("profile `{name}` not found in config file");
+    };
+    profile.to_config()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"
+        default_profile = "paper"
+
+        [profiles.paper]
+        api_key_id = "PK1"
+        api_secret_key = "paper-secret"
+
+        [profiles.live]
+        api_key_id = "AK1"
+        api_secret_key = "live-secret"
+        paper = false
+    "#;
+
+    #[test]
+    fn flag_beats_env_beats_file_default() {
+        let file = ConfigFile::parse(SAMPLE).unwrap();
+        assert_eq!(
+            select_profile(Some("live"), Some("other"), Some(&file)),
+            Some("live")
+        );
+        assert_eq!(
+            select_profile(None, Some("live"), Some(&file)),
+            Some("live")
+        );
+        assert_eq!(select_profile(None, None, Some(&file)), Some("paper"));
+        assert_eq!(select_profile(None, None, None), None);
+    }
+
+    #[test]
+    fn profiles_build_configs() {
+        let file = ConfigFile::parse(SAMPLE).unwrap();
+
+        let paper = resolve(None, None, Some(&file)).unwrap();
+        assert_eq!(paper.api_key_id, "PK1");
+        assert_eq!(paper.trading_base_url, "https://paper-api.alpaca.markets");
+
+        let live = resolve(None, Some("live"), Some(&file)).unwrap();
+        assert_eq!(live.api_key_id, "AK1");
+        assert_eq!(live.trading_base_url, "https://api.alpaca.markets");
+
+        let err = resolve(Some("staging"), None, Some(&file)).unwrap_err();
+        assert!(err.to_string().contains("`staging`"), "{err}");
+    }
+}
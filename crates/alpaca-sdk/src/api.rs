@@ -0,0 +1,197 @@
+//! [`TradingApi`], the core trading and market data calls as a trait, so
+//! strategy code can be written against it and tested with a stub instead of
+//! a live [`AlpacaClient`].
+
+use futures_util::future::BoxFuture;
+
+use crate::client::AlpacaClient;
+use crate::error::AlpacaError;
+use crate::types::*;
+
+/// The core Alpaca calls a strategy needs, implemented by [`AlpacaClient`].
+///
+/// Write strategy code generic over `impl TradingApi` (or against
+/// `&dyn TradingApi`) and pass the real client in production and a stub in
+/// tests.
+pub trait TradingApi: Send + Sync {
+    fn get_account(&self) -> BoxFuture<'_, Result<AlpacaAccountResponse, AlpacaError>>;
+
+    /// Submit `order` as-is to `/v2/orders`.
+    fn submit_order<'a>(
+        &'a self,
+        order: &'a AlpacaOrderRequest,
+    ) -> BoxFuture<'a, Result<AlpacaOrderResponse, AlpacaError>>;
+
+    fn list_positions(&self) -> BoxFuture<'_, Result<Vec<AlpacaPositionResponse>, AlpacaError>>;
+
+    fn get_latest_quote<'a>(
+        &'a self,
+        symbol: &'a str,
+    ) -> BoxFuture<'a, Result<AlpacaQuoteResponse, AlpacaError>>;
+
+    /// Every bar for `symbol` between `start` and `end`, with the client's
+    /// default feed and the default (split) adjustment.
+    fn get_bars<'a>(
+        &'a self,
+        symbol: &'a str,
+        start: TimeBound,
+        end: TimeBound,
+        timeframe: TimeFrame,
+    ) -> BoxFuture<'a, Result<Vec<AlpacaBar>, AlpacaError>>;
+}
+
+impl TradingApi for AlpacaClient {
+    fn get_account(&self) -> BoxFuture<'_, Result<AlpacaAccountResponse, AlpacaError>> {
+        Box::pin(AlpacaClient::get_account(self))
+    }
+
+    fn submit_order<'a>(
+        &'a self,
+        order: &'a AlpacaOrderRequest,
+    ) -> BoxFuture<'a, Result<AlpacaOrderResponse, AlpacaError>> {
+        Box::pin(self.submit_order_request(order))
+    }
+
+    fn list_positions(&self) -> BoxFuture<'_, Result<Vec<AlpacaPositionResponse>, AlpacaError>> {
+        Box::pin(AlpacaClient::list_positions(self))
+    }
+
+    fn get_latest_quote<'a>(
+        &'a self,
+        symbol: &'a str,
+    ) -> BoxFuture<'a, Result<AlpacaQuoteResponse, AlpacaError>> {
+        Box::pin(AlpacaClient::get_latest_quote(self, symbol))
+    }
+
+    fn get_bars<'a>(
+        &'a self,
+        symbol: &'a str,
+        start: TimeBound,
+        end: TimeBound,
+        timeframe: TimeFrame,
+    ) -> BoxFuture<'a, Result<Vec<AlpacaBar>, AlpacaError>> {
+        Box::pin(async move {
+            AlpacaClient::get_bars(self, symbol, &BarsRequest::new(start, end, timeframe)).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::account_json;
+    use std::sync::Mutex;
+
+    use rust_decimal::Decimal;
+
+    /// Fixed account and quote; records submitted orders.
+    #[derive(Default)]
+    struct StubApi {
+        submitted: Mutex<Vec<AlpacaOrderRequest>>,
+    }
+
+    impl TradingApi for StubApi {
+        fn get_account(&self) -> BoxFuture<'_, Result<AlpacaAccountResponse, AlpacaError>> {
+            Box::pin(async { Ok(serde_json::from_value(account_json()).unwrap()) })
+        }
+
+        fn submit_order<'a>(
+            &'a self,
+            order: &'a AlpacaOrderRequest,
+        ) -> BoxFuture<'a, Result<AlpacaOrderResponse, AlpacaError>> {
+            self.submitted.lock().unwrap().push(order.clone());
+            Box::pin(async { Err(AlpacaError::Config("stub accepts no orders".into())) })
+        }
+
+        fn list_positions(
+            &self,
+        ) -> BoxFuture<'_, Result<Vec<AlpacaPositionResponse>, AlpacaError>> {
+            Box::pin(async { Ok(Vec::new()) })
+        }
+
+        fn get_latest_quote<'a>(
+            &'a self,
+            symbol: &'a str,
+        ) -> BoxFuture<'a, Result<AlpacaQuoteResponse, AlpacaError>> {
+            Box::pin(async move {
+                let quote = serde_json::from_str(
+                    r#"{"ap":"151","as":2,"bp":"149","bs":1,"t":"2024-06-03T15:00:00Z"}"#,
+                )
+                .unwrap();
+                Ok(AlpacaQuoteResponse {
+                    symbol: Some(symbol.to_string()),
+                    quote,
+                })
+            })
+        }
+
+        fn get_bars<'a>(
+            &'a self,
+            _symbol: &'a str,
+            _start: TimeBound,
+            _end: TimeBound,
+            _timeframe: TimeFrame,
+        ) -> BoxFuture<'a, Result<Vec<AlpacaBar>, AlpacaError>> {
+            Box::pin(async { Ok(Vec::new()) })
+        }
+    }
+
+    /// Buy as many whole shares at the ask as buying power allows, unless
+    /// already holding `symbol`.
+    async fn buy_with_available_cash(
+        api: &impl TradingApi,
+        symbol: &str,
+    ) -> Result<Option<i32>, AlpacaError> {
+        let positions = api.list_positions().await?;
+        if positions.iter().any(|p| p.symbol == symbol) {
+            return Ok(None);
+        }
+        let account = api.get_account().await?;
+        let ask = api.get_latest_quote(symbol).await?.quote.ask_price;
+        let qty: i32 = (account.buying_power / ask)
+            .floor()
+            .try_into()
+            .unwrap_or_default();
+        if qty == 0 {
+            return Ok(None);
+        }
+        let order = AlpacaOrderRequest {
+            symbol: symbol.to_string(),
+            qty,
+            side: "buy".into(),
+            order_type: "limit".into(),
+            time_in_force: "day".into(),
+            limit_price: Some(ask),
+            stop_price: None,
+            trail_percent: None,
+            trail_price: None,
+            extended_hours: false,
+            client_order_id: None,
+            order_class: None,
+            position_intent: None,
+        };
+        // The stub rejects every order; the strategy only cares that it was sent.
+        let _ = api.submit_order(&order).await;
+        Ok(Some(qty))
+    }
+
+    #[tokio::test]
+    async fn strategy_runs_against_stub() {
+        let stub = StubApi::default();
+        let qty = buy_with_available_cash(&stub, "AAPL").await.unwrap();
+
+        // 20000 / 151 = 132.4
+        assert_eq!(qty, Some(132));
+        let submitted = stub.submitted.lock().unwrap();
+        assert_eq!(submitted.len(), 1);
+        assert_eq!(submitted[0].symbol, "AAPL");
+        assert_eq!(submitted[0].limit_price, Some(Decimal::from(151)));
+    }
+
+    #[test]
+    fn client_is_usable_as_trait_object() {
+        let config = crate::AlpacaConfig::paper("key".into(), "secret".into());
+        let client = AlpacaClient::new(config).unwrap();
+        let _api: &dyn TradingApi = &client;
+    }
+}
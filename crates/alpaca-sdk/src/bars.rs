@@ -0,0 +1,167 @@
+//! Analytics over historical bar series, e.g. the output of
+//! [`AlpacaClient::get_bars`](crate::AlpacaClient::get_bars).
+//!
+//! All functions assume bars are in ascending time order.
+
+use rust_decimal::Decimal;
+
+use crate::types::AlpacaBar;
+
+/// Volume-weighted average price, using each bar's typical price
+/// `(high + low + close) / 3`. `None` for an empty series or zero volume.
+pub fn vwap(bars: &[AlpacaBar]) -> Option<Decimal> {
+    let three = Decimal::from(3);
+    let (notional, volume) =
+        bars.iter()
+            .fold((Decimal::ZERO, Decimal::ZERO), |(notional, volume), bar| {
+                let typical = (bar.high + bar.low + bar.close) / three;
+                let bar_volume = Decimal::from(bar.volume);
+                (notional + typical * bar_volume, volume + bar_volume)
+            });
+    (!volume.is_zero()).then(|| notional / volume)
+}
+
+/// Close-to-close simple returns; element `i` is the return from `bars[i]` to
+/// `bars[i + 1]`, so the result is one shorter than the input. A zero prior
+/// close yields a zero return.
+pub fn simple_returns(bars: &[AlpacaBar]) -> Vec<Decimal> {
+    bars.windows(2)
+        .map(|pair| {
+            let (prev, next) = (pair[0].close, pair[1].close);
+            (next - prev).checked_div(prev).unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Simple moving average of closes over `period` bars; element `i` averages
+/// `bars[i..i + period]`. Empty when `period` is zero or exceeds the series.
+pub fn sma(bars: &[AlpacaBar], period: usize) -> Vec<Decimal> {
+    if period == 0 {
+        return Vec::new();
+    }
+    let divisor = Decimal::from(period);
+    bars.windows(period)
+        .map(|window| window.iter().map(|bar| bar.close).sum::<Decimal>() / divisor)
+        .collect()
+}
+
+/// Aggregate every `factor` consecutive bars into one, e.g. 1Min bars with a
+/// factor of 5 into 5Min bars: open of the first, highest high, lowest low,
+/// close of the last, summed volume, stamped with the first bar's time.
+///
+/// A trailing window shorter than `factor` is emitted when `keep_partial` is
+/// set and dropped otherwise. Windows are counted from the first bar, so
+/// align the input to the coarser timeframe's boundaries first.
+pub fn resample_bars(bars: &[AlpacaBar], factor: usize, keep_partial: bool) -> Vec<AlpacaBar> {
+    if factor == 0 {
+        return Vec::new();
+    }
+    bars.chunks(factor)
+        .filter(|window| keep_partial || window.len() == factor)
+        .map(|window| {
+            let (first, last) = (&window[0], &window[window.len() - 1]);
+            AlpacaBar {
+                timestamp: first.timestamp,
+                open: first.open,
+                high: window.iter().map(|b| b.high).max().unwrap_or(first.high),
+                low: window.iter().map(|b| b.low).min().unwrap_or(first.low),
+                close: last.close,
+                volume: window.iter().map(|b| b.volume).sum(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn bar(day: u32, high: i64, low: i64, close: i64, volume: i64) -> AlpacaBar {
+        AlpacaBar {
+            timestamp: Utc.with_ymd_and_hms(2024, 6, day, 4, 0, 0).unwrap(),
+            open: Decimal::from(close),
+            high: Decimal::from(high),
+            low: Decimal::from(low),
+            close: Decimal::from(close),
+            volume,
+        }
+    }
+
+    fn series() -> Vec<AlpacaBar> {
+        vec![
+            bar(3, 12, 8, 10, 100),  // typical 10
+            bar(4, 13, 10, 10, 300), // typical 11
+            bar(5, 16, 11, 12, 100), // typical 13
+            bar(6, 16, 15, 14, 0),   // typical 15, no volume
+        ]
+    }
+
+    #[test]
+    fn vwap_weights_typical_price_by_volume() {
+        // (10*100 + 11*300 + 13*100) / 500 = 5600 / 500
+        assert_eq!(vwap(&series()), Some(Decimal::new(112, 1)));
+        assert_eq!(vwap(&[]), None);
+        assert_eq!(vwap(&series()[3..]), None);
+    }
+
+    #[test]
+    fn simple_returns_between_closes() {
+        let returns = simple_returns(&series());
+        // 10 -> 10 -> 12 -> 14
+        assert_eq!(returns.len(), 3);
+        assert_eq!(returns[0], Decimal::ZERO);
+        assert_eq!(returns[1], Decimal::new(2, 1));
+        assert_eq!(returns[2].round_dp(6), Decimal::new(166667, 6));
+        assert!(simple_returns(&series()[..1]).is_empty());
+    }
+
+    #[test]
+    fn sma_over_closes() {
+        let averages = sma(&series(), 2);
+        assert_eq!(averages, vec![10.into(), 11.into(), 13.into()]);
+        assert_eq!(sma(&series(), 4), vec![Decimal::new(115, 1)]);
+        assert!(sma(&series(), 5).is_empty());
+        assert!(sma(&series(), 0).is_empty());
+    }
+
+    #[test]
+    fn resample_minutes_into_six_minute_bars() {
+        let start = Utc.with_ymd_and_hms(2024, 6, 3, 13, 30, 0).unwrap();
+        let minutes: Vec<AlpacaBar> = (0..12)
+            .map(|i| AlpacaBar {
+                timestamp: start + chrono::Duration::minutes(i),
+                open: Decimal::from(100 + i),
+                high: Decimal::from(102 + i),
+                low: Decimal::from(99 + i),
+                close: Decimal::from(101 + i),
+                volume: 10 * (i + 1),
+            })
+            .collect();
+
+        let resampled = resample_bars(&minutes, 6, false);
+        assert_eq!(resampled.len(), 2);
+
+        let first = &resampled[0];
+        assert_eq!(first.timestamp, start);
+        assert_eq!(first.open, Decimal::from(100));
+        assert_eq!(first.high, Decimal::from(107));
+        assert_eq!(first.low, Decimal::from(99));
+        assert_eq!(first.close, Decimal::from(106));
+        assert_eq!(first.volume, 210);
+
+        let second = &resampled[1];
+        assert_eq!(second.timestamp, start + chrono::Duration::minutes(6));
+        assert_eq!(second.open, Decimal::from(106));
+        assert_eq!(second.high, Decimal::from(113));
+        assert_eq!(second.low, Decimal::from(105));
+        assert_eq!(second.close, Decimal::from(112));
+        assert_eq!(second.volume, 570);
+
+        assert_eq!(resample_bars(&minutes, 5, false).len(), 2);
+        let with_partial = resample_bars(&minutes, 5, true);
+        assert_eq!(with_partial.len(), 3);
+        assert_eq!(with_partial[2].volume, 110 + 120);
+        assert!(resample_bars(&minutes, 0, true).is_empty());
+    }
+}
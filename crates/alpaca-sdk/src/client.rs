@@ -1,24 +1,68 @@
-use api_client_core::{paginate, RestClient};
-use chrono::NaiveDate;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use api_client_core::{
+    encode_path_segment, paginate_by_last_id, paginate_page, paginate_stream, push_query_param,
+    HttpTransport, ParseMode, RateLimiter, RequestObserver, RestClient,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use futures_util::{future, stream, Stream, StreamExt, TryStreamExt};
 use reqwest::header::HeaderMap;
 use rust_decimal::Decimal;
-use tracing::debug;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tracing::{debug, warn};
 
-use crate::config::AlpacaConfig;
+use crate::clock::{elapsed_since, Clock, SystemClock};
+use crate::config::{normalize_base_url, AlpacaConfig};
 use crate::error::AlpacaError;
+use crate::export::{encode_bar, encode_csv_header, BarFormat, BarWriter};
 use crate::types::*;
 
+const CORRELATION_ID_HEADER: &str = "X-Correlation-ID";
+/// Cancel requests [`AlpacaClient::cancel_orders`] keeps in flight at once.
+const CANCEL_CONCURRENCY: usize = 4;
+/// Largest `limit` the orders endpoint accepts.
+const MAX_ORDERS_PAGE_SIZE: u32 = 500;
+
 /// Async client for the Alpaca Trading and Market Data APIs.
 ///
 /// Built on `api_client_core::RestClient` for standardized HTTP handling.
+/// Clones are cheap and share one connection pool, so a clone can be handed
+/// to each task instead of wrapping the client in an `Arc`.
+#[derive(Clone)]
 pub struct AlpacaClient {
     trading: RestClient,
     market_data: RestClient,
+    crypto_data: RestClient,
+    clock: Arc<dyn Clock>,
     config: AlpacaConfig,
 }
 
 impl AlpacaClient {
+    /// Build a client for `config`. Fails with [`AlpacaError::Config`] if a
+    /// base URL isn't an absolute http(s) URL; trailing slashes are dropped.
     pub fn new(config: AlpacaConfig) -> Result<Self, AlpacaError> {
+        Self::build(config, None)
+    }
+
+    /// Build a client whose trading and market data requests go through
+    /// `transport`, e.g. an `api_client_core::MockTransport` in tests.
+    pub fn with_transport(
+        config: AlpacaConfig,
+        transport: Arc<dyn HttpTransport>,
+    ) -> Result<Self, AlpacaError> {
+        Self::build(config, Some(transport))
+    }
+
+    fn build(
+        mut config: AlpacaConfig,
+        transport: Option<Arc<dyn HttpTransport>>,
+    ) -> Result<Self, AlpacaError> {
+        config.trading_base_url = normalize_base_url("trading base URL", &config.trading_base_url)?;
+        config.market_data_base_url =
+            normalize_base_url("market data base URL", &config.market_data_base_url)?;
         let mut headers = HeaderMap::new();
         headers.insert(
             "APCA-API-KEY-ID",
@@ -39,36 +83,199 @@ impl AlpacaClient {
                 })?,
         );
 
-        let trading = RestClient::builder(&config.trading_base_url)
-            .default_headers(headers.clone())
-            .timeout(std::time::Duration::from_secs(30))
-            .build()
-            .map_err(AlpacaError::from)?;
-
-        let market_data = RestClient::builder(&config.market_data_base_url)
-            .default_headers(headers)
-            .timeout(std::time::Duration::from_secs(30))
-            .build()
-            .map_err(AlpacaError::from)?;
+        let builder = |base_url: &str| {
+            let mut builder = RestClient::builder(base_url)
+                .default_headers(headers.clone())
+                .timeout(config.timeout)
+                .user_agent(&config.user_agent);
+            if let Some(proxy) = &config.proxy {
+                builder = builder.proxy(proxy.as_str());
+            }
+            for pem in &config.root_certificates {
+                builder = builder.add_root_certificate(pem);
+            }
+            match &transport {
+                Some(transport) => builder.transport(Arc::clone(transport)),
+                None => builder,
+            }
+        };
+        let trading = builder(&config.trading_base_url).build()?;
+        let market_data = builder(&config.market_data_base_url).build()?;
+        let crypto_data = market_data.clone();
 
         Ok(Self {
             trading,
             market_data,
+            crypto_data,
+            clock: Arc::new(SystemClock),
+            config,
+        })
+    }
+
+    /// A client sharing this one's connections, transport and settings
+    /// whose requests time out after `timeout` unless a call overrides it.
+    pub fn with_timeout(&self, timeout: Duration) -> Self {
+        let mut config = self.config.clone();
+        config.timeout = timeout;
+        Self {
+            trading: self.trading.with_timeout(timeout),
+            market_data: self.market_data.with_timeout(timeout),
+            crypto_data: self.crypto_data.with_timeout(timeout),
             config,
+            ..self.clone()
+        }
+    }
+
+    /// A client sharing this one's connections that tags every request with
+    /// an `X-Correlation-ID` header, so calls can be traced back to a caller.
+    pub fn with_correlation_id(&self, correlation_id: &str) -> Result<Self, AlpacaError> {
+        Ok(Self {
+            trading: self
+                .trading
+                .with_header(CORRELATION_ID_HEADER, correlation_id)?,
+            market_data: self
+                .market_data
+                .with_header(CORRELATION_ID_HEADER, correlation_id)?,
+            crypto_data: self
+                .crypto_data
+                .with_header(CORRELATION_ID_HEADER, correlation_id)?,
+            ..self.clone()
+        })
+    }
+
+    /// A client sharing this one's connections whose trading and market data
+    /// requests both draw from `limiter`, e.g. `RateLimiter::per_minute(200)`.
+    pub fn with_rate_limiter(&self, limiter: Arc<RateLimiter>) -> Self {
+        Self {
+            trading: self.trading.with_rate_limiter(Some(Arc::clone(&limiter))),
+            market_data: self
+                .market_data
+                .with_rate_limiter(Some(Arc::clone(&limiter))),
+            crypto_data: self.crypto_data.with_rate_limiter(Some(limiter)),
+            ..self.clone()
+        }
+    }
+
+    /// A client sharing this one's connections that parses responses with
+    /// `mode`; `ParseMode::Strict` fails on fields the SDK doesn't model.
+    pub fn with_parse_mode(&self, mode: ParseMode) -> Self {
+        Self {
+            trading: self.trading.with_parse_mode(mode),
+            market_data: self.market_data.with_parse_mode(mode),
+            crypto_data: self.crypto_data.with_parse_mode(mode),
+            ..self.clone()
+        }
+    }
+
+    /// A client sharing this one's connections that reports every trading and
+    /// market data request to `observer`, e.g. to export metrics.
+    pub fn with_observer(&self, observer: Arc<dyn RequestObserver>) -> Self {
+        Self {
+            trading: self.trading.with_observer(Arc::clone(&observer)),
+            market_data: self.market_data.with_observer(Arc::clone(&observer)),
+            crypto_data: self.crypto_data.with_observer(observer),
+            ..self.clone()
+        }
+    }
+
+    /// A client sharing this one's connections whose stock market data
+    /// requests (quotes, trades, bars, snapshots, screeners) go to `url`
+    /// instead of `config.market_data_base_url`, e.g. a caching proxy.
+    /// Crypto requests are unaffected; see
+    /// [`with_crypto_data_url`](Self::with_crypto_data_url).
+    ///
+    /// `url` is validated and stripped of trailing slashes like the config's
+    /// base URLs.
+    pub fn with_market_data_url(&self, url: impl Into<String>) -> Result<Self, AlpacaError> {
+        let url = normalize_base_url("market data URL", &url.into())?;
+        Ok(Self {
+            market_data: self.market_data.with_base_url(url),
+            ..self.clone()
+        })
+    }
+
+    /// A client sharing this one's connections whose crypto market data
+    /// requests go to `url`. Defaults to `config.market_data_base_url`.
+    /// `url` is validated like [`with_market_data_url`](Self::with_market_data_url)'s.
+    pub fn with_crypto_data_url(&self, url: impl Into<String>) -> Result<Self, AlpacaError> {
+        let url = normalize_base_url("crypto data URL", &url.into())?;
+        Ok(Self {
+            crypto_data: self.crypto_data.with_base_url(url),
+            ..self.clone()
         })
     }
 
+    /// A client sharing this one's connections that reads the time and
+    /// sleeps through `clock`, e.g. a `MockClock` to test polling helpers
+    /// without waiting.
+    pub fn with_clock(&self, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            clock,
+            ..self.clone()
+        }
+    }
+
     /// Returns the underlying config (useful for WebSocket auth).
     pub fn config(&self) -> &AlpacaConfig {
         &self.config
     }
 
+    /// GET a trading API path and return the untouched JSON, e.g. to capture
+    /// fields the SDK's types don't model yet.
+    pub async fn get_raw(&self, path: &str) -> Result<serde_json::Value, AlpacaError> {
+        Ok(self.trading.get_raw(path).await?)
+    }
+
+    /// GET a market data API path and return the untouched JSON.
+    pub async fn get_market_data_raw(&self, path: &str) -> Result<serde_json::Value, AlpacaError> {
+        Ok(self.market_data.get_raw(path).await?)
+    }
+
+    /// The requested feed, or the configured default.
+    fn feed<'a>(&self, feed: Option<&'a str>) -> &'a str {
+        feed.unwrap_or(self.config.default_feed.as_str())
+    }
+
     // ── Account ──────────────────────────────────────────────────────
 
     pub async fn get_account(&self) -> Result<AlpacaAccountResponse, AlpacaError> {
         Ok(self.trading.get("/v2/account").await?)
     }
 
+    /// Fetch account activities, newest first, following the last-id cursor
+    /// across pages. `activity_types` is a comma-separated filter such as
+    /// `"FILL,DIV"`; `page_size` defaults to 100.
+    pub async fn get_account_activities(
+        &self,
+        activity_types: Option<&str>,
+        page_size: Option<u32>,
+    ) -> Result<Vec<AlpacaAccountActivity>, AlpacaError> {
+        let page_size = page_size.unwrap_or(100);
+        let page_size_str = page_size.to_string();
+        let client = &self.trading;
+        let fetch_page = |page_token: Option<String>| {
+            let page_size_str = &page_size_str;
+            async move {
+                let mut query = vec![("direction", "desc"), ("page_size", page_size_str.as_str())];
+                if let Some(types) = activity_types {
+                    query.push(("activity_types", types));
+                }
+                if let Some(ref token) = page_token {
+                    query.push(("page_token", token.as_str()));
+                }
+                client
+                    .get_with_query("/v2/account/activities", &query)
+                    .await
+            }
+        };
+        Ok(paginate_by_last_id(
+            fetch_page,
+            |activity: &AlpacaAccountActivity| activity.id.clone(),
+            page_size as usize,
+        )
+        .await?)
+    }
+
     // ── Orders ───────────────────────────────────────────────────────
 
     #[allow(clippy::too_many_arguments)]
@@ -89,36 +296,265 @@ impl AlpacaClient {
             order_type: order_type.to_string(),
             time_in_force: time_in_force.to_string(),
             limit_price,
+            stop_price: None,
+            trail_percent: None,
+            trail_price: None,
             extended_hours,
+            client_order_id: None,
+            order_class: None,
+            position_intent: None,
         };
+        validate_order(&body)?;
         debug!("submit_order symbol={symbol} qty={qty} side={side}");
         Ok(self.trading.post("/v2/orders", &body).await?)
     }
 
+    /// Submit a fully specified order, e.g. one with an `order_class` or
+    /// `client_order_id` that [`submit_order`](Self::submit_order) doesn't set.
+    pub async fn submit_order_request(
+        &self,
+        order: &AlpacaOrderRequest,
+    ) -> Result<AlpacaOrderResponse, AlpacaError> {
+        validate_order(order)?;
+        debug!(
+            "submit_order symbol={} qty={} side={}",
+            order.symbol, order.qty, order.side
+        );
+        Ok(self.trading.post("/v2/orders", order).await?)
+    }
+
+    /// Submit an order built with [`OrderBuilder`].
+    pub async fn submit(&self, order: OrderBuilder) -> Result<AlpacaOrderResponse, AlpacaError> {
+        self.submit_order_request(&order.build()).await
+    }
+
+    /// Submit a multi-leg (`mleg`) options order such as a vertical spread or
+    /// iron condor. Needs at least two legs, each with a positive `ratio_qty`;
+    /// the order must be a `day` order of type `market` or `limit`, and a
+    /// limit order needs a `limit_price`.
+    pub async fn submit_multileg_order(
+        &self,
+        legs: Vec<OptionLeg>,
+        qty: i32,
+        order_type: &str,
+        time_in_force: &str,
+        limit_price: Option<Decimal>,
+    ) -> Result<AlpacaOrderResponse, AlpacaError> {
+        validate_multileg(order_type, time_in_force, limit_price, &legs)?;
+        let body = AlpacaMultilegOrderRequest {
+            order_class: "mleg".to_string(),
+            qty,
+            order_type: order_type.to_string(),
+            time_in_force: time_in_force.to_string(),
+            limit_price,
+            legs,
+        };
+        debug!("submit_multileg_order legs={} qty={qty}", body.legs.len());
+        Ok(self.trading.post("/v2/orders", &body).await?)
+    }
+
+    /// Submit an order tagged with `client_order_id`, recovering from
+    /// ambiguous failures. `order.client_order_id` must be set, e.g. with
+    /// [`OrderBuilder::client_order_id`].
+    ///
+    /// If the request fails at the transport level (timeout, dropped
+    /// connection) the order may or may not have been accepted, so the order
+    /// is looked up by `client_order_id`: an existing order is returned, and
+    /// if none exists the submission is retried once. API errors (4xx/5xx) are
+    /// returned as-is without a retry.
+    pub async fn submit_order_idempotent(
+        &self,
+        order: &AlpacaOrderRequest,
+    ) -> Result<AlpacaOrderResponse, AlpacaError> {
+        let Some(client_order_id) = order.client_order_id.as_deref() else {
+            return Err(AlpacaError::Config(
+                "invalid order: idempotent submission needs a client_order_id".into(),
+            ));
+        };
+        validate_order(order)?;
+        debug!(
+            "submit_order_idempotent symbol={} qty={} client_order_id={client_order_id}",
+            order.symbol, order.qty
+        );
+        submit_with_recovery(
+            || async { Ok(self.trading.post("/v2/orders", order).await?) },
+            || self.get_order_by_client_id(client_order_id),
+        )
+        .await
+    }
+
     pub async fn get_order(&self, order_id: &str) -> Result<AlpacaOrderResponse, AlpacaError> {
+        let order_id = encode_path_segment(order_id);
         Ok(self.trading.get(&format!("/v2/orders/{order_id}")).await?)
     }
 
+    /// Follow `replaced_by` links from `order_id` to the latest order in its
+    /// cancel-replace chain. The result starts with `order_id` itself and
+    /// ends with the order that hasn't been replaced. A link back to an
+    /// order already visited ends the walk.
+    pub async fn get_order_chain(
+        &self,
+        order_id: &str,
+    ) -> Result<Vec<AlpacaOrderResponse>, AlpacaError> {
+        let mut chain: Vec<AlpacaOrderResponse> = Vec::new();
+        let mut next = Some(order_id.to_string());
+        while let Some(id) = next.take() {
+            if chain.iter().any(|order| order.id == id) {
+                warn!(order_id = %id, "cycle in order replacement chain");
+                break;
+            }
+            let order = self.get_order(&id).await?;
+            next = order.replaced_by.clone();
+            chain.push(order);
+        }
+        Ok(chain)
+    }
+
+    /// Poll [`get_order`](Self::get_order) every `poll_interval` until the
+    /// order reaches a terminal status (see
+    /// [`AlpacaOrderResponse::is_terminal`]), failing with
+    /// [`AlpacaError::Timeout`] after `timeout`.
+    pub async fn wait_for_fill(
+        &self,
+        order_id: &str,
+        timeout: Duration,
+        poll_interval: Duration,
+    ) -> Result<AlpacaOrderResponse, AlpacaError> {
+        let start = self.clock.now();
+        loop {
+            let order = self.get_order(order_id).await?;
+            if order.is_terminal() {
+                return Ok(order);
+            }
+            let remaining = timeout.saturating_sub(elapsed_since(&*self.clock, start));
+            if remaining.is_zero() {
+                return Err(AlpacaError::Timeout(timeout));
+            }
+            debug!(order_id, status = %order.status, "order not done, polling again");
+            self.clock.sleep(poll_interval.min(remaining)).await;
+        }
+    }
+
+    /// Look up an order by the `client_order_id` it was submitted with.
+    pub async fn get_order_by_client_id(
+        &self,
+        client_order_id: &str,
+    ) -> Result<AlpacaOrderResponse, AlpacaError> {
+        Ok(self
+            .trading
+            .get_with_query(
+                "/v2/orders:by_client_order_id",
+                &[("client_order_id", client_order_id)],
+            )
+            .await?)
+    }
+
     pub async fn list_orders(
         &self,
         status: Option<&str>,
     ) -> Result<Vec<AlpacaOrderResponse>, AlpacaError> {
-        let path = match status {
-            Some(s) => format!("/v2/orders?status={s}"),
-            None => "/v2/orders".to_string(),
-        };
+        let mut path = "/v2/orders".to_string();
+        if let Some(s) = status {
+            push_query_param(&mut path, "status", s);
+        }
         Ok(self.trading.get(&path).await?)
     }
 
+    /// Fetch every order matching `params`, newest first, across as many
+    /// requests as needed.
+    ///
+    /// The orders endpoint has no page token, so this walks backwards in
+    /// time, stopping after a page shorter than the limit. `until` is
+    /// exclusive, so each window ends just after the oldest `submitted_at`
+    /// seen so far: orders sharing that timestamp which didn't fit on the
+    /// page are fetched by the next request, and ones already returned are
+    /// dropped by id. If a whole page shares one timestamp the walk steps
+    /// past it, so only more than `limit` orders in the same instant can be
+    /// missed.
+    pub async fn list_all_orders(
+        &self,
+        params: &ListOrdersParams,
+    ) -> Result<Vec<AlpacaOrderResponse>, AlpacaError> {
+        let limit = params
+            .limit
+            .unwrap_or(MAX_ORDERS_PAGE_SIZE)
+            .clamp(1, MAX_ORDERS_PAGE_SIZE);
+        let mut base_path = "/v2/orders".to_string();
+        if let Some(status) = &params.status {
+            push_query_param(&mut base_path, "status", status);
+        }
+        push_query_param(&mut base_path, "direction", "desc");
+        push_query_param(&mut base_path, "limit", limit);
+        if let Some(after) = params.after {
+            push_query_param(&mut base_path, "after", order_cursor(after));
+        }
+        if !params.symbols.is_empty() {
+            push_query_param(&mut base_path, "symbols", params.symbols.join(","));
+        }
+
+        let mut orders = Vec::new();
+        let mut seen = HashSet::new();
+        let mut until = params.until;
+        let mut previous_oldest: Option<DateTime<Utc>> = None;
+        loop {
+            let mut path = base_path.clone();
+            if let Some(until) = until {
+                push_query_param(&mut path, "until", order_cursor(until));
+            }
+            let page: Vec<AlpacaOrderResponse> = self.trading.get(&path).await?;
+            let full_page = page.len() >= limit as usize;
+            let oldest = page
+                .iter()
+                .map(|order| order.submitted_at.unwrap_or(order.created_at))
+                .min();
+            orders.extend(
+                page.into_iter()
+                    .filter(|order| seen.insert(order.id.clone())),
+            );
+
+            let Some(oldest) = oldest.filter(|_| full_page) else {
+                break;
+            };
+            until = if previous_oldest.is_some_and(|previous| oldest >= previous) {
+                // The whole page shares the previous boundary timestamp:
+                // step past it rather than request the same window forever.
+                if until == Some(oldest) {
+                    break;
+                }
+                Some(oldest)
+            } else {
+                Some(oldest + chrono::Duration::nanoseconds(1))
+            };
+            previous_oldest = Some(oldest);
+        }
+        Ok(orders)
+    }
+
     pub async fn cancel_order(&self, order_id: &str) -> Result<(), AlpacaError> {
         Ok(self
             .trading
-            .delete(&format!("/v2/orders/{order_id}"))
+            .delete(&format!("/v2/orders/{}", encode_path_segment(order_id)))
             .await?)
     }
 
-    pub async fn cancel_all_orders(&self) -> Result<(), AlpacaError> {
-        Ok(self.trading.delete("/v2/orders").await?)
+    /// Cancel specific orders, at most four requests in flight. Returns each
+    /// id with its outcome, in input order, so a failure on one (e.g. a 404
+    /// for an order that already filled) doesn't hide the others.
+    pub async fn cancel_orders(
+        &self,
+        order_ids: &[&str],
+    ) -> Vec<(String, Result<(), AlpacaError>)> {
+        stream::iter(order_ids)
+            .map(|&order_id| async move { (order_id.to_string(), self.cancel_order(order_id).await) })
+            .buffered(CANCEL_CONCURRENCY)
+            .collect()
+            .await
+    }
+
+    /// Cancel every open order. Alpaca answers `207 Multi-Status` with one
+    /// entry per order; check each entry's `status` to see which succeeded.
+    pub async fn cancel_all_orders(&self) -> Result<Vec<AlpacaCancelOrderStatus>, AlpacaError> {
+        Ok(self.trading.delete_parsed("/v2/orders").await?)
     }
 
     pub async fn replace_order(
@@ -135,7 +571,10 @@ impl AlpacaClient {
         };
         Ok(self
             .trading
-            .patch(&format!("/v2/orders/{order_id}"), &body)
+            .patch(
+                &format!("/v2/orders/{}", encode_path_segment(order_id)),
+                &body,
+            )
             .await?)
     }
 
@@ -145,10 +584,22 @@ impl AlpacaClient {
         Ok(self.trading.get("/v2/positions").await?)
     }
 
+    /// Liquidate every position, optionally canceling open orders first.
+    /// Like [`cancel_all_orders`](Self::cancel_all_orders), the result has one
+    /// entry per position with its own HTTP status.
+    pub async fn close_all_positions(
+        &self,
+        cancel_orders: bool,
+    ) -> Result<Vec<AlpacaClosePositionStatus>, AlpacaError> {
+        let mut path = "/v2/positions".to_string();
+        push_query_param(&mut path, "cancel_orders", cancel_orders);
+        Ok(self.trading.delete_parsed(&path).await?)
+    }
+
     pub async fn close_position(&self, symbol: &str) -> Result<AlpacaOrderResponse, AlpacaError> {
         Ok(self
             .trading
-            .delete_parsed(&format!("/v2/positions/{symbol}"))
+            .delete_parsed(&format!("/v2/positions/{}", encode_path_segment(symbol)))
             .await?)
     }
 
@@ -170,9 +621,26 @@ impl AlpacaClient {
     }
 
     pub async fn get_asset(&self, symbol: &str) -> Result<AlpacaAssetResponse, AlpacaError> {
+        let symbol = encode_path_segment(symbol);
         Ok(self.trading.get(&format!("/v2/assets/{symbol}")).await?)
     }
 
+    /// Whether `symbol` can be sold short right now. Alpaca only shorts
+    /// easy-to-borrow names, so this needs both `shortable` and
+    /// `easy_to_borrow`.
+    pub async fn is_shortable(&self, symbol: &str) -> Result<bool, AlpacaError> {
+        let asset = self.get_asset(symbol).await?;
+        Ok(asset.shortable && asset.easy_to_borrow)
+    }
+
+    pub async fn is_fractionable(&self, symbol: &str) -> Result<bool, AlpacaError> {
+        Ok(self.get_asset(symbol).await?.fractionable)
+    }
+
+    pub async fn is_tradable(&self, symbol: &str) -> Result<bool, AlpacaError> {
+        Ok(self.get_asset(symbol).await?.tradable)
+    }
+
     // ── Calendar & Clock ─────────────────────────────────────────────
 
     pub async fn get_calendar(
@@ -198,127 +666,2741 @@ impl AlpacaClient {
         Ok(self.trading.get("/v2/clock").await?)
     }
 
+    pub async fn is_market_open(&self) -> Result<bool, AlpacaError> {
+        Ok(self.get_clock().await?.is_open)
+    }
+
+    pub async fn next_market_open(&self) -> Result<DateTime<Utc>, AlpacaError> {
+        Ok(self.get_clock().await?.next_open)
+    }
+
+    pub async fn next_market_close(&self) -> Result<DateTime<Utc>, AlpacaError> {
+        Ok(self.get_clock().await?.next_close)
+    }
+
+    /// Time until the next open, measured from the clock's own `timestamp`
+    /// rather than local time so local clock skew doesn't matter. Zero while
+    /// the market is open.
+    pub async fn time_until_open(&self) -> Result<Duration, AlpacaError> {
+        Ok(self.get_clock().await?.time_until_open())
+    }
+
+    /// Return once the market is open, re-checking the clock every
+    /// `poll_interval`. Returns immediately if it already is. With `max_wait`
+    /// set, gives up with [`AlpacaError::Timeout`] once it has elapsed.
+    ///
+    /// Cancellation-safe: dropping the future just stops polling.
+    pub async fn wait_until_open(
+        &self,
+        poll_interval: Duration,
+        max_wait: Option<Duration>,
+    ) -> Result<(), AlpacaError> {
+        let start = self.clock.now();
+        loop {
+            if self.is_market_open().await? {
+                return Ok(());
+            }
+            let sleep = match max_wait {
+                Some(max_wait) => {
+                    let remaining = max_wait.saturating_sub(elapsed_since(&*self.clock, start));
+                    if remaining.is_zero() {
+                        return Err(AlpacaError::Timeout(max_wait));
+                    }
+                    poll_interval.min(remaining)
+                }
+                None => poll_interval,
+            };
+            debug!(?sleep, "market closed, waiting to re-check clock");
+            self.clock.sleep(sleep).await;
+        }
+    }
+
     // ── Market Data ──────────────────────────────────────────────────
 
     pub async fn get_latest_quote(&self, symbol: &str) -> Result<AlpacaQuoteResponse, AlpacaError> {
         Ok(self
             .market_data
-            .get(&format!("/v2/stocks/{symbol}/quotes/latest"))
+            .get(&format!(
+                "/v2/stocks/{}/quotes/latest",
+                encode_path_segment(symbol)
+            ))
             .await?)
     }
 
     pub async fn get_latest_trade(&self, symbol: &str) -> Result<AlpacaTradeResponse, AlpacaError> {
         Ok(self
             .market_data
-            .get(&format!("/v2/stocks/{symbol}/trades/latest"))
+            .get(&format!(
+                "/v2/stocks/{}/trades/latest",
+                encode_path_segment(symbol)
+            ))
+            .await?)
+    }
+
+    /// Latest US crypto quotes for pairs such as `"BTC/USD"`, keyed by pair.
+    pub async fn get_latest_crypto_quotes(
+        &self,
+        symbols: &[&str],
+    ) -> Result<AlpacaCryptoQuotesResponse, AlpacaError> {
+        let symbols = symbols.join(",");
+        Ok(self
+            .crypto_data
+            .get_with_query(
+                "/v1beta3/crypto/us/latest/quotes",
+                &[("symbols", symbols.as_str())],
+            )
+            .await?)
+    }
+
+    /// Latest US crypto order books (bid and ask depth) for pairs such as
+    /// `"BTC/USD"`, keyed by pair.
+    pub async fn get_crypto_orderbook(
+        &self,
+        symbols: &[&str],
+    ) -> Result<AlpacaCryptoOrderbooksResponse, AlpacaError> {
+        let symbols = symbols.join(",");
+        Ok(self
+            .crypto_data
+            .get_with_query(
+                "/v1beta3/crypto/us/latest/orderbooks",
+                &[("symbols", symbols.as_str())],
+            )
             .await?)
     }
 
     pub async fn get_snapshot(&self, symbol: &str) -> Result<AlpacaSnapshot, AlpacaError> {
         Ok(self
             .market_data
-            .get(&format!("/v2/stocks/{symbol}/snapshot"))
+            .get(&format!(
+                "/v2/stocks/{}/snapshot",
+                encode_path_segment(symbol)
+            ))
+            .await?)
+    }
+
+    /// Fetch snapshots for many symbols with at most `concurrency` requests in
+    /// flight. A failing symbol is reported in its own entry instead of
+    /// aborting the batch.
+    pub async fn get_snapshots_concurrent(
+        &self,
+        symbols: &[&str],
+        concurrency: usize,
+    ) -> HashMap<String, Result<AlpacaSnapshot, AlpacaError>> {
+        fetch_concurrent(symbols, concurrency, |symbol| self.get_snapshot(symbol)).await
+    }
+
+    /// Top stock gainers and losers for the day. `top` defaults to 10 server-side.
+    pub async fn get_movers(&self, top: Option<u32>) -> Result<AlpacaMoversResponse, AlpacaError> {
+        let top_str = top.map(|t| t.to_string());
+        let mut query: Vec<(&str, &str)> = Vec::new();
+        if let Some(ref t) = top_str {
+            query.push(("top", t.as_str()));
+        }
+        Ok(self
+            .market_data
+            .get_with_query("/v1beta1/screener/stocks/movers", &query)
+            .await?)
+    }
+
+    /// Most active stocks ranked `by` `"volume"` or `"trades"`.
+    pub async fn get_most_actives(
+        &self,
+        by: &str,
+        top: Option<u32>,
+    ) -> Result<AlpacaMostActivesResponse, AlpacaError> {
+        let top_str = top.map(|t| t.to_string());
+        let mut query = vec![("by", by)];
+        if let Some(ref t) = top_str {
+            query.push(("top", t.as_str()));
+        }
+        Ok(self
+            .market_data
+            .get_with_query("/v1beta1/screener/stocks/most-actives", &query)
             .await?)
     }
 
     /// Fetch historical bars for a single symbol with auto-pagination.
-    #[allow(clippy::too_many_arguments)]
+    ///
+    /// `request.page_size` is the number of bars requested per API call and
+    /// does not limit the result; `request.max_bars` caps the total number of
+    /// bars returned across all pages. Pages are concatenated in the order
+    /// returned, so [`SortDirection::Desc`] with `max_bars` yields the most
+    /// recent N bars. See [`BarsRequest`] for the other options.
+    ///
+    /// A bar repeated across a page boundary is returned once, so timestamps
+    /// are unique.
     pub async fn get_bars(
         &self,
         symbol: &str,
-        start: NaiveDate,
-        end: NaiveDate,
-        timeframe: &str,
-        feed: Option<&str>,
-        adjustment: Option<&str>,
-        limit: Option<u32>,
+        request: &BarsRequest,
     ) -> Result<Vec<AlpacaBar>, AlpacaError> {
-        let limit = limit.unwrap_or(10000);
-        let adjustment = adjustment.unwrap_or("split");
-        let feed = feed.unwrap_or("iex");
-        let base_path = format!(
-            "/v2/stocks/{symbol}/bars?start={start}&end={end}&timeframe={timeframe}&adjustment={adjustment}&feed={feed}&limit={limit}"
-        );
+        validate_range(request.start, request.end, self.clock.now().date_naive())?;
+        let limit = request_page_size(request.page_size, request.max_bars);
+        let base_path = bars_path(symbol, request, self.feed(request.feed.as_deref()), limit);
+
+        let client = &self.market_data;
+        let timeout = request.timeout;
+        let fetch_page = |page_token: Option<String>| {
+            let mut path = base_path.clone();
+            if let Some(ref token) = page_token {
+                push_query_param(&mut path, "page_token", token);
+            }
+            async move {
+                let resp: AlpacaSingleSymbolBarsResponse =
+                    client.get_with_timeout(&path, timeout).await?;
+                Ok((resp.bars, resp.next_page_token))
+            }
+        };
+        collect_unique(fetch_page, request.max_bars, |bar| bar.timestamp).await
+    }
+
+    /// Fetch a single page of historical bars starting at `page_token`.
+    ///
+    /// Returns the bars and the token for the next page (`None` when done).
+    /// Persist the token to checkpoint a long pull and resume it later.
+    /// `request.max_bars` is ignored; the page holds up to `page_size` bars.
+    pub async fn get_bars_page(
+        &self,
+        symbol: &str,
+        request: &BarsRequest,
+        page_token: Option<&str>,
+    ) -> Result<(Vec<AlpacaBar>, Option<String>), AlpacaError> {
+        validate_range(request.start, request.end, self.clock.now().date_naive())?;
+        let limit = request_page_size(request.page_size, None);
+        let base_path = bars_path(symbol, request, self.feed(request.feed.as_deref()), limit);
+
+        let client = &self.market_data;
+        let timeout = request.timeout;
+        let fetch_page = |page_token: Option<String>| async move {
+            let mut path = base_path;
+            if let Some(ref token) = page_token {
+                push_query_param(&mut path, "page_token", token);
+            }
+            let resp: AlpacaSingleSymbolBarsResponse =
+                client.get_with_timeout(&path, timeout).await?;
+            Ok((resp.bars, resp.next_page_token))
+        };
+        Ok(paginate_page(fetch_page, page_token.map(str::to_string)).await?)
+    }
+
+    /// Stream historical bars for a single symbol, fetching pages on demand.
+    ///
+    /// Unlike [`get_bars`](Self::get_bars), bars are yielded as each page
+    /// arrives instead of being collected into memory first. As with
+    /// `get_bars`, a bar repeated across a page boundary is yielded once (the
+    /// later copy), so each bar is held back until the next one arrives.
+    /// `request.max_bars` caps the number of unique bars yielded.
+    pub fn get_bars_stream<'a>(
+        &'a self,
+        symbol: &str,
+        request: &BarsRequest,
+    ) -> impl Stream<Item = Result<AlpacaBar, AlpacaError>> + 'a {
+        let range_check = validate_range(request.start, request.end, self.clock.now().date_naive());
+        let limit = request_page_size(request.page_size, request.max_bars);
+        let base_path = bars_path(symbol, request, self.feed(request.feed.as_deref()), limit);
 
         let client = &self.market_data;
-        let bars = paginate(|page_token| {
+        let timeout = request.timeout;
+        let pages = paginate_stream(move |page_token| {
             let mut path = base_path.clone();
             if let Some(ref token) = page_token {
-                path.push_str(&format!("&page_token={token}"));
+                push_query_param(&mut path, "page_token", token);
             }
             async move {
-                let resp: AlpacaSingleSymbolBarsResponse = client.get(&path).await?;
+                let resp: AlpacaSingleSymbolBarsResponse =
+                    client.get_with_timeout(&path, timeout).await?;
                 Ok((resp.bars, resp.next_page_token))
             }
         })
-        .await?;
+        .map_err(AlpacaError::from);
+        let bars = unique_adjacent(pages, |bar: &AlpacaBar| bar.timestamp)
+            .take(request.max_bars.unwrap_or(usize::MAX));
+
+        match range_check {
+            Ok(()) => bars.left_stream(),
+            Err(e) => stream::once(future::ready(Err(e))).right_stream(),
+        }
+    }
+
+    /// Write every bar for `symbol` in `request` to `writer` in `format`, one
+    /// page at a time as it arrives, and return how many bars were written.
+    ///
+    /// Built on [`get_bars_stream`](Self::get_bars_stream), so memory use is
+    /// bounded by the page size rather than the length of the series. On
+    /// error, bars from earlier pages have already been written.
+    pub async fn download_bars_to_writer<W: std::io::Write>(
+        &self,
+        symbol: &str,
+        request: &BarsRequest,
+        format: BarFormat,
+        writer: W,
+    ) -> Result<usize, AlpacaError> {
+        let mut out = BarWriter::new(writer, format)?;
+        let bars = self.get_bars_stream(symbol, request);
+        let mut bars = std::pin::pin!(bars);
+        let mut written = 0;
+        while let Some(bar) = bars.try_next().await? {
+            out.write(&bar)?;
+            written += 1;
+        }
+        out.finish()?;
+        debug!("download_bars_to_writer symbol={symbol} bars={written}");
+        Ok(written)
+    }
+
+    /// Like [`download_bars_to_writer`](Self::download_bars_to_writer), but
+    /// writes to a [`tokio::io::AsyncWrite`] such as a `tokio::fs::File`, so
+    /// a slow disk doesn't block the runtime.
+    ///
+    /// Each bar is a separate write; wrap unbuffered writers in a
+    /// [`tokio::io::BufWriter`]. The writer is flushed before returning.
+    pub async fn download_bars_to_async_writer<W: AsyncWrite + Unpin>(
+        &self,
+        symbol: &str,
+        request: &BarsRequest,
+        format: BarFormat,
+        mut writer: W,
+    ) -> Result<usize, AlpacaError> {
+        if format == BarFormat::Csv {
+            writer.write_all(&encode_csv_header()?).await?;
+        }
+        let bars = self.get_bars_stream(symbol, request);
+        let mut bars = std::pin::pin!(bars);
+        let mut written = 0;
+        while let Some(bar) = bars.try_next().await? {
+            writer.write_all(&encode_bar(&bar, format)?).await?;
+            written += 1;
+        }
+        writer.flush().await?;
+        debug!("download_bars_to_async_writer symbol={symbol} bars={written}");
+        Ok(written)
+    }
 
-        Ok(bars)
+    /// Fetch the bars in `request` for each of `symbols`, with at most
+    /// `concurrency` symbols downloading at once.
+    ///
+    /// Each symbol is a full [`get_bars`](Self::get_bars) walk with the same
+    /// `request`. Every page request still goes
+    /// through the client's rate limiter (see
+    /// [`with_rate_limiter`](Self::with_rate_limiter)), so a large
+    /// `concurrency` is paced rather than rejected with 429s; no random
+    /// jitter is added on top, as the limiter already spreads requests out.
+    /// A failing symbol is reported in its own entry instead of aborting the
+    /// batch.
+    pub async fn get_bars_for_symbols(
+        &self,
+        symbols: &[&str],
+        request: &BarsRequest,
+        concurrency: usize,
+    ) -> HashMap<String, Result<Vec<AlpacaBar>, AlpacaError>> {
+        debug!(
+            "get_bars_for_symbols symbols={} concurrency={concurrency}",
+            symbols.len()
+        );
+        fetch_concurrent(symbols, concurrency, |symbol| {
+            self.get_bars(symbol, request)
+        })
+        .await
     }
 
     /// Fetch historical trades for a single symbol with auto-pagination.
+    ///
+    /// `request.limit` caps the total number of trades returned across all
+    /// pages; see [`TicksRequest`] for the other options. A trade repeated
+    /// across a page boundary is returned once.
     pub async fn get_trades(
         &self,
         symbol: &str,
-        start: NaiveDate,
-        end: NaiveDate,
-        feed: Option<&str>,
-        limit: Option<u32>,
+        request: TicksRequest,
     ) -> Result<Vec<AlpacaTrade>, AlpacaError> {
-        let limit = limit.unwrap_or(10000);
-        let feed = feed.unwrap_or("iex");
-        let base_path =
-            format!("/v2/stocks/{symbol}/trades?start={start}&end={end}&feed={feed}&limit={limit}");
+        validate_range(request.start, request.end, self.clock.now().date_naive())?;
+        let limit = request_page_size(request.page_size, request.limit);
+        let feed = self.feed(request.feed.as_deref());
+        let base_path = ticks_path("trades", symbol, &request, feed, limit);
 
         let client = &self.market_data;
-        let trades = paginate(|page_token| {
+        let timeout = request.timeout;
+        let fetch_page = |page_token: Option<String>| {
             let mut path = base_path.clone();
             if let Some(ref token) = page_token {
-                path.push_str(&format!("&page_token={token}"));
+                push_query_param(&mut path, "page_token", token);
             }
             async move {
-                let resp: AlpacaTradesPageResponse = client.get(&path).await?;
+                let resp: AlpacaTradesPageResponse =
+                    client.get_with_timeout(&path, timeout).await?;
                 Ok((resp.trades, resp.next_page_token))
             }
+        };
+        collect_unique(fetch_page, request.limit, |trade| {
+            (trade.timestamp, trade.id)
         })
-        .await?;
+        .await
+    }
+
+    /// Fetch a single page of historical trades starting at `page_token`,
+    /// returning the trades and the next page's token (`None` when done).
+    pub async fn get_trades_page(
+        &self,
+        symbol: &str,
+        request: TicksRequest,
+        page_token: Option<&str>,
+    ) -> Result<(Vec<AlpacaTrade>, Option<String>), AlpacaError> {
+        validate_range(request.start, request.end, self.clock.now().date_naive())?;
+        let limit = request_page_size(request.page_size, None);
+        let feed = self.feed(request.feed.as_deref());
+        let base_path = ticks_path("trades", symbol, &request, feed, limit);
 
-        Ok(trades)
+        let client = &self.market_data;
+        let timeout = request.timeout;
+        let fetch_page = |page_token: Option<String>| async move {
+            let mut path = base_path;
+            if let Some(ref token) = page_token {
+                push_query_param(&mut path, "page_token", token);
+            }
+            let resp: AlpacaTradesPageResponse = client.get_with_timeout(&path, timeout).await?;
+            Ok((resp.trades, resp.next_page_token))
+        };
+        Ok(paginate_page(fetch_page, page_token.map(str::to_string)).await?)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Fetch historical quotes for a single symbol with auto-pagination.
+    ///
+    /// Options are as for [`get_trades`](Self::get_trades). An identical
+    /// quote repeated across a page boundary is returned once.
+    pub async fn get_quotes(
+        &self,
+        symbol: &str,
+        request: TicksRequest,
+    ) -> Result<Vec<AlpacaQuote>, AlpacaError> {
+        validate_range(request.start, request.end, self.clock.now().date_naive())?;
+        let limit = request_page_size(request.page_size, request.limit);
+        let feed = self.feed(request.feed.as_deref());
+        let base_path = ticks_path("quotes", symbol, &request, feed, limit);
 
-    #[test]
-    fn client_from_config() {
-        let config = AlpacaConfig::paper("test_key".into(), "test_secret".into());
-        let client = AlpacaClient::new(config);
-        assert!(client.is_ok());
+        let client = &self.market_data;
+        let timeout = request.timeout;
+        let fetch_page = |page_token: Option<String>| {
+            let mut path = base_path.clone();
+            if let Some(ref token) = page_token {
+                push_query_param(&mut path, "page_token", token);
+            }
+            async move {
+                let resp: AlpacaQuotesPageResponse =
+                    client.get_with_timeout(&path, timeout).await?;
+                Ok((resp.quotes, resp.next_page_token))
+            }
+        };
+        collect_unique(fetch_page, request.limit, |quote| {
+            (
+                quote.timestamp,
+                quote.bid_price,
+                quote.bid_size,
+                quote.ask_price,
+                quote.ask_size,
+            )
+        })
+        .await
     }
+}
 
-    #[test]
-    fn client_config_accessor() {
-        let config = AlpacaConfig::paper("my_key".into(), "my_secret".into());
-        let client = AlpacaClient::new(config).unwrap();
-        assert_eq!(client.config().api_key_id, "my_key");
-        assert_eq!(client.config().api_secret_key, "my_secret");
-        assert_eq!(
-            client.config().trading_base_url,
-            "https://paper-api.alpaca.markets"
-        );
+/// Collect every page from `fetch_page`, collapsing runs of items with the
+/// same key (keeping the later-fetched one), and stop once `max_items`
+/// unique items are collected, without requesting further pages.
+///
+/// Alpaca sometimes repeats the boundary record at the start of the next
+/// page; since pages arrive in order, such duplicates are always adjacent.
+/// Deduplicating before applying the cap keeps a repeated record from
+/// costing a slot.
+async fn collect_unique<T, K, F, Fut>(
+    fetch_page: F,
+    max_items: Option<usize>,
+    key: impl Fn(&T) -> K,
+) -> Result<Vec<T>, AlpacaError>
+where
+    K: PartialEq,
+    F: Fn(Option<String>) -> Fut,
+    Fut: Future<Output = Result<(Vec<T>, Option<String>), api_client_core::ApiClientError>>,
+{
+    let max_items = max_items.unwrap_or(usize::MAX);
+    let mut unique: Vec<T> = Vec::new();
+    if max_items == 0 {
+        return Ok(unique);
     }
+    let mut items = std::pin::pin!(paginate_stream(fetch_page));
+    while let Some(item) = items.try_next().await? {
+        match unique.last_mut() {
+            Some(last) if key(last) == key(&item) => *last = item,
+            _ => unique.push(item),
+        }
+        if unique.len() == max_items {
+            break;
+        }
+    }
+    Ok(unique)
+}
 
-    #[test]
-    fn client_live_config() {
-        let config = AlpacaConfig {
-            api_key_id: "key".into(),
+/// Stream counterpart of [`collect_unique`]: collapse runs of items with the
+/// same key, yielding the last of each run. Each item is held back until the
+/// next one shows whether it was repeated.
+fn unique_adjacent<T, K, E>(
+    items: impl Stream<Item = Result<T, E>>,
+    key: impl Fn(&T) -> K,
+) -> impl Stream<Item = Result<T, E>>
+where
+    K: PartialEq,
+{
+    let items = Box::pin(items.fuse());
+    stream::unfold(
+        (items, None::<T>, key),
+        |(mut items, mut held, key)| async move {
+            loop {
+                match items.next().await {
+                    Some(Ok(item)) => match held.take() {
+                        Some(prev) if key(&prev) != key(&item) => {
+                            return Some((Ok(prev), (items, Some(item), key)));
+                        }
+                        _ => held = Some(item),
+                    },
+                    Some(Err(e)) => return Some((Err(e), (items, held, key))),
+                    None => return held.map(|prev| (Ok(prev), (items, None, key))),
+                }
+            }
+        },
+    )
+}
+
+/// Run `fetch` for each symbol, keeping at most `concurrency` (minimum 1)
+/// futures in flight, and collect every result keyed by symbol.
+async fn fetch_concurrent<'a, T, F, Fut>(
+    symbols: &'a [&'a str],
+    concurrency: usize,
+    fetch: F,
+) -> HashMap<String, Result<T, AlpacaError>>
+where
+    F: Fn(&'a str) -> Fut,
+    Fut: Future<Output = Result<T, AlpacaError>>,
+{
+    stream::iter(symbols)
+        .map(|&symbol| {
+            let request = fetch(symbol);
+            async move { (symbol.to_string(), request.await) }
+        })
+        .buffer_unordered(concurrency.max(1))
+        .collect()
+        .await
+}
+
+/// Submit, and on a transport failure check whether the order landed before
+/// deciding whether to resubmit.
+async fn submit_with_recovery<T, S, SF, L, LF>(submit: S, lookup: L) -> Result<T, AlpacaError>
+where
+    S: Fn() -> SF,
+    SF: Future<Output = Result<T, AlpacaError>>,
+    L: FnOnce() -> LF,
+    LF: Future<Output = Result<T, AlpacaError>>,
+{
+    let err = match submit().await {
+        Err(e @ AlpacaError::Http(_)) => e,
+        result => return result,
+    };
+
+    warn!("Order submission outcome unknown ({err}); looking up by client_order_id");
+    match lookup().await {
+        Ok(existing) => Ok(existing),
+        Err(e) if e.is_not_found() => submit().await,
+        Err(lookup_err) => {
+            warn!("Order lookup failed: {lookup_err}");
+            Err(err)
+        }
+    }
+}
+
+const DEFAULT_PAGE_SIZE: u32 = 10000;
+
+/// Reject inverted ranges and ranges ending after `today`, which the
+/// historical endpoints would answer with an empty result.
+fn validate_range(start: TimeBound, end: TimeBound, today: NaiveDate) -> Result<(), AlpacaError> {
+    let inverted = match (start, end) {
+        (TimeBound::DateTime(start), TimeBound::DateTime(end)) => start > end,
+        _ => start.date() > end.date(),
+    };
+    if inverted {
+        return Err(AlpacaError::Config(format!(
+            "start date {start} is after end date {end}"
+        )));
+    }
+    if end.date() > today {
+        return Err(AlpacaError::Config(format!(
+            "end date {end} is in the future (today is {today})"
+        )));
+    }
+    Ok(())
+}
+
+/// Reject order type / time in force / order class combinations Alpaca is
+/// known to refuse, before spending a round trip on them:
+///
+/// | Rule                                  | Allowed                              |
+/// |---------------------------------------|--------------------------------------|
+/// | `type`                                | `market`, `limit`, `stop`, `stop_limit`, `trailing_stop` |
+/// | `time_in_force`                       | `day`, `gtc`, `opg`, `cls`, `ioc`, `fok` |
+/// | `order_class`                         | `simple`, `bracket`, `oco`, `oto`    |
+/// | `opg`, `cls`, `ioc`, `fok`            | `market` or `limit` orders only, so `stop`, `stop_limit` and `trailing_stop` use `day` or `gtc` |
+/// | `bracket`, `oco`, `oto`               | `day` or `gtc`                       |
+/// | `bracket`, `oto` entry                | `market` or `limit`                  |
+/// | `oco`                                 | `limit`                              |
+/// | `extended_hours`                      | `limit` with `day` or `gtc`, simple class |
+/// | `limit`, `stop_limit`                 | need `limit_price`                   |
+/// | `market`                              | no `limit_price`                     |
+/// | `stop`, `stop_limit`                  | need `stop_price`                    |
+/// | `trailing_stop`                       | exactly one of `trail_price`, `trail_percent` |
+fn validate_order(order: &AlpacaOrderRequest) -> Result<(), AlpacaError> {
+    const TYPES: &[&str] = &["market", "limit", "stop", "stop_limit", "trailing_stop"];
+    const TIFS: &[&str] = &["day", "gtc", "opg", "cls", "ioc", "fok"];
+    const CLASSES: &[&str] = &["simple", "bracket", "oco", "oto"];
+
+    let order_type = order.order_type.as_str();
+    let tif = order.time_in_force.as_str();
+    let class = order.order_class.as_deref().unwrap_or("simple");
+    let invalid = |reason: String| Err(AlpacaError::Config(format!("invalid order: {reason}")));
+
+    if !TYPES.contains(&order_type) {
+        return invalid(format!(
+            "unknown type `{order_type}`, expected one of {}",
+            TYPES.join(", ")
+        ));
+    }
+    if !TIFS.contains(&tif) {
+        return invalid(format!(
+            "unknown time_in_force `{tif}`, expected one of {}",
+            TIFS.join(", ")
+        ));
+    }
+    if !CLASSES.contains(&class) {
+        return invalid(format!(
+            "unknown order_class `{class}`, expected one of {}",
+            CLASSES.join(", ")
+        ));
+    }
+    let day_or_gtc = matches!(tif, "day" | "gtc");
+    if matches!(tif, "opg" | "cls" | "ioc" | "fok") && !matches!(order_type, "market" | "limit") {
+        return invalid(format!(
+            "time_in_force `{tif}` only applies to market and limit orders, not `{order_type}`"
+        ));
+    }
+    if class != "simple" && !day_or_gtc {
+        return invalid(format!(
+            "{class} orders need time_in_force day or gtc, not `{tif}`"
+        ));
+    }
+    if matches!(class, "bracket" | "oto") && !matches!(order_type, "market" | "limit") {
+        return invalid(format!(
+            "{class} orders need a market or limit entry, not `{order_type}`"
+        ));
+    }
+    if class == "oco" && order_type != "limit" {
+        return invalid(format!("oco orders must be type limit, not `{order_type}`"));
+    }
+    if order.extended_hours && (order_type != "limit" || !day_or_gtc || class != "simple") {
+        return invalid(format!(
+            "extended_hours needs a simple limit order with day or gtc, got {class} {order_type} {tif}"
+        ));
+    }
+    if matches!(order_type, "limit" | "stop_limit") && order.limit_price.is_none() {
+        return invalid(format!("{order_type} orders need a limit_price"));
+    }
+    if order_type == "market" && order.limit_price.is_some() {
+        return invalid("market orders take no limit_price".into());
+    }
+    if matches!(order_type, "stop" | "stop_limit") && order.stop_price.is_none() {
+        return invalid(format!("{order_type} orders need a stop_price"));
+    }
+    if order_type == "trailing_stop" && order.trail_price.is_some() == order.trail_percent.is_some()
+    {
+        return invalid(
+            "trailing_stop orders need exactly one of trail_price or trail_percent".into(),
+        );
+    }
+    Ok(())
+}
+
+/// Reject multi-leg orders the API would refuse: a parent type or
+/// time-in-force other than those `mleg` orders support, or unusable legs.
+fn validate_multileg(
+    order_type: &str,
+    time_in_force: &str,
+    limit_price: Option<Decimal>,
+    legs: &[OptionLeg],
+) -> Result<(), AlpacaError> {
+    match order_type {
+        "market" => {}
+        "limit" if limit_price.is_none() => {
+            return Err(AlpacaError::Config(
+                "multi-leg limit order needs a limit_price".into(),
+            ))
+        }
+        "limit" => {}
+        other => {
+            return Err(AlpacaError::Config(format!(
+                "multi-leg order type must be market or limit, got `{other}`"
+            )))
+        }
+    }
+    if time_in_force != "day" {
+        return Err(AlpacaError::Config(format!(
+            "multi-leg order time_in_force must be day, got `{time_in_force}`"
+        )));
+    }
+    if legs.len() < 2 {
+        return Err(AlpacaError::Config(format!(
+            "multi-leg order needs at least 2 legs, got {}",
+            legs.len()
+        )));
+    }
+    if let Some(leg) = legs.iter().find(|leg| leg.ratio_qty == 0) {
+        return Err(AlpacaError::Config(format!(
+            "leg {} has ratio_qty 0; ratios must be positive",
+            leg.symbol
+        )));
+    }
+    Ok(())
+}
+
+/// Per-request page size, shrunk to the total cap when that is smaller so the
+/// last request doesn't fetch rows that would be thrown away.
+fn request_page_size(page_size: Option<u32>, max_items: Option<usize>) -> u32 {
+    let page_size = page_size.unwrap_or(DEFAULT_PAGE_SIZE);
+    match max_items {
+        Some(max) => page_size.min(u32::try_from(max).unwrap_or(u32::MAX)).max(1),
+        None => page_size,
+    }
+}
+
+/// Order timestamps at full precision, so the time cursor doesn't skip orders
+/// submitted within the same second.
+fn order_cursor(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true)
+}
+
+fn bars_path(symbol: &str, request: &BarsRequest, feed: &str, limit: u32) -> String {
+    let mut path = format!("/v2/stocks/{}/bars", encode_path_segment(symbol));
+    push_query_param(&mut path, "start", request.start);
+    push_query_param(&mut path, "end", request.end);
+    push_query_param(&mut path, "timeframe", request.timeframe);
+    push_query_param(
+        &mut path,
+        "adjustment",
+        request.adjustment.unwrap_or_default(),
+    );
+    push_query_param(&mut path, "feed", feed);
+    push_query_param(&mut path, "limit", limit);
+    if let Some(asof) = request.asof {
+        push_query_param(&mut path, "asof", asof);
+    }
+    if let Some(sort) = request.sort {
+        push_query_param(&mut path, "sort", sort);
+    }
+    if let Some(ref currency) = request.currency {
+        push_query_param(&mut path, "currency", currency);
+    }
+    path
+}
+
+/// Path for a stock `dataset` that pages like trades, e.g. `trades` or
+/// `quotes`.
+fn ticks_path(
+    dataset: &str,
+    symbol: &str,
+    request: &TicksRequest,
+    feed: &str,
+    limit: u32,
+) -> String {
+    let mut path = format!("/v2/stocks/{}/{dataset}", encode_path_segment(symbol));
+    push_query_param(&mut path, "start", request.start);
+    push_query_param(&mut path, "end", request.end);
+    push_query_param(&mut path, "feed", feed);
+    push_query_param(&mut path, "limit", limit);
+    if let Some(asof) = request.asof {
+        push_query_param(&mut path, "asof", asof);
+    }
+    if let Some(sort) = request.sort {
+        push_query_param(&mut path, "sort", sort);
+    }
+    if let Some(ref currency) = request.currency {
+        push_query_param(&mut path, "currency", currency);
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::testing::{account_json, bar_json, bars_page, mock_client, order_at, order_json};
+    use api_client_core::MockTransport;
+    use reqwest::Method;
+
+    #[test]
+    fn client_from_config() {
+        let config = AlpacaConfig::paper("test_key".into(), "test_secret".into());
+        let client = AlpacaClient::new(config);
+        assert!(client.is_ok());
+    }
+
+    #[test]
+    fn client_config_accessor() {
+        let config = AlpacaConfig::paper("my_key".into(), "my_secret".into());
+        let client = AlpacaClient::new(config).unwrap();
+        assert_eq!(client.config().api_key_id, "my_key");
+        assert_eq!(client.config().api_secret_key, "my_secret");
+        assert_eq!(
+            client.config().trading_base_url,
+            "https://paper-api.alpaca.markets"
+        );
+    }
+
+    #[test]
+    fn client_live_config() {
+        let config = AlpacaConfig {
+            api_key_id: "key".into(),
             api_secret_key: "secret".into(),
             trading_base_url: "https://api.alpaca.markets".into(),
             market_data_base_url: "https://data.alpaca.markets".into(),
+            default_feed: crate::stream::MarketDataFeed::Iex,
+            timeout: std::time::Duration::from_secs(30),
+            user_agent: "test-agent".into(),
+            proxy: None,
+            root_certificates: Vec::new(),
         };
         let client = AlpacaClient::new(config);
         assert!(client.is_ok());
     }
+
+    #[test]
+    fn page_size_defaults_and_caps() {
+        assert_eq!(request_page_size(None, None), 10000);
+        assert_eq!(request_page_size(Some(500), None), 500);
+        assert_eq!(request_page_size(None, Some(50)), 50);
+        assert_eq!(request_page_size(Some(100), Some(5000)), 100);
+        assert_eq!(request_page_size(Some(100), Some(0)), 1);
+    }
+
+    #[test]
+    fn bars_path_uses_page_size() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 1, 31).unwrap();
+        let path = bars_path(
+            "AAPL",
+            &BarsRequest::new(start, end, TimeFrame::Day),
+            "iex",
+            50,
+        );
+        assert_eq!(
+            path,
+            "/v2/stocks/AAPL/bars?start=2024-01-01&end=2024-01-31&timeframe=1Day&adjustment=split&feed=iex&limit=50"
+        );
+    }
+
+    #[test]
+    fn bars_path_sends_each_adjustment() {
+        let day = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+        for (adjustment, expected) in [
+            (None, "&adjustment=split&"),
+            (Some(Adjustment::Raw), "&adjustment=raw&"),
+            (Some(Adjustment::Split), "&adjustment=split&"),
+            (Some(Adjustment::Dividend), "&adjustment=dividend&"),
+            (Some(Adjustment::All), "&adjustment=all&"),
+        ] {
+            let path = bars_path(
+                "AAPL",
+                &BarsRequest {
+                    adjustment,
+                    ..BarsRequest::new(day, day, TimeFrame::Day)
+                },
+                "iex",
+                10,
+            );
+            assert!(path.contains(expected), "{path}");
+        }
+        assert_eq!("ALL".parse::<Adjustment>().unwrap(), Adjustment::All);
+        assert!("splits".parse::<Adjustment>().is_err());
+    }
+
+    #[test]
+    fn bars_path_formats_rfc3339_instants() {
+        let day = NaiveDate::from_ymd_opt(2024, 6, 3).unwrap();
+        let start = day.and_hms_opt(13, 30, 0).unwrap().and_utc();
+        let end = day.and_hms_opt(14, 0, 0).unwrap().and_utc();
+        let path = bars_path(
+            "AAPL",
+            &BarsRequest::new(start, end, TimeFrame::MIN_1),
+            "iex",
+            50,
+        );
+        assert!(path.contains("?start=2024-06-03T13%3A30%3A00Z&end=2024-06-03T14%3A00%3A00Z&"));
+    }
+
+    /// A real transport-level timeout from a server that accepts but never responds.
+    async fn timeout_error() -> AlpacaError {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let err = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_millis(20))
+            .build()
+            .unwrap()
+            .get(url)
+            .send()
+            .await
+            .unwrap_err();
+        assert!(err.is_timeout());
+        AlpacaError::Http(err)
+    }
+
+    fn not_found() -> AlpacaError {
+        AlpacaError::NotFound {
+            resource: "order not found".into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn idempotent_submit_returns_existing_order_after_timeout() {
+        let timeout = std::sync::Mutex::new(Some(timeout_error().await));
+        let submits = std::sync::atomic::AtomicUsize::new(0);
+
+        let result = submit_with_recovery(
+            || {
+                submits.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                let err = timeout.lock().unwrap().take();
+                async move { Err::<String, _>(err.unwrap()) }
+            },
+            || async { Ok("order-1".to_string()) },
+        )
+        .await;
+
+        assert_eq!(result.unwrap(), "order-1");
+        assert_eq!(submits.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn idempotent_submit_resubmits_when_order_missing() {
+        let timeout = std::sync::Mutex::new(Some(timeout_error().await));
+
+        let result = submit_with_recovery(
+            || {
+                let err = timeout.lock().unwrap().take();
+                async move {
+                    match err {
+                        Some(e) => Err(e),
+                        None => Ok("order-2".to_string()),
+                    }
+                }
+            },
+            || async { Err(not_found()) },
+        )
+        .await;
+
+        assert_eq!(result.unwrap(), "order-2");
+    }
+
+    #[tokio::test]
+    async fn idempotent_submit_never_retries_api_errors() {
+        let result: Result<String, _> = submit_with_recovery(
+            || async {
+                Err(AlpacaError::Api {
+                    status: 403,
+                    body: r#"{"code":40310000,"message":"insufficient buying power"}"#.into(),
+                })
+            },
+            || async { panic!("lookup must not run for API errors") },
+        )
+        .await;
+
+        assert_eq!(result.unwrap_err().code(), Some(40310000));
+    }
+
+    #[tokio::test]
+    async fn idempotent_submit_requires_client_order_id() {
+        let (client, transport) = mock_client(MockTransport::new());
+        let order = OrderBuilder::new("AAPL", 1, "buy").build();
+
+        let err = client.submit_order_idempotent(&order).await.unwrap_err();
+        assert!(
+            matches!(&err, AlpacaError::Config(msg) if msg.contains("client_order_id")),
+            "{err:?}"
+        );
+        assert!(transport.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn idempotent_submit_sends_client_order_id() {
+        let order = order_json("ord-1", "accepted").to_string();
+        let (client, transport) =
+            mock_client(MockTransport::new().respond(Method::POST, "/v2/orders", 200, &order));
+        let request = OrderBuilder::new("AAPL", 1, "buy")
+            .limit(Decimal::from(190))
+            .client_order_id("my-order-1")
+            .build();
+
+        let placed = client.submit_order_idempotent(&request).await.unwrap();
+        assert_eq!(placed.id, "ord-1");
+        let body = transport.requests()[0].body.clone().unwrap();
+        assert_eq!(body["client_order_id"], "my-order-1");
+    }
+
+    #[test]
+    fn default_feed_flows_into_request_path() {
+        let config = AlpacaConfig::builder()
+            .api_key("key")
+            .api_secret("secret")
+            .default_feed(crate::stream::MarketDataFeed::Sip)
+            .build()
+            .unwrap();
+        let client = AlpacaClient::new(config).unwrap();
+        assert_eq!(client.feed(None), "sip");
+        assert_eq!(client.feed(Some("iex")), "iex");
+
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let path = bars_path(
+            "AAPL",
+            &BarsRequest::new(start, start, TimeFrame::Day),
+            client.feed(None),
+            10,
+        );
+        assert!(path.contains("&feed=sip&"));
+    }
+
+    #[tokio::test]
+    async fn account_activities_page_by_last_id() {
+        let (client, transport) = mock_client(
+            MockTransport::new()
+                .respond(
+                    Method::GET,
+                    "/v2/account/activities",
+                    200,
+                    r#"[{"id":"a3","activity_type":"FILL"},{"id":"a2","activity_type":"DIV"}]"#,
+                )
+                .respond(
+                    Method::GET,
+                    "/v2/account/activities",
+                    200,
+                    r#"[{"id":"a1","activity_type":"FILL"}]"#,
+                ),
+        );
+
+        let activities = client
+            .get_account_activities(Some("FILL,DIV"), Some(2))
+            .await
+            .unwrap();
+        let ids: Vec<&str> = activities.iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(ids, vec!["a3", "a2", "a1"]);
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(!requests[0].query.iter().any(|(k, _)| k == "page_token"));
+        assert!(requests[1]
+            .query
+            .contains(&("page_token".to_string(), "a2".to_string())));
+    }
+
+    #[tokio::test]
+    async fn asof_is_sent_only_when_given() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+        for (asof, expected) in [(Some(start), Some("&asof=2024-01-02")), (None, None)] {
+            let (client, transport) = mock_client(
+                MockTransport::new()
+                    .respond(Method::GET, "/v2/stocks/FB/bars", 200, r#"{"bars":[]}"#)
+                    .respond(Method::GET, "/v2/stocks/FB/trades", 200, r#"{"trades":[]}"#)
+                    .respond(Method::GET, "/v2/stocks/FB/quotes", 200, r#"{"quotes":[]}"#),
+            );
+            client
+                .get_bars(
+                    "FB",
+                    &BarsRequest {
+                        asof,
+                        ..BarsRequest::new(start, start, TimeFrame::Day)
+                    },
+                )
+                .await
+                .unwrap();
+            client
+                .get_trades(
+                    "FB",
+                    TicksRequest {
+                        asof,
+                        ..TicksRequest::new(start, start)
+                    },
+                )
+                .await
+                .unwrap();
+            client
+                .get_quotes(
+                    "FB",
+                    TicksRequest {
+                        asof,
+                        ..TicksRequest::new(start, start)
+                    },
+                )
+                .await
+                .unwrap();
+            let requests = transport.requests();
+            assert_eq!(requests.len(), 3);
+            for url in requests.iter().map(|r| &r.url) {
+                match expected {
+                    Some(param) => assert!(url.contains(param), "{url}"),
+                    None => assert!(!url.contains("asof"), "{url}"),
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn every_feed_flows_into_query_string() {
+        use crate::stream::MarketDataFeed;
+
+        let start = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+        for feed in [
+            MarketDataFeed::Sip,
+            MarketDataFeed::Iex,
+            MarketDataFeed::Test,
+            MarketDataFeed::DelayedSip,
+            MarketDataFeed::Boats,
+        ] {
+            let (client, transport) = mock_client(
+                MockTransport::new()
+                    .respond(Method::GET, "/v2/stocks/AAPL/bars", 200, r#"{"bars":[]}"#)
+                    .respond(
+                        Method::GET,
+                        "/v2/stocks/AAPL/trades",
+                        200,
+                        r#"{"trades":[]}"#,
+                    )
+                    .respond(
+                        Method::GET,
+                        "/v2/stocks/AAPL/quotes",
+                        200,
+                        r#"{"quotes":[]}"#,
+                    ),
+            );
+            let feed_param = format!("&feed={}&", feed.as_str());
+            client
+                .get_bars(
+                    "AAPL",
+                    &BarsRequest::new(start, start, TimeFrame::Day).feed(feed.as_str()),
+                )
+                .await
+                .unwrap();
+            client
+                .get_trades("AAPL", TicksRequest::new(start, start).feed(feed.as_str()))
+                .await
+                .unwrap();
+            client
+                .get_quotes("AAPL", TicksRequest::new(start, start).feed(feed.as_str()))
+                .await
+                .unwrap();
+            let requests = transport.requests();
+            assert_eq!(requests.len(), 3);
+            for request in &requests {
+                assert!(request.url.contains(&feed_param), "{}", request.url);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn bars_timeout_override_against_slow_server() {
+        // Accepts connections but never responds.
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let config = AlpacaConfig::builder()
+            .api_key("key")
+            .api_secret("secret")
+            .market_data_base_url(format!("http://{}", listener.local_addr().unwrap()))
+            .build()
+            .unwrap();
+        let client = AlpacaClient::new(config)
+            .unwrap()
+            .with_timeout(Duration::from_secs(60));
+        assert_eq!(client.config().timeout, Duration::from_secs(60));
+
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let result = client
+            .get_bars(
+                "AAPL",
+                &BarsRequest::new(start, start, TimeFrame::Day).timeout(Duration::from_millis(20)),
+            )
+            .await;
+        match result {
+            Err(AlpacaError::Http(e)) => assert!(e.is_timeout()),
+            other => panic!("expected timeout, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn bars_sort_desc_preserves_server_order() {
+        let page = serde_json::json!({
+            "bars": [bar_json(3, 3), bar_json(2, 2)],
+            "next_page_token": null
+        });
+        let (client, transport) = mock_client(MockTransport::new().respond(
+            Method::GET,
+            "/v2/stocks/AAPL/bars",
+            200,
+            &page.to_string(),
+        ));
+
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 1, 31).unwrap();
+        let bars = client
+            .get_bars(
+                "AAPL",
+                &BarsRequest::new(start, end, TimeFrame::Day)
+                    .sort(SortDirection::Desc)
+                    .currency("EUR")
+                    .max_bars(2),
+            )
+            .await
+            .unwrap();
+        let closes: Vec<Decimal> = bars.iter().map(|b| b.close).collect();
+        assert_eq!(closes, vec![3.into(), 2.into()]);
+
+        let url = &transport.requests()[0].url;
+        assert!(url.contains("&sort=desc&currency=EUR"), "{url}");
+    }
+
+    #[tokio::test]
+    async fn requests_carry_user_agent_and_correlation_id() {
+        let (client, transport) = mock_client(MockTransport::new().respond(
+            Method::GET,
+            "/v2/clock",
+            200,
+            r#"{"timestamp":"2024-06-03T10:00:00-04:00","is_open":true,"next_open":"2024-06-04T09:30:00-04:00","next_close":"2024-06-03T16:00:00-04:00"}"#,
+        ));
+        let client = client.with_correlation_id("rebalance-7").unwrap();
+
+        client.get_clock().await.unwrap();
+        let headers = &transport.requests()[0].headers;
+        assert_eq!(
+            headers["user-agent"],
+            format!("alpaca-rs/{}", env!("CARGO_PKG_VERSION")).as_str()
+        );
+        assert_eq!(headers["x-correlation-id"], "rebalance-7");
+    }
+
+    #[tokio::test]
+    async fn most_actives_hits_screener_endpoint() {
+        let (client, transport) = mock_client(MockTransport::new().respond(
+            Method::GET,
+            "/v1beta1/screener/stocks/most-actives",
+            200,
+            r#"{"most_actives":[{"symbol":"NVDA","volume":1000,"trade_count":10}],"last_updated":"2024-06-03T19:59:00Z"}"#,
+        ));
+
+        let actives = client.get_most_actives("trades", Some(5)).await.unwrap();
+        assert_eq!(actives.most_actives[0].symbol, "NVDA");
+        let request = &transport.requests()[0];
+        assert_eq!(
+            request.url,
+            "https://data.alpaca.markets/v1beta1/screener/stocks/most-actives"
+        );
+        assert_eq!(
+            request.query,
+            vec![
+                ("by".to_string(), "trades".to_string()),
+                ("top".to_string(), "5".to_string())
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn get_account_through_mock_transport() {
+        let (client, transport) = mock_client(MockTransport::new().respond(
+            Method::GET,
+            "/v2/account",
+            200,
+            &account_json().to_string(),
+        ));
+
+        let account = client.get_account().await.unwrap();
+        assert_eq!(account.account_number, "PA123");
+        assert_eq!(account.cash, Decimal::new(10000, 0));
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0].url,
+            "https://paper-api.alpaca.markets/v2/account"
+        );
+        assert_eq!(requests[0].headers["apca-api-key-id"], "key");
+    }
+
+    #[tokio::test]
+    async fn submit_order_through_mock_transport() {
+        let (client, transport) = mock_client(MockTransport::new().respond(
+            Method::POST,
+            "/v2/orders",
+            422,
+            r#"{"code":42210000,"message":"qty must be > 0"}"#,
+        ));
+
+        let err = client
+            .submit_order("AAPL", 0, "buy", "market", "day", None, false)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status_code(), Some(422));
+        assert_eq!(err.message().as_deref(), Some("qty must be > 0"));
+
+        let body = transport.requests()[0].body.clone().unwrap();
+        assert_eq!(body["symbol"], "AAPL");
+        assert_eq!(body["type"], "market");
+    }
+
+    #[tokio::test]
+    async fn submit_posts_built_order() {
+        let (client, transport) = mock_client(MockTransport::new().respond(
+            Method::POST,
+            "/v2/orders",
+            200,
+            &order_json("o-1", "accepted").to_string(),
+        ));
+
+        let order = client
+            .submit(OrderBuilder::buy("AAPL", 10).limit(Decimal::from(190)))
+            .await
+            .unwrap();
+        assert_eq!(order.id, "o-1");
+
+        let body = transport.requests()[0].body.clone().unwrap();
+        assert_eq!(body["type"], "limit");
+        assert_eq!(body["limit_price"], "190");
+
+        let err = client
+            .submit(OrderBuilder::buy("AAPL", 10).extended_hours())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AlpacaError::Config(_)), "{err:?}");
+        assert_eq!(transport.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn bars_page_token_round_trips() {
+        let (client, transport) = mock_client(
+            MockTransport::new()
+                .respond(
+                    Method::GET,
+                    "/v2/stocks/AAPL/bars",
+                    200,
+                    &bars_page(&[2, 3], Some("tok-2")),
+                )
+                .respond(
+                    Method::GET,
+                    "/v2/stocks/AAPL/bars",
+                    200,
+                    &bars_page(&[4], None),
+                ),
+        );
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 1, 31).unwrap();
+
+        let (first, token) = client
+            .get_bars_page(
+                "AAPL",
+                &BarsRequest::new(start, end, TimeFrame::Day).page_size(2),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(first.len(), 2);
+        assert_eq!(token.as_deref(), Some("tok-2"));
+
+        // Resume from the saved token as if after a restart.
+        let (rest, token) = client
+            .get_bars_page(
+                "AAPL",
+                &BarsRequest::new(start, end, TimeFrame::Day).page_size(2),
+                token.as_deref(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(rest.len(), 1);
+        assert_eq!(chrono::Datelike::day(&rest[0].timestamp), 4);
+        assert_eq!(token, None);
+
+        let requests = transport.requests();
+        assert!(!requests[0].url.contains("page_token"));
+        assert!(requests[1].url.ends_with("&page_token=tok-2"));
+    }
+
+    #[tokio::test]
+    async fn bars_overlapping_pages_are_deduplicated() {
+        let page1 = serde_json::json!({
+            "bars": [bar_json(2, 10), bar_json(3, 11)],
+            "next_page_token": "tok-2"
+        })
+        .to_string();
+        // The boundary bar (Jan 3) is repeated with a corrected close.
+        let page2 = serde_json::json!({
+            "bars": [bar_json(3, 12), bar_json(4, 13)],
+            "next_page_token": null
+        })
+        .to_string();
+        let (client, _) = mock_client(
+            MockTransport::new()
+                .respond(Method::GET, "/v2/stocks/AAPL/bars", 200, &page1)
+                .respond(Method::GET, "/v2/stocks/AAPL/bars", 200, &page2),
+        );
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 1, 31).unwrap();
+
+        let bars = client
+            .get_bars("AAPL", &BarsRequest::new(start, end, TimeFrame::Day))
+            .await
+            .unwrap();
+        let closes: Vec<Decimal> = bars.iter().map(|b| b.close).collect();
+        assert_eq!(closes, vec![10.into(), 12.into(), 13.into()]);
+        let mut timestamps: Vec<_> = bars.iter().map(|b| b.timestamp).collect();
+        timestamps.dedup();
+        assert_eq!(timestamps.len(), bars.len());
+    }
+
+    #[tokio::test]
+    async fn collect_unique_keeps_distinct_keys() {
+        let pages = |token: Option<String>| async move {
+            Ok(match token.as_deref() {
+                None => (vec![(1, 'a'), (1, 'b'), (2, 'c')], Some("p2".to_string())),
+                _ => (vec![(2, 'd'), (1, 'e')], None),
+            })
+        };
+        let unique = collect_unique(pages, None, |t| t.0).await.unwrap();
+        assert_eq!(unique, vec![(1, 'b'), (2, 'd'), (1, 'e')]);
+    }
+
+    #[test]
+    fn rate_limiter_is_shared_between_apis() {
+        let config = AlpacaConfig::paper("key".into(), "secret".into());
+        let client = AlpacaClient::new(config)
+            .unwrap()
+            .with_rate_limiter(Arc::new(RateLimiter::per_minute(200)));
+        let trading = client.trading.rate_limiter().unwrap();
+        let market_data = client.market_data.rate_limiter().unwrap();
+        assert!(Arc::ptr_eq(trading, market_data));
+    }
+
+    #[tokio::test]
+    async fn fetch_concurrent_bounds_in_flight_requests() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let in_flight = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let symbols = ["AAPL", "TSLA", "SPY", "QQQ", "NVDA", "BAD"];
+        let results = fetch_concurrent(&symbols, 2, |symbol| {
+            let (in_flight, peak) = (&in_flight, &peak);
+            async move {
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                if symbol == "BAD" {
+                    Err(AlpacaError::Api {
+                        status: 404,
+                        body: "not found".into(),
+                    })
+                } else {
+                    Ok(symbol.len())
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+        assert_eq!(results.len(), symbols.len());
+        assert_eq!(results["NVDA"].as_ref().unwrap(), &4);
+        assert!(matches!(
+            results["BAD"],
+            Err(AlpacaError::Api { status: 404, .. })
+        ));
+    }
+
+    #[test]
+    fn validate_range_rejects_inverted_and_future_ranges() {
+        let day = |d| NaiveDate::from_ymd_opt(2024, 6, d).unwrap();
+        let today = day(15);
+
+        let check =
+            |start: NaiveDate, end: NaiveDate| validate_range(start.into(), end.into(), today);
+        assert!(check(day(1), day(10)).is_ok());
+        assert!(check(day(15), day(15)).is_ok());
+
+        let inverted = check(day(10), day(1)).unwrap_err();
+        assert!(matches!(inverted, AlpacaError::Config(_)));
+        assert!(inverted.to_string().contains("after end date"));
+
+        let future = check(day(1), day(16)).unwrap_err();
+        assert!(matches!(future, AlpacaError::Config(_)));
+        assert!(future.to_string().contains("in the future"));
+
+        let at = |h| day(3).and_hms_opt(h, 0, 0).unwrap().and_utc();
+        assert!(validate_range(at(13).into(), at(14).into(), today).is_ok());
+        assert!(validate_range(at(14).into(), at(13).into(), today).is_err());
+        // A date end covers the whole day, so an intraday start on it is fine.
+        assert!(validate_range(at(14).into(), day(3).into(), today).is_ok());
+    }
+
+    #[tokio::test]
+    async fn bars_stream_yields_range_error() {
+        let client = AlpacaClient::new(AlpacaConfig::paper("k".into(), "s".into())).unwrap();
+        let day = |d| NaiveDate::from_ymd_opt(2024, 6, d).unwrap();
+        let results: Vec<_> = client
+            .get_bars_stream("AAPL", &BarsRequest::new(day(10), day(1), TimeFrame::Day))
+            .collect()
+            .await;
+        assert_eq!(results.len(), 1);
+        assert!(matches!(results[0], Err(AlpacaError::Config(_))));
+    }
+
+    #[tokio::test]
+    async fn market_clock_helpers_use_server_timestamp() {
+        let (client, transport) = mock_client(
+            MockTransport::new()
+                .respond(
+                    Method::GET,
+                    "/v2/clock",
+                    200,
+                    r#"{
+                        "timestamp": "2024-06-03T12:00:00Z", "is_open": false,
+                        "next_open": "2024-06-03T13:30:00Z",
+                        "next_close": "2024-06-03T20:00:00Z"
+                    }"#,
+                )
+                .respond(
+                    Method::GET,
+                    "/v2/clock",
+                    200,
+                    r#"{
+                        "timestamp": "2024-06-03T14:00:00Z", "is_open": true,
+                        "next_open": "2024-06-04T13:30:00Z",
+                        "next_close": "2024-06-03T20:00:00Z"
+                    }"#,
+                ),
+        );
+
+        assert_eq!(
+            client.time_until_open().await.unwrap(),
+            Duration::from_secs(90 * 60)
+        );
+        assert!(client.is_market_open().await.unwrap());
+        assert_eq!(client.time_until_open().await.unwrap(), Duration::ZERO);
+        assert_eq!(
+            client.next_market_open().await.unwrap(),
+            "2024-06-04T13:30:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+        assert_eq!(
+            client.next_market_close().await.unwrap(),
+            "2024-06-03T20:00:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+        assert_eq!(transport.requests().len(), 5);
+    }
+
+    #[tokio::test]
+    async fn wait_until_open_polls_until_clock_flips() {
+        let closed = r#"{
+            "timestamp": "2024-06-03T13:29:59Z", "is_open": false,
+            "next_open": "2024-06-03T13:30:00Z", "next_close": "2024-06-03T20:00:00Z"
+        }"#;
+        let open = r#"{
+            "timestamp": "2024-06-03T13:30:00Z", "is_open": true,
+            "next_open": "2024-06-04T13:30:00Z", "next_close": "2024-06-03T20:00:00Z"
+        }"#;
+        let (client, transport) = mock_client(
+            MockTransport::new()
+                .respond(Method::GET, "/v2/clock", 200, closed)
+                .respond(Method::GET, "/v2/clock", 200, open),
+        );
+        client
+            .wait_until_open(Duration::from_millis(10), Some(Duration::from_secs(5)))
+            .await
+            .unwrap();
+        assert_eq!(transport.requests().len(), 2);
+
+        // Already open: no sleep, one more request.
+        client
+            .wait_until_open(Duration::from_secs(60), None)
+            .await
+            .unwrap();
+        assert_eq!(transport.requests().len(), 3);
+
+        let (client, _) =
+            mock_client(MockTransport::new().respond(Method::GET, "/v2/clock", 200, closed));
+        let err = client
+            .wait_until_open(Duration::from_millis(10), Some(Duration::from_millis(30)))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AlpacaError::Timeout(_)));
+        assert!(err.is_timeout());
+    }
+
+    #[tokio::test]
+    async fn wait_for_fill_polls_until_terminal() {
+        let order = |status| order_json("ord-1", status).to_string();
+        let (client, transport) = mock_client(
+            MockTransport::new()
+                .respond(Method::GET, "/v2/orders/ord-1", 200, &order("new"))
+                .respond(Method::GET, "/v2/orders/ord-1", 200, &order("filled")),
+        );
+        let filled = client
+            .wait_for_fill("ord-1", Duration::from_secs(5), Duration::from_millis(10))
+            .await
+            .unwrap();
+        assert_eq!(filled.status, "filled");
+        assert_eq!(transport.requests().len(), 2);
+
+        let (client, _) = mock_client(MockTransport::new().respond(
+            Method::GET,
+            "/v2/orders/ord-1",
+            200,
+            &order("accepted"),
+        ));
+        let err = client
+            .wait_for_fill(
+                "ord-1",
+                Duration::from_millis(30),
+                Duration::from_millis(10),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AlpacaError::Timeout(_)));
+    }
+
+    fn leg(symbol: &str, ratio_qty: u32, side: &str, intent: &str) -> OptionLeg {
+        OptionLeg {
+            symbol: symbol.to_string(),
+            ratio_qty,
+            side: side.to_string(),
+            position_intent: Some(intent.to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn submit_multileg_order_validates_and_posts_mleg() {
+        let mut accepted = order_json("ord-1", "accepted");
+        accepted["order_class"] = "mleg".into();
+        let (client, transport) = mock_client(MockTransport::new().respond(
+            Method::POST,
+            "/v2/orders",
+            200,
+            &accepted.to_string(),
+        ));
+
+        let single = vec![leg("AAPL240621C00190000", 1, "buy", "buy_to_open")];
+        let err = client
+            .submit_multileg_order(single, 1, "limit", "day", Some(Decimal::ONE))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AlpacaError::Config(_)));
+
+        let zero_ratio = vec![
+            leg("AAPL240621C00190000", 1, "buy", "buy_to_open"),
+            leg("AAPL240621C00200000", 0, "sell", "sell_to_open"),
+        ];
+        let err = client
+            .submit_multileg_order(zero_ratio, 1, "limit", "day", Some(Decimal::ONE))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AlpacaError::Config(_)));
+
+        let spread = vec![
+            leg("AAPL240621C00190000", 1, "buy", "buy_to_open"),
+            leg("AAPL240621C00200000", 1, "sell", "sell_to_open"),
+        ];
+        for (order_type, tif, limit_price, reason) in [
+            ("stop", "day", None, "type must be market or limit"),
+            ("market", "gtc", None, "time_in_force must be day"),
+            ("limit", "day", None, "needs a limit_price"),
+        ] {
+            let err = client
+                .submit_multileg_order(spread.clone(), 1, order_type, tif, limit_price)
+                .await
+                .unwrap_err();
+            assert!(
+                matches!(&err, AlpacaError::Config(msg) if msg.contains(reason)),
+                "{err:?}"
+            );
+        }
+        assert!(transport.requests().is_empty());
+
+        let spread = vec![
+            leg("AAPL240621C00190000", 1, "buy", "buy_to_open"),
+            leg("AAPL240621C00200000", 1, "sell", "sell_to_open"),
+        ];
+        let order = client
+            .submit_multileg_order(spread, 2, "limit", "day", Some(Decimal::new(325, 2)))
+            .await
+            .unwrap();
+        assert_eq!(order.order_class.as_deref(), Some("mleg"));
+        let body = transport.requests()[0].body.clone().unwrap();
+        assert_eq!(body["order_class"], "mleg");
+        assert_eq!(body["legs"].as_array().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn get_order_chain_follows_replaced_by() {
+        let replaced = |id, by: &str| {
+            let mut order = order_json(id, "replaced");
+            order["replaced_by"] = by.into();
+            order.to_string()
+        };
+        let (client, transport) = mock_client(
+            MockTransport::new()
+                .respond(Method::GET, "/v2/orders/a", 200, &replaced("a", "b"))
+                .respond(Method::GET, "/v2/orders/b", 200, &replaced("b", "c"))
+                .respond(
+                    Method::GET,
+                    "/v2/orders/c",
+                    200,
+                    &order_json("c", "new").to_string(),
+                ),
+        );
+        let chain = client.get_order_chain("a").await.unwrap();
+        let ids: Vec<&str> = chain.iter().map(|o| o.id.as_str()).collect();
+        assert_eq!(ids, ["a", "b", "c"]);
+        assert_eq!(transport.requests().len(), 3);
+
+        let (client, transport) = mock_client(
+            MockTransport::new()
+                .respond(Method::GET, "/v2/orders/a", 200, &replaced("a", "b"))
+                .respond(Method::GET, "/v2/orders/b", 200, &replaced("b", "a")),
+        );
+        assert_eq!(client.get_order_chain("a").await.unwrap().len(), 2);
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[test]
+    fn invalid_proxy_in_config_fails_client_construction() {
+        let config = AlpacaConfig::builder()
+            .api_key("key")
+            .api_secret("secret")
+            .proxy("::not a url::")
+            .build()
+            .unwrap();
+        match AlpacaClient::new(config) {
+            Err(AlpacaError::Config(msg)) => assert!(msg.contains("invalid proxy URL"), "{msg}"),
+            other => panic!("expected Config error, got {:?}", other.err()),
+        }
+    }
+
+    #[tokio::test]
+    async fn clones_share_the_transport() {
+        let (client, transport) = mock_client(MockTransport::new().respond(
+            Method::GET,
+            "/v2/clock",
+            200,
+            r#"{"timestamp":"2024-06-03T14:00:00Z","is_open":true,
+                    "next_open":"2024-06-04T13:30:00Z","next_close":"2024-06-03T20:00:00Z"}"#,
+        ));
+        let clone = client.clone();
+        let task = tokio::spawn(async move { clone.is_market_open().await });
+
+        assert!(client.is_market_open().await.unwrap());
+        assert!(task.await.unwrap().unwrap());
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn multi_status_results_report_each_item() {
+        let (client, transport) = mock_client(
+            MockTransport::new()
+                .respond(
+                    Method::DELETE,
+                    "/v2/orders",
+                    207,
+                    r#"[
+                        {"id": "ord-1", "status": 200},
+                        {"id": "ord-2", "status": 422,
+                         "body": {"code": 42210000, "message": "order is not cancelable"}}
+                    ]"#,
+                )
+                .respond(
+                    Method::DELETE,
+                    "/v2/positions",
+                    207,
+                    r#"[
+                        {"symbol": "AAPL", "status": 200, "body": {"id": "close-1"}},
+                        {"symbol": "TSLA", "status": 422,
+                         "body": {"code": 42210000, "message": "insufficient qty"}}
+                    ]"#,
+                ),
+        );
+
+        let cancels = client.cancel_all_orders().await.unwrap();
+        let succeeded: Vec<bool> = cancels.iter().map(|c| c.is_success()).collect();
+        assert_eq!(succeeded, [true, false]);
+        assert_eq!(cancels[1].status, 422);
+
+        let closes = client.close_all_positions(true).await.unwrap();
+        assert!(closes[0].is_success());
+        assert_eq!(closes[1].symbol, "TSLA");
+        assert!(!closes[1].is_success());
+        assert!(transport.requests()[1]
+            .url
+            .ends_with("/v2/positions?cancel_orders=true"));
+    }
+
+    #[tokio::test]
+    async fn market_data_url_overrides_route_per_category() {
+        let (client, transport) = mock_client(
+            MockTransport::new()
+                .respond(
+                    Method::GET,
+                    "/v2/stocks/AAPL/quotes/latest",
+                    200,
+                    r#"{"symbol":"AAPL","quote":{"ap":"151.02","as":2,"ax":"Q","bp":"150.98","bs":1,"bx":"Q","t":"2024-06-03T15:00:00Z","z":"C"}}"#,
+                )
+                .respond(
+                    Method::GET,
+                    "/v1beta3/crypto/us/latest/quotes",
+                    200,
+                    r#"{"quotes":{"BTC/USD":{"ap":67251,"as":0.25,"bp":67249,"bs":0.4,"t":"2024-06-03T15:00:00Z"}}}"#,
+                ),
+        );
+        let client = client
+            .with_market_data_url("http://stocks-cache.local/")
+            .unwrap()
+            .with_crypto_data_url("http://crypto-cache.local")
+            .unwrap();
+
+        let quote = client.get_latest_quote("AAPL").await.unwrap();
+        assert_eq!(quote.quote.bid_exchange, Some(Exchange::Nasdaq));
+        let crypto = client.get_latest_crypto_quotes(&["BTC/USD"]).await.unwrap();
+        assert_eq!(crypto.quotes["BTC/USD"].ask_size, Decimal::new(25, 2));
+        client.get_account().await.unwrap_err();
+
+        let requests = transport.requests();
+        assert_eq!(
+            requests[0].url,
+            "http://stocks-cache.local/v2/stocks/AAPL/quotes/latest"
+        );
+        assert_eq!(
+            requests[1].url,
+            "http://crypto-cache.local/v1beta3/crypto/us/latest/quotes"
+        );
+        assert_eq!(
+            requests[1].query,
+            vec![("symbols".to_string(), "BTC/USD".to_string())]
+        );
+        assert!(requests[2]
+            .url
+            .starts_with("https://paper-api.alpaca.markets/"));
+    }
+
+    #[test]
+    fn data_url_overrides_are_validated() {
+        let (client, _) = mock_client(MockTransport::new());
+        for url in ["ftp://cache.local", "http://cache.local/?feed=sip"] {
+            let err = client.with_market_data_url(url).err().expect(url);
+            assert!(
+                matches!(&err, AlpacaError::Config(msg) if msg.contains("market data URL")),
+                "{err:?}"
+            );
+            let err = client.with_crypto_data_url(url).err().expect(url);
+            assert!(
+                matches!(&err, AlpacaError::Config(msg) if msg.contains("crypto data URL")),
+                "{err:?}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn polling_helpers_follow_injected_clock() {
+        let closed = r#"{
+            "timestamp": "2024-06-03T13:00:00Z", "is_open": false,
+            "next_open": "2024-06-03T13:30:00Z", "next_close": "2024-06-03T20:00:00Z"
+        }"#;
+        let (client, transport) =
+            mock_client(MockTransport::new().respond(Method::GET, "/v2/clock", 200, closed));
+        let start: DateTime<Utc> = "2024-06-03T13:00:00Z".parse().unwrap();
+        let clock = Arc::new(MockClock::new(start));
+        let client = client.with_clock(clock.clone());
+
+        // An hour-long wait finishes instantly: each sleep just advances the clock.
+        let err = client
+            .wait_until_open(Duration::from_secs(600), Some(Duration::from_secs(3600)))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AlpacaError::Timeout(_)));
+        assert_eq!(clock.now(), start + chrono::Duration::hours(1));
+        assert_eq!(transport.requests().len(), 7);
+
+        let response = client.get_clock().await.unwrap();
+        clock.set("2024-06-03T13:20:00Z".parse().unwrap());
+        assert_eq!(
+            response.time_until_open_at(clock.now()),
+            Duration::from_secs(600)
+        );
+        assert_eq!(response.time_until_open(), Duration::from_secs(1800));
+
+        // Date ranges are checked against the injected clock's "today".
+        clock.set("2024-06-01T12:00:00Z".parse().unwrap());
+        let err = client
+            .get_bars(
+                "AAPL",
+                &BarsRequest::new(
+                    NaiveDate::from_ymd_opt(2024, 5, 1).unwrap(),
+                    NaiveDate::from_ymd_opt(2024, 6, 3).unwrap(),
+                    TimeFrame::Day,
+                ),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("in the future"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn cancel_orders_reports_each_outcome() {
+        let (client, transport) =
+            mock_client(MockTransport::new().respond(Method::DELETE, "/v2/orders/ord-1", 204, ""));
+        let results = client.cancel_orders(&["ord-1", "ord-gone"]).await;
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].0, "ord-1");
+        assert!(results[0].1.is_ok());
+        assert_eq!(results[1].0, "ord-gone");
+        assert!(matches!(results[1].1, Err(AlpacaError::NotFound { .. })));
+        assert_eq!(transport.requests().len(), 2);
+        assert!(client.cancel_orders(&[]).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn asset_flag_helpers() {
+        let asset = |symbol: &str, shortable: bool, etb: bool| {
+            format!(
+                r#"{{"id":"a-{symbol}","class":"us_equity","exchange":"NASDAQ","symbol":"{symbol}",
+                    "status":"active","tradable":true,"marginable":true,"shortable":{shortable},
+                    "easy_to_borrow":{etb},"fractionable":{shortable}}}"#
+            )
+        };
+        let (client, _) = mock_client(
+            MockTransport::new()
+                .respond(
+                    Method::GET,
+                    "/v2/assets/AAPL",
+                    200,
+                    &asset("AAPL", true, true),
+                )
+                .respond(
+                    Method::GET,
+                    "/v2/assets/GME",
+                    200,
+                    &asset("GME", true, false),
+                )
+                .respond(
+                    Method::GET,
+                    "/v2/assets/XYZ",
+                    200,
+                    &asset("XYZ", false, false),
+                ),
+        );
+
+        assert!(client.is_shortable("AAPL").await.unwrap());
+        assert!(!client.is_shortable("GME").await.unwrap());
+        assert!(!client.is_shortable("XYZ").await.unwrap());
+        assert!(client.is_fractionable("AAPL").await.unwrap());
+        assert!(!client.is_fractionable("XYZ").await.unwrap());
+        assert!(client.is_tradable("XYZ").await.unwrap());
+        assert!(matches!(
+            client.is_tradable("NOPE").await,
+            Err(AlpacaError::NotFound { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn get_crypto_orderbook_requests_pairs() {
+        let (client, transport) = mock_client(MockTransport::new().respond(
+            Method::GET,
+            "/v1beta3/crypto/us/latest/orderbooks",
+            200,
+            r#"{"orderbooks":{
+                "BTC/USD":{"a":[{"p":67251,"s":0.25}],"b":[{"p":67249,"s":0.4}],"t":"2024-06-03T15:00:00Z"},
+                "ETH/USD":{"a":[],"b":[],"t":"2024-06-03T15:00:00Z"}
+            }}"#,
+        ));
+        let books = client
+            .get_crypto_orderbook(&["BTC/USD", "ETH/USD"])
+            .await
+            .unwrap()
+            .orderbooks;
+        assert_eq!(books["BTC/USD"].bids[0].price, Decimal::from(67249));
+        assert!(books["ETH/USD"].asks.is_empty());
+        assert_eq!(
+            transport.requests()[0].query,
+            vec![("symbols".to_string(), "BTC/USD,ETH/USD".to_string())]
+        );
+    }
+
+    #[test]
+    fn validate_order_matrix() {
+        let order = |order_type: &str, tif: &str, class: Option<&str>, limit: Option<i64>| {
+            AlpacaOrderRequest {
+                symbol: "AAPL".into(),
+                qty: 1,
+                side: "buy".into(),
+                order_type: order_type.into(),
+                time_in_force: tif.into(),
+                limit_price: limit.map(Decimal::from),
+                stop_price: None,
+                trail_percent: None,
+                trail_price: None,
+                extended_hours: false,
+                client_order_id: None,
+                order_class: class.map(str::to_string),
+                position_intent: None,
+            }
+        };
+        let valid = [
+            order("market", "day", None, None),
+            order("market", "opg", None, None),
+            order("limit", "ioc", None, Some(150)),
+            order("limit", "fok", Some("simple"), Some(150)),
+            AlpacaOrderRequest {
+                stop_price: Some(Decimal::from(140)),
+                ..order("stop", "gtc", None, None)
+            },
+            AlpacaOrderRequest {
+                stop_price: Some(Decimal::from(140)),
+                ..order("stop_limit", "day", None, Some(139))
+            },
+            AlpacaOrderRequest {
+                trail_percent: Some(Decimal::from(5)),
+                ..order("trailing_stop", "day", None, None)
+            },
+            AlpacaOrderRequest {
+                trail_price: Some(Decimal::from(2)),
+                ..order("trailing_stop", "gtc", None, None)
+            },
+            order("market", "gtc", Some("bracket"), None),
+            order("limit", "day", Some("oco"), Some(150)),
+            order("limit", "gtc", Some("oto"), Some(150)),
+        ];
+        for o in &valid {
+            assert!(validate_order(o).is_ok(), "{o:?}");
+        }
+
+        let invalid = [
+            (
+                order("market", "ioc", Some("bracket"), None),
+                "bracket orders need",
+            ),
+            (
+                order("stop", "fok", None, None),
+                "only applies to market and limit",
+            ),
+            (order("stop_limit", "opg", None, Some(150)), "only applies"),
+            (
+                order("stop", "day", Some("bracket"), None),
+                "market or limit entry",
+            ),
+            (
+                order("market", "day", Some("oco"), None),
+                "oco orders must be type limit",
+            ),
+            (order("limit", "day", None, None), "need a limit_price"),
+            (
+                order("market", "day", None, Some(150)),
+                "take no limit_price",
+            ),
+            (
+                order("market", "week", None, None),
+                "unknown time_in_force `week`",
+            ),
+            (
+                order("stop", "day", None, None),
+                "stop orders need a stop_price",
+            ),
+            (
+                order("stop_limit", "day", None, Some(139)),
+                "stop_limit orders need a stop_price",
+            ),
+            (
+                order("trailing_stop", "day", None, None),
+                "exactly one of trail_price or trail_percent",
+            ),
+            (
+                AlpacaOrderRequest {
+                    trail_price: Some(Decimal::from(2)),
+                    trail_percent: Some(Decimal::from(5)),
+                    ..order("trailing_stop", "day", None, None)
+                },
+                "exactly one of trail_price or trail_percent",
+            ),
+            (order("stoplimit", "day", None, None), "unknown type"),
+            (
+                order("limit", "day", Some("mleg"), Some(1)),
+                "unknown order_class",
+            ),
+        ];
+        for (o, reason) in &invalid {
+            let err = validate_order(o).unwrap_err();
+            assert!(matches!(err, AlpacaError::Config(_)));
+            assert!(err.to_string().contains(reason), "{o:?}: {err}");
+        }
+
+        let mut extended = order("limit", "day", None, Some(150));
+        extended.extended_hours = true;
+        assert!(validate_order(&extended).is_ok());
+        extended.order_type = "market".into();
+        extended.limit_price = None;
+        assert!(validate_order(&extended)
+            .unwrap_err()
+            .to_string()
+            .contains("extended_hours"));
+    }
+
+    #[tokio::test]
+    async fn invalid_orders_are_not_sent() {
+        let (client, transport) = mock_client(MockTransport::new());
+        let err = client
+            .submit_order("AAPL", 1, "buy", "stop", "fok", None, false)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AlpacaError::Config(_)));
+        assert!(transport.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn base_urls_are_validated_and_normalized() {
+        let mut config = AlpacaConfig::paper("key".into(), "secret".into());
+        config.trading_base_url = "https://paper-api.alpaca.markets//".into();
+        let transport = Arc::new(MockTransport::new().respond(
+            Method::GET,
+            "/v2/clock",
+            200,
+            r#"{"timestamp":"2024-06-03T10:00:00-04:00","is_open":true,
+                "next_open":"2024-06-04T09:30:00-04:00","next_close":"2024-06-03T16:00:00-04:00"}"#,
+        ));
+        let client = AlpacaClient::with_transport(config.clone(), transport.clone()).unwrap();
+        assert_eq!(
+            client.config().trading_base_url,
+            "https://paper-api.alpaca.markets"
+        );
+        client.get_clock().await.unwrap();
+        assert_eq!(
+            transport.requests()[0].url,
+            "https://paper-api.alpaca.markets/v2/clock"
+        );
+
+        config.market_data_base_url = "htps://data.alpaca.markets".into();
+        assert!(matches!(
+            AlpacaClient::new(config),
+            Err(AlpacaError::Config(msg)) if msg.contains("market data base URL")
+        ));
+    }
+
+    #[tokio::test]
+    async fn page_tokens_are_percent_encoded() {
+        let (client, transport) = mock_client(MockTransport::new().respond(
+            Method::GET,
+            "/v2/stocks/AAPL/trades",
+            200,
+            r#"{"trades":[]}"#,
+        ));
+        let day = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+        client
+            .get_trades_page("AAPL", TicksRequest::new(day, day), Some("QUFQTA+/=&x"))
+            .await
+            .unwrap();
+        let url = &transport.requests()[0].url;
+        assert!(url.ends_with("&page_token=QUFQTA%2B%2F%3D%26x"), "{url}");
+    }
+
+    #[tokio::test]
+    async fn path_segments_are_percent_encoded() {
+        let order = order_json("ord-1", "accepted").to_string();
+        let (client, transport) = mock_client(
+            MockTransport::new()
+                .respond(Method::DELETE, "/v2/positions/BTC%2FUSD", 200, &order)
+                .respond(Method::GET, "/v2/orders/my%20order%201", 200, &order),
+        );
+
+        client.close_position("BTC/USD").await.unwrap();
+        client.get_order("my order 1").await.unwrap();
+        let urls: Vec<_> = transport.requests().into_iter().map(|r| r.url).collect();
+        assert_eq!(
+            urls,
+            [
+                "https://paper-api.alpaca.markets/v2/positions/BTC%2FUSD",
+                "https://paper-api.alpaca.markets/v2/orders/my%20order%201",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn list_all_orders_walks_time_windows() {
+        let first = format!(
+            "[{},{}]",
+            order_at("o3", "2024-06-03T15:00:00.123456Z"),
+            order_at("o2", "2024-06-03T14:00:00Z")
+        );
+        // The boundary order comes back again in the next window.
+        let second = format!(
+            "[{},{}]",
+            order_at("o2", "2024-06-03T14:00:00Z"),
+            order_at("o1", "2024-06-03T13:00:00Z")
+        );
+        let (client, transport) = mock_client(
+            MockTransport::new()
+                .respond(Method::GET, "/v2/orders", 200, &first)
+                .respond(Method::GET, "/v2/orders", 200, &second)
+                .respond(Method::GET, "/v2/orders", 200, "[]"),
+        );
+
+        let until = "2024-06-04T00:00:00Z".parse().unwrap();
+        let params = ListOrdersParams::default()
+            .status("closed")
+            .limit(2)
+            .until(until)
+            .symbols(&["AAPL", "BTC/USD"]);
+        let orders = client.list_all_orders(&params).await.unwrap();
+
+        let ids: Vec<_> = orders.iter().map(|o| o.id.as_str()).collect();
+        assert_eq!(ids, ["o3", "o2", "o1"]);
+        let urls: Vec<_> = transport.requests().into_iter().map(|r| r.url).collect();
+        assert_eq!(urls.len(), 3);
+        assert!(urls[0].contains("?status=closed&direction=desc&limit=2&symbols=AAPL%2CBTC%2FUSD"));
+        assert!(
+            urls[0].ends_with("&until=2024-06-04T00%3A00%3A00Z"),
+            "{}",
+            urls[0]
+        );
+        assert!(
+            urls[1].ends_with("&until=2024-06-03T14%3A00%3A00.000000001Z"),
+            "{}",
+            urls[1]
+        );
+        assert!(
+            urls[2].ends_with("&until=2024-06-03T13%3A00%3A00.000000001Z"),
+            "{}",
+            urls[2]
+        );
+    }
+
+    #[tokio::test]
+    async fn list_all_orders_keeps_orders_sharing_the_boundary_timestamp() {
+        let page = |orders: &[(&str, &str)]| {
+            let orders: Vec<_> = orders.iter().map(|(id, at)| order_at(id, at)).collect();
+            serde_json::Value::from(orders).to_string()
+        };
+        let (t3, t2, t1) = (
+            "2024-06-03T15:00:00Z",
+            "2024-06-03T14:00:00Z",
+            "2024-06-03T13:00:00Z",
+        );
+        // o4, o3 and o2 share t2; the first page cuts off after o3.
+        let (client, transport) = mock_client(
+            MockTransport::new()
+                .respond(
+                    Method::GET,
+                    "/v2/orders",
+                    200,
+                    &page(&[("o5", t3), ("o4", t2), ("o3", t2)]),
+                )
+                .respond(
+                    Method::GET,
+                    "/v2/orders",
+                    200,
+                    &page(&[("o4", t2), ("o3", t2), ("o2", t2)]),
+                )
+                .respond(Method::GET, "/v2/orders", 200, &page(&[("o1", t1)])),
+        );
+
+        let orders = client
+            .list_all_orders(&ListOrdersParams::default().limit(3))
+            .await
+            .unwrap();
+
+        let ids: Vec<_> = orders.iter().map(|o| o.id.as_str()).collect();
+        assert_eq!(ids, ["o5", "o4", "o3", "o2", "o1"]);
+        let urls: Vec<_> = transport.requests().into_iter().map(|r| r.url).collect();
+        assert_eq!(urls.len(), 3);
+        assert!(!urls[0].contains("until="), "{}", urls[0]);
+        // The second window still includes t2...
+        assert!(urls[1].ends_with("&until=2024-06-03T14%3A00%3A00.000000001Z"));
+        // ...and once a full page sits at t2, the walk steps past it.
+        assert!(urls[2].ends_with("&until=2024-06-03T14%3A00%3A00Z"));
+    }
+
+    #[tokio::test]
+    async fn list_all_orders_stops_on_short_page() {
+        let page = format!("[{}]", order_at("o1", "2024-06-03T13:00:00Z"));
+        let (client, transport) =
+            mock_client(MockTransport::new().respond(Method::GET, "/v2/orders", 200, &page));
+        let orders = client
+            .list_all_orders(&ListOrdersParams::default())
+            .await
+            .unwrap();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(transport.requests().len(), 1);
+        assert!(transport.requests()[0]
+            .url
+            .ends_with("/v2/orders?direction=desc&limit=500"));
+    }
+
+    fn paged_bars_transport() -> MockTransport {
+        MockTransport::new()
+            .respond(
+                Method::GET,
+                "/v2/stocks/AAPL/bars",
+                200,
+                &bars_page(&[2, 3], Some("p2")),
+            )
+            .respond(
+                Method::GET,
+                "/v2/stocks/AAPL/bars",
+                200,
+                &bars_page(&[4, 5], Some("p3")),
+            )
+            .respond(
+                Method::GET,
+                "/v2/stocks/AAPL/bars",
+                200,
+                &bars_page(&[8], None),
+            )
+    }
+
+    #[tokio::test]
+    async fn page_size_does_not_cap_total_bars() {
+        let (client, transport) = mock_client(paged_bars_transport());
+        let start = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 1, 8).unwrap();
+        let bars = client
+            .get_bars(
+                "AAPL",
+                &BarsRequest::new(start, end, TimeFrame::Day).page_size(2),
+            )
+            .await
+            .unwrap();
+        assert_eq!(bars.len(), 5);
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests.iter().all(|r| r.url.contains("&limit=2")));
+    }
+
+    #[tokio::test]
+    async fn max_bars_caps_total_and_stops_paging() {
+        let (client, transport) = mock_client(paged_bars_transport());
+        let start = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 1, 8).unwrap();
+        let bars = client
+            .get_bars(
+                "AAPL",
+                &BarsRequest::new(start, end, TimeFrame::Day)
+                    .page_size(2)
+                    .max_bars(3),
+            )
+            .await
+            .unwrap();
+        let days: Vec<_> = bars
+            .iter()
+            .map(|bar| chrono::Datelike::day(&bar.timestamp))
+            .collect();
+        assert_eq!(days, [2, 3, 4]);
+        // The third page is never requested.
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn download_bars_writes_every_page() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 1, 8).unwrap();
+        for (format, header_lines) in [(BarFormat::Ndjson, 0), (BarFormat::Csv, 1)] {
+            let (client, transport) = mock_client(
+                MockTransport::new()
+                    .respond(
+                        Method::GET,
+                        "/v2/stocks/AAPL/bars",
+                        200,
+                        &bars_page(&[2, 3], Some("p2")),
+                    )
+                    .respond(
+                        Method::GET,
+                        "/v2/stocks/AAPL/bars",
+                        200,
+                        &bars_page(&[4], None),
+                    ),
+            );
+            let mut out = Vec::new();
+            let written = client
+                .download_bars_to_writer(
+                    "AAPL",
+                    &BarsRequest::new(start, end, TimeFrame::Day),
+                    format,
+                    &mut out,
+                )
+                .await
+                .unwrap();
+            assert_eq!(written, 3);
+            assert_eq!(transport.requests().len(), 2);
+
+            let text = String::from_utf8(out).unwrap();
+            let lines: Vec<_> = text.lines().collect();
+            assert_eq!(lines.len(), header_lines + 3, "{text}");
+            if format == BarFormat::Ndjson {
+                let last: AlpacaBar = serde_json::from_str(lines[2]).unwrap();
+                assert_eq!(chrono::Datelike::day(&last.timestamp), 4);
+            }
+        }
+    }
+
+    /// Delays every response and records the most requests in flight at once.
+    struct SlowTransport {
+        inner: MockTransport,
+        in_flight: std::sync::atomic::AtomicUsize,
+        peak: std::sync::atomic::AtomicUsize,
+    }
+
+    impl api_client_core::HttpTransport for SlowTransport {
+        fn send(
+            &self,
+            request: api_client_core::HttpRequest,
+        ) -> futures_util::future::BoxFuture<
+            '_,
+            Result<api_client_core::HttpResponse, api_client_core::ApiClientError>,
+        > {
+            use std::sync::atomic::Ordering;
+
+            Box::pin(async move {
+                let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                self.peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                let response = self.inner.send(request).await;
+                self.in_flight.fetch_sub(1, Ordering::SeqCst);
+                response
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn get_bars_for_symbols_bounds_concurrency_and_isolates_failures() {
+        let transport = Arc::new(SlowTransport {
+            inner: MockTransport::new()
+                .respond(
+                    Method::GET,
+                    "/v2/stocks/AAPL/bars",
+                    200,
+                    &bars_page(&[2, 3], Some("p2")),
+                )
+                .respond(
+                    Method::GET,
+                    "/v2/stocks/AAPL/bars",
+                    200,
+                    &bars_page(&[4], None),
+                )
+                .respond(
+                    Method::GET,
+                    "/v2/stocks/SPY/bars",
+                    200,
+                    &bars_page(&[2], None),
+                )
+                .respond(
+                    Method::GET,
+                    "/v2/stocks/QQQ/bars",
+                    200,
+                    &bars_page(&[2, 3], None),
+                )
+                .respond(
+                    Method::GET,
+                    "/v2/stocks/BAD/bars",
+                    422,
+                    r#"{"code":42210000,"message":"invalid symbol"}"#,
+                ),
+            in_flight: Default::default(),
+            peak: Default::default(),
+        });
+        let config = AlpacaConfig::paper("key".into(), "secret".into());
+        let client = AlpacaClient::with_transport(config, transport.clone())
+            .unwrap()
+            .with_rate_limiter(Arc::new(RateLimiter::per_minute(1000)));
+        let start = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 1, 8).unwrap();
+
+        let results = client
+            .get_bars_for_symbols(
+                &["AAPL", "SPY", "QQQ", "BAD"],
+                &BarsRequest::new(start, end, TimeFrame::Day),
+                2,
+            )
+            .await;
+
+        assert_eq!(transport.peak.load(std::sync::atomic::Ordering::SeqCst), 2);
+        assert_eq!(results.len(), 4);
+        assert_eq!(results["AAPL"].as_ref().unwrap().len(), 3);
+        assert_eq!(results["SPY"].as_ref().unwrap().len(), 1);
+        assert_eq!(results["QQQ"].as_ref().unwrap().len(), 2);
+        assert_eq!(
+            results["BAD"].as_ref().unwrap_err().status_code(),
+            Some(422)
+        );
+        assert_eq!(transport.inner.requests().len(), 5);
+    }
+
+    #[tokio::test]
+    async fn with_timeout_keeps_injected_transport() {
+        let (client, transport) = mock_client(MockTransport::new().respond(
+            Method::GET,
+            "/v2/clock",
+            200,
+            r#"{"timestamp":"2024-06-03T13:00:00Z","is_open":true,
+                "next_open":"2024-06-04T13:30:00Z","next_close":"2024-06-03T20:00:00Z"}"#,
+        ));
+        let client = client
+            .with_rate_limiter(Arc::new(RateLimiter::per_minute(1000)))
+            .with_timeout(Duration::from_secs(3));
+        assert_eq!(client.config().timeout, Duration::from_secs(3));
+        assert!(client.trading.rate_limiter().is_some());
+
+        client.get_clock().await.unwrap();
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].timeout, Some(Duration::from_secs(3)));
+    }
+
+    #[tokio::test]
+    async fn max_bars_counts_boundary_duplicates_once() {
+        let (client, transport) = mock_client(
+            MockTransport::new()
+                .respond(
+                    Method::GET,
+                    "/v2/stocks/AAPL/bars",
+                    200,
+                    &bars_page(&[2, 3], Some("p2")),
+                )
+                .respond(
+                    Method::GET,
+                    "/v2/stocks/AAPL/bars",
+                    200,
+                    &bars_page(&[3, 4], Some("p3")),
+                )
+                .respond(
+                    Method::GET,
+                    "/v2/stocks/AAPL/bars",
+                    200,
+                    &bars_page(&[5], None),
+                ),
+        );
+        let start = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 1, 8).unwrap();
+        let bars = client
+            .get_bars(
+                "AAPL",
+                &BarsRequest::new(start, end, TimeFrame::Day)
+                    .page_size(2)
+                    .max_bars(3),
+            )
+            .await
+            .unwrap();
+        let days: Vec<_> = bars
+            .iter()
+            .map(|bar| chrono::Datelike::day(&bar.timestamp))
+            .collect();
+        assert_eq!(days, [2, 3, 4]);
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn bars_page_and_stream_forward_sort_and_currency() {
+        let (client, transport) = mock_client(paged_bars_transport());
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 1, 31).unwrap();
+        let request = BarsRequest::new(start, end, TimeFrame::Day)
+            .sort(SortDirection::Desc)
+            .currency("EUR")
+            .max_bars(3);
+
+        let (page, _) = client.get_bars_page("AAPL", &request, None).await.unwrap();
+        assert_eq!(page.len(), 2);
+        let streamed: Vec<_> = client
+            .get_bars_stream("AAPL", &request)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(streamed.len(), 3);
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 3);
+        for request in &requests {
+            assert!(
+                request.url.contains("&sort=desc&currency=EUR"),
+                "{}",
+                request.url
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn ticks_requests_forward_sort_and_currency() {
+        let (client, transport) = mock_client(
+            MockTransport::new()
+                .respond(
+                    Method::GET,
+                    "/v2/stocks/AAPL/trades",
+                    200,
+                    r#"{"trades":[]}"#,
+                )
+                .respond(
+                    Method::GET,
+                    "/v2/stocks/AAPL/trades",
+                    200,
+                    r#"{"trades":[]}"#,
+                )
+                .respond(
+                    Method::GET,
+                    "/v2/stocks/AAPL/quotes",
+                    200,
+                    r#"{"quotes":[]}"#,
+                ),
+        );
+        let day = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+        let request = TicksRequest::new(day, day)
+            .sort(SortDirection::Desc)
+            .currency("EUR")
+            .page_size(50);
+
+        client.get_trades("AAPL", request.clone()).await.unwrap();
+        client
+            .get_trades_page("AAPL", request.clone(), None)
+            .await
+            .unwrap();
+        client.get_quotes("AAPL", request).await.unwrap();
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 3);
+        for request in &requests {
+            assert!(
+                request.url.contains("&limit=50&sort=desc&currency=EUR"),
+                "{}",
+                request.url
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn get_quotes_pages_and_drops_boundary_repeats() {
+        let quote = |second: u32, bid: &str| {
+            format!(
+                r#"{{"t":"2024-01-02T14:30:{second:02}Z","bp":"{bid}","bs":"1","ap":"190","as":"2","c":["R"]}}"#
+            )
+        };
+        let page1 = format!(
+            r#"{{"quotes":[{},{}],"symbol":"AAPL","next_page_token":"q2"}}"#,
+            quote(1, "189"),
+            quote(2, "189.5")
+        );
+        let page2 = format!(
+            r#"{{"quotes":[{},{}],"symbol":"AAPL","next_page_token":null}}"#,
+            quote(2, "189.5"),
+            quote(2, "189.6")
+        );
+        let (client, transport) = mock_client(
+            MockTransport::new()
+                .respond(Method::GET, "/v2/stocks/AAPL/quotes", 200, &page1)
+                .respond(Method::GET, "/v2/stocks/AAPL/quotes", 200, &page2),
+        );
+        let day = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+
+        let quotes = client
+            .get_quotes("AAPL", TicksRequest::new(day, day).page_size(2))
+            .await
+            .unwrap();
+        let bids: Vec<_> = quotes.iter().map(|q| q.bid_price.to_string()).collect();
+        assert_eq!(bids, ["189", "189.5", "189.6"]);
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0]
+            .url
+            .contains("/v2/stocks/AAPL/quotes?start=2024-01-02&"));
+        assert!(
+            requests[1].url.ends_with("&page_token=q2"),
+            "{}",
+            requests[1].url
+        );
+    }
+
+    #[tokio::test]
+    async fn async_download_matches_sync_output() {
+        let request = BarsRequest::new(
+            NaiveDate::from_ymd_opt(2024, 1, 2).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 1, 8).unwrap(),
+            TimeFrame::Day,
+        );
+        for format in [BarFormat::Ndjson, BarFormat::Csv] {
+            let (client, _) = mock_client(paged_bars_transport());
+            let mut sync_out = Vec::new();
+            let sync_written = client
+                .download_bars_to_writer("AAPL", &request, format, &mut sync_out)
+                .await
+                .unwrap();
+
+            let (client, transport) = mock_client(paged_bars_transport());
+            let mut async_out = Vec::new();
+            let async_written = client
+                .download_bars_to_async_writer("AAPL", &request, format, &mut async_out)
+                .await
+                .unwrap();
+
+            assert_eq!(async_written, sync_written);
+            assert_eq!(transport.requests().len(), 3);
+            assert_eq!(
+                String::from_utf8(async_out).unwrap(),
+                String::from_utf8(sync_out).unwrap()
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn downloads_write_a_boundary_repeat_once() {
+        let overlapping = || {
+            MockTransport::new()
+                .respond(
+                    Method::GET,
+                    "/v2/stocks/AAPL/bars",
+                    200,
+                    &bars_page(&[2, 3], Some("p2")),
+                )
+                .respond(
+                    Method::GET,
+                    "/v2/stocks/AAPL/bars",
+                    200,
+                    &bars_page(&[3, 4], None),
+                )
+        };
+        let request = BarsRequest::new(
+            NaiveDate::from_ymd_opt(2024, 1, 2).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 1, 8).unwrap(),
+            TimeFrame::Day,
+        );
+        for (format, rows) in [(BarFormat::Ndjson, 3), (BarFormat::Csv, 4)] {
+            let (client, _) = mock_client(overlapping());
+            let mut out = Vec::new();
+            let written = client
+                .download_bars_to_writer("AAPL", &request, format, &mut out)
+                .await
+                .unwrap();
+            assert_eq!(written, 3);
+            assert_eq!(String::from_utf8(out).unwrap().lines().count(), rows);
+
+            let (client, _) = mock_client(overlapping());
+            let mut out = Vec::new();
+            let written = client
+                .download_bars_to_async_writer("AAPL", &request, format, &mut out)
+                .await
+                .unwrap();
+            assert_eq!(written, 3);
+            assert_eq!(String::from_utf8(out).unwrap().lines().count(), rows);
+        }
+
+        let (client, _) = mock_client(overlapping());
+        let days: Vec<u32> = client
+            .get_bars_stream("AAPL", &request.max_bars(2))
+            .map_ok(|bar| chrono::Datelike::day(&bar.timestamp))
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(days, [2, 3]);
+    }
 }
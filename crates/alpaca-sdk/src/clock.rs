@@ -0,0 +1,104 @@
+//! Wall-clock time and sleeping behind a trait, so the client's
+//! time-dependent helpers ([`AlpacaClient::wait_until_open`],
+//! [`AlpacaClient::wait_for_fill`], date-range checks) can be driven
+//! deterministically in tests.
+//!
+//! [`AlpacaClient::wait_until_open`]: crate::AlpacaClient::wait_until_open
+//! [`AlpacaClient::wait_for_fill`]: crate::AlpacaClient::wait_for_fill
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use futures_util::future::BoxFuture;
+
+/// Source of the current time and of delays.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Resolve after `duration` has passed on this clock.
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()>;
+}
+
+/// [`Clock`] backed by the system time and `tokio::time::sleep`; the default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+#[cfg(any(test, feature = "testing"))]
+pub use mock::MockClock;
+
+#[cfg(any(test, feature = "testing"))]
+mod mock {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    /// [`Clock`] frozen at a chosen instant. Sleeping returns immediately and
+    /// moves the clock forward by the requested duration, so polling loops
+    /// run instantly but still see time pass.
+    #[derive(Debug)]
+    pub struct MockClock {
+        now: Mutex<DateTime<Utc>>,
+    }
+
+    impl MockClock {
+        pub fn new(now: DateTime<Utc>) -> Self {
+            Self {
+                now: Mutex::new(now),
+            }
+        }
+
+        pub fn set(&self, now: DateTime<Utc>) {
+            *self.now.lock().unwrap() = now;
+        }
+
+        pub fn advance(&self, duration: Duration) {
+            let mut now = self.now.lock().unwrap();
+            *now = chrono::Duration::from_std(duration)
+                .ok()
+                .and_then(|delta| now.checked_add_signed(delta))
+                .unwrap_or(DateTime::<Utc>::MAX_UTC);
+        }
+    }
+
+    impl Clock for MockClock {
+        fn now(&self) -> DateTime<Utc> {
+            *self.now.lock().unwrap()
+        }
+
+        fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+            self.advance(duration);
+            Box::pin(std::future::ready(()))
+        }
+    }
+}
+
+/// Time elapsed on `clock` since `start`; zero if the clock went backwards.
+pub(crate) fn elapsed_since(clock: &dyn Clock, start: DateTime<Utc>) -> Duration {
+    (clock.now() - start).to_std().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mock_clock_advances_on_sleep() {
+        let start = "2024-06-03T13:00:00Z".parse().unwrap();
+        let clock = MockClock::new(start);
+        futures_util::FutureExt::now_or_never(clock.sleep(Duration::from_secs(90))).unwrap();
+        assert_eq!(elapsed_since(&clock, start), Duration::from_secs(90));
+
+        clock.set("2024-06-03T12:00:00Z".parse().unwrap());
+        assert_eq!(elapsed_since(&clock, start), Duration::ZERO);
+    }
+}
@@ -0,0 +1,280 @@
+//! Equity trade condition codes, e.g. the `conditions` of an
+//! [`AlpacaTrade`](crate::types::AlpacaTrade) or
+//! [`AlpacaStreamTrade`](crate::types::AlpacaStreamTrade).
+//!
+//! Codes follow the consolidated (CTA/UTP) sale condition tables. Quote
+//! conditions use a separate table and are not mapped here.
+
+/// A single trade condition code. Codes without a mapping are kept as
+/// [`Unknown`](Self::Unknown).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TradeCondition {
+    /// `@`: regular sale.
+    Regular,
+    /// `A`: acquisition.
+    Acquisition,
+    /// `B`: bunched trade.
+    Bunched,
+    /// `C`: cash sale, settled same day.
+    CashSale,
+    /// `D`: distribution.
+    Distribution,
+    /// `F`: intermarket sweep order.
+    IntermarketSweep,
+    /// `G`: bunched sold trade, reported late.
+    BunchedSold,
+    /// `H`: price variation trade.
+    PriceVariation,
+    /// `I`: odd lot trade.
+    OddLot,
+    /// `K`: rule 155 trade.
+    Rule155,
+    /// `L`: sold last, reported late but in sequence.
+    SoldLast,
+    /// `M`: market center official close.
+    OfficialClose,
+    /// `N`: next day settlement.
+    NextDay,
+    /// `O`: opening prints.
+    OpeningPrints,
+    /// `P`: prior reference price.
+    PriorReferencePrice,
+    /// `Q`: market center official open.
+    OfficialOpen,
+    /// `R`: seller's option.
+    Seller,
+    /// `S`: split trade.
+    Split,
+    /// `T`: extended hours trade (form T).
+    FormT,
+    /// `U`: extended hours trade, sold out of sequence.
+    ExtendedHoursSold,
+    /// `V`: contingent trade.
+    Contingent,
+    /// `W`: average price trade.
+    AveragePrice,
+    /// `X`: cross trade.
+    Cross,
+    /// `Y`: yellow flag regular trade.
+    YellowFlag,
+    /// `Z`: sold out of sequence.
+    SoldOutOfSequence,
+    /// `4`: derivatively priced.
+    DerivativelyPriced,
+    /// `5`: market center reopening trade.
+    Reopening,
+    /// `6`: market center closing trade.
+    ClosingPrints,
+    /// `7`: qualified contingent trade.
+    QualifiedContingent,
+    /// `9`: corrected consolidated close.
+    CorrectedClose,
+    /// Any code without a mapping, kept verbatim.
+    Unknown(String),
+}
+
+impl TradeCondition {
+    /// Map a raw code; surrounding whitespace is ignored.
+    pub fn from_code(code: &str) -> Self {
+        match code.trim() {
+            "@" => Self::Regular,
+            "A" => Self::Acquisition,
+            "B" => Self::Bunched,
+            "C" => Self::CashSale,
+            "D" => Self::Distribution,
+            "F" => Self::IntermarketSweep,
+            "G" => Self::BunchedSold,
+            "H" => Self::PriceVariation,
+            "I" => Self::OddLot,
+            "K" => Self::Rule155,
+            "L" => Self::SoldLast,
+            "M" => Self::OfficialClose,
+            "N" => Self::NextDay,
+            "O" => Self::OpeningPrints,
+            "P" => Self::PriorReferencePrice,
+            "Q" => Self::OfficialOpen,
+            "R" => Self::Seller,
+            "S" => Self::Split,
+            "T" => Self::FormT,
+            "U" => Self::ExtendedHoursSold,
+            "V" => Self::Contingent,
+            "W" => Self::AveragePrice,
+            "X" => Self::Cross,
+            "Y" => Self::YellowFlag,
+            "Z" => Self::SoldOutOfSequence,
+            "4" => Self::DerivativelyPriced,
+            "5" => Self::Reopening,
+            "6" => Self::ClosingPrints,
+            "7" => Self::QualifiedContingent,
+            "9" => Self::CorrectedClose,
+            other => Self::Unknown(other.to_string()),
+        }
+    }
+
+    /// The raw code, as sent by Alpaca.
+    pub fn code(&self) -> &str {
+        match self {
+            Self::Regular => "@",
+            Self::Acquisition => "A",
+            Self::Bunched => "B",
+            Self::CashSale => "C",
+            Self::Distribution => "D",
+            Self::IntermarketSweep => "F",
+            Self::BunchedSold => "G",
+            Self::PriceVariation => "H",
+            Self::OddLot => "I",
+            Self::Rule155 => "K",
+            Self::SoldLast => "L",
+            Self::OfficialClose => "M",
+            Self::NextDay => "N",
+            Self::OpeningPrints => "O",
+            Self::PriorReferencePrice => "P",
+            Self::OfficialOpen => "Q",
+            Self::Seller => "R",
+            Self::Split => "S",
+            Self::FormT => "T",
+            Self::ExtendedHoursSold => "U",
+            Self::Contingent => "V",
+            Self::AveragePrice => "W",
+            Self::Cross => "X",
+            Self::YellowFlag => "Y",
+            Self::SoldOutOfSequence => "Z",
+            Self::DerivativelyPriced => "4",
+            Self::Reopening => "5",
+            Self::ClosingPrints => "6",
+            Self::QualifiedContingent => "7",
+            Self::CorrectedClose => "9",
+            Self::Unknown(code) => code,
+        }
+    }
+
+    /// Human-readable name of the condition; `"Unknown"` for unmapped codes.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Regular => "Regular Sale",
+            Self::Acquisition => "Acquisition",
+            Self::Bunched => "Bunched Trade",
+            Self::CashSale => "Cash Sale",
+            Self::Distribution => "Distribution",
+            Self::IntermarketSweep => "Intermarket Sweep",
+            Self::BunchedSold => "Bunched Sold Trade",
+            Self::PriceVariation => "Price Variation Trade",
+            Self::OddLot => "Odd Lot Trade",
+            Self::Rule155 => "Rule 155 Trade",
+            Self::SoldLast => "Sold Last",
+            Self::OfficialClose => "Market Center Official Close",
+            Self::NextDay => "Next Day",
+            Self::OpeningPrints => "Opening Prints",
+            Self::PriorReferencePrice => "Prior Reference Price",
+            Self::OfficialOpen => "Market Center Official Open",
+            Self::Seller => "Seller",
+            Self::Split => "Split Trade",
+            Self::FormT => "Form T",
+            Self::ExtendedHoursSold => "Extended Trading Hours (Sold Out of Sequence)",
+            Self::Contingent => "Contingent Trade",
+            Self::AveragePrice => "Average Price Trade",
+            Self::Cross => "Cross Trade",
+            Self::YellowFlag => "Yellow Flag Regular Trade",
+            Self::SoldOutOfSequence => "Sold (Out of Sequence)",
+            Self::DerivativelyPriced => "Derivatively Priced",
+            Self::Reopening => "Market Center Reopening Trade",
+            Self::ClosingPrints => "Market Center Closing Trade",
+            Self::QualifiedContingent => "Qualified Contingent Trade",
+            Self::CorrectedClose => "Corrected Consolidated Close",
+            Self::Unknown(_) => "Unknown",
+        }
+    }
+
+    /// Whether the condition marks an ordinary regular-hours sale at a market
+    /// price: regular sales, sweeps, crosses, and auction prints. Odd lots,
+    /// late or out-of-sequence reports, extended hours, average-price and
+    /// contingent trades, official open/close reports, and unknown codes are
+    /// not regular.
+    pub fn is_regular(&self) -> bool {
+        matches!(
+            self,
+            Self::Regular
+                | Self::IntermarketSweep
+                | Self::Cross
+                | Self::OpeningPrints
+                | Self::Reopening
+                | Self::ClosingPrints
+        )
+    }
+}
+
+impl From<&str> for TradeCondition {
+    fn from(code: &str) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl std::fmt::Display for TradeCondition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// Map a trade's raw condition codes; `None` (no conditions) maps to empty.
+pub fn parse_conditions(codes: Option<&[String]>) -> Vec<TradeCondition> {
+    codes
+        .unwrap_or_default()
+        .iter()
+        .map(|code| TradeCondition::from_code(code))
+        .collect()
+}
+
+/// Whether a trade with these raw condition codes is a regular sale: every
+/// code is [regular](TradeCondition::is_regular). A trade with no conditions
+/// counts as regular.
+pub fn is_regular_trade(codes: Option<&[String]>) -> bool {
+    codes
+        .unwrap_or_default()
+        .iter()
+        .all(|code| TradeCondition::from_code(code).is_regular())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_known_codes() {
+        let regular = TradeCondition::from_code("@");
+        assert_eq!(regular, TradeCondition::Regular);
+        assert_eq!(regular.description(), "Regular Sale");
+        assert!(regular.is_regular());
+
+        let sweep = TradeCondition::from("F");
+        assert_eq!(sweep, TradeCondition::IntermarketSweep);
+        assert_eq!(sweep.description(), "Intermarket Sweep");
+        assert!(sweep.is_regular());
+
+        let odd_lot = TradeCondition::from_code("I");
+        assert_eq!(odd_lot.code(), "I");
+        assert!(!odd_lot.is_regular());
+        assert!(!TradeCondition::from_code("T").is_regular());
+    }
+
+    #[test]
+    fn keeps_unknown_codes() {
+        let unknown = TradeCondition::from_code("8");
+        assert_eq!(unknown, TradeCondition::Unknown("8".into()));
+        assert_eq!(unknown.code(), "8");
+        assert_eq!(unknown.to_string(), "8");
+        assert_eq!(unknown.description(), "Unknown");
+        assert!(!unknown.is_regular());
+    }
+
+    #[test]
+    fn filters_irregular_trades() {
+        let codes = |c: &[&str]| c.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert!(is_regular_trade(None));
+        assert!(is_regular_trade(Some(&codes(&["@", "F"]))));
+        assert!(!is_regular_trade(Some(&codes(&["@", "I"]))));
+        assert_eq!(
+            parse_conditions(Some(&codes(&["@", "Z"]))),
+            [TradeCondition::Regular, TradeCondition::SoldOutOfSequence]
+        );
+    }
+}
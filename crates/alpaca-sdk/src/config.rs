@@ -1,13 +1,66 @@
+use std::time::Duration;
+
+use crate::error::AlpacaError;
+use crate::stream::MarketDataFeed;
+
+const PAPER_TRADING_URL: &str = "https://paper-api.alpaca.markets";
+const LIVE_TRADING_URL: &str = "https://api.alpaca.markets";
+const MARKET_DATA_URL: &str = "https://data.alpaca.markets";
+const MARKET_DATA_SANDBOX_URL: &str = "https://data.sandbox.alpaca.markets";
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default `User-Agent`, e.g. `alpaca-rs/0.2.0`.
+pub fn default_user_agent() -> String {
+    format!("alpaca-rs/{}", env!("CARGO_PKG_VERSION"))
+}
+
+/// Check that `url` is an absolute http(s) URL and strip trailing slashes, so
+/// request paths can be appended directly. `name` labels the error.
+pub(crate) fn normalize_base_url(name: &str, url: &str) -> Result<String, AlpacaError> {
+    let parsed = url::Url::parse(url)
+        .map_err(|e| AlpacaError::Config(format!("invalid {name} '{url}': {e}")))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(AlpacaError::Config(format!(
+            "invalid {name} '{url}': scheme must be http or https"
+        )));
+    }
+    if parsed.query().is_some() || parsed.fragment().is_some() {
+        return Err(AlpacaError::Config(format!(
+            "invalid {name} '{url}': must not have a query or fragment"
+        )));
+    }
+    Ok(parsed.as_str().trim_end_matches('/').to_string())
+}
+
 /// Configuration for connecting to the Alpaca API.
 #[derive(Debug, Clone)]
 pub struct AlpacaConfig {
     pub api_key_id: String,
     pub api_secret_key: String,
+    /// Base URL for trading requests. Validated and stripped of trailing
+    /// slashes by [`AlpacaConfigBuilder::build`] and
+    /// [`AlpacaClient::new`](crate::AlpacaClient::new).
     pub trading_base_url: String,
+    /// Base URL for market data requests; validated like `trading_base_url`.
     pub market_data_base_url: String,
+    /// Feed used for historical market data requests that don't name one.
+    pub default_feed: MarketDataFeed,
+    /// Per-request HTTP timeout.
+    pub timeout: Duration,
+    /// `User-Agent` sent with every REST request.
+    pub user_agent: String,
+    /// HTTP(S) proxy for REST requests and WebSocket streams.
+    pub proxy: Option<String>,
+    /// Extra PEM-encoded CA certificates trusted by the REST client.
+    pub root_certificates: Vec<Vec<u8>>,
 }
 
 impl AlpacaConfig {
+    /// Start building a config; defaults to paper trading on the IEX feed.
+    pub fn builder() -> AlpacaConfigBuilder {
+        AlpacaConfigBuilder::default()
+    }
+
     /// Create config from environment variables.
     ///
     /// Required: `APCA_API_KEY_ID`, `APCA_API_SECRET_KEY`
@@ -17,9 +70,14 @@ impl AlpacaConfig {
             api_key_id: std::env::var("APCA_API_KEY_ID")?,
             api_secret_key: std::env::var("APCA_API_SECRET_KEY")?,
             trading_base_url: std::env::var("APCA_TRADING_BASE_URL")
-                .unwrap_or_else(|_| "https://paper-api.alpaca.markets".into()),
+                .unwrap_or_else(|_| PAPER_TRADING_URL.into()),
             market_data_base_url: std::env::var("APCA_MARKET_DATA_BASE_URL")
-                .unwrap_or_else(|_| "https://data.alpaca.markets".into()),
+                .unwrap_or_else(|_| MARKET_DATA_URL.into()),
+            default_feed: MarketDataFeed::default(),
+            timeout: DEFAULT_TIMEOUT,
+            user_agent: default_user_agent(),
+            proxy: None,
+            root_certificates: Vec::new(),
         })
     }
 
@@ -28,8 +86,251 @@ impl AlpacaConfig {
         Self {
             api_key_id,
             api_secret_key,
-            trading_base_url: "https://paper-api.alpaca.markets".into(),
-            market_data_base_url: "https://data.alpaca.markets".into(),
+            trading_base_url: PAPER_TRADING_URL.into(),
+            market_data_base_url: MARKET_DATA_URL.into(),
+            default_feed: MarketDataFeed::default(),
+            timeout: DEFAULT_TIMEOUT,
+            user_agent: default_user_agent(),
+            proxy: None,
+            root_certificates: Vec::new(),
+        }
+    }
+}
+
+/// Builder for [`AlpacaConfig`].
+#[derive(Debug, Clone)]
+pub struct AlpacaConfigBuilder {
+    api_key_id: Option<String>,
+    api_secret_key: Option<String>,
+    paper: bool,
+    trading_base_url: Option<String>,
+    market_data_base_url: Option<String>,
+    sandbox_market_data: bool,
+    default_feed: MarketDataFeed,
+    timeout: Duration,
+    user_agent: String,
+    proxy: Option<String>,
+    root_certificates: Vec<Vec<u8>>,
+}
+
+impl Default for AlpacaConfigBuilder {
+    fn default() -> Self {
+        Self {
+            api_key_id: None,
+            api_secret_key: None,
+            paper: true,
+            trading_base_url: None,
+            market_data_base_url: None,
+            sandbox_market_data: false,
+            default_feed: MarketDataFeed::default(),
+            timeout: DEFAULT_TIMEOUT,
+            user_agent: default_user_agent(),
+            proxy: None,
+            root_certificates: Vec::new(),
+        }
+    }
+}
+
+impl AlpacaConfigBuilder {
+    pub fn api_key(mut self, api_key_id: impl Into<String>) -> Self {
+        self.api_key_id = Some(api_key_id.into());
+        self
+    }
+
+    pub fn api_secret(mut self, api_secret_key: impl Into<String>) -> Self {
+        self.api_secret_key = Some(api_secret_key.into());
+        self
+    }
+
+    /// Choose the paper (`true`, default) or live trading endpoint.
+    /// Ignored if `trading_base_url` is set explicitly.
+    pub fn paper(mut self, paper: bool) -> Self {
+        self.paper = paper;
+        self
+    }
+
+    pub fn trading_base_url(mut self, url: impl Into<String>) -> Self {
+        self.trading_base_url = Some(url.into());
+        self
+    }
+
+    pub fn market_data_base_url(mut self, url: impl Into<String>) -> Self {
+        self.market_data_base_url = Some(url.into());
+        self
+    }
+
+    /// Send market data requests to the sandbox data host instead of
+    /// production. Ignored if `market_data_base_url` is set explicitly.
+    pub fn sandbox_market_data(mut self, sandbox: bool) -> Self {
+        self.sandbox_market_data = sandbox;
+        self
+    }
+
+    pub fn default_feed(mut self, feed: MarketDataFeed) -> Self {
+        self.default_feed = feed;
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// Route REST and WebSocket traffic through the proxy at `url`. Streams
+    /// need an `http://` proxy URL.
+    pub fn proxy(mut self, url: impl Into<String>) -> Self {
+        self.proxy = Some(url.into());
+        self
+    }
+
+    /// Trust an extra PEM-encoded CA certificate for REST requests.
+    pub fn add_root_certificate(mut self, pem: &[u8]) -> Self {
+        self.root_certificates.push(pem.to_vec());
+        self
+    }
+
+    pub fn build(self) -> Result<AlpacaConfig, AlpacaError> {
+        let trading_base_url = self.trading_base_url.unwrap_or_else(|| {
+            if self.paper {
+                PAPER_TRADING_URL.into()
+            } else {
+                LIVE_TRADING_URL.into()
+            }
+        });
+        let market_data_base_url = self.market_data_base_url.unwrap_or_else(|| {
+            if self.sandbox_market_data {
+                MARKET_DATA_SANDBOX_URL.into()
+            } else {
+                MARKET_DATA_URL.into()
+            }
+        });
+        Ok(AlpacaConfig {
+            api_key_id: self
+                .api_key_id
+                .ok_or_else(|| AlpacaError::Config("API key is required".into()))?,
+            api_secret_key: self
+                .api_secret_key
+                .ok_or_else(|| AlpacaError::Config("API secret is required".into()))?,
+            trading_base_url: normalize_base_url("trading base URL", &trading_base_url)?,
+            market_data_base_url: normalize_base_url(
+                "market data base URL",
+                &market_data_base_url,
+            )?,
+            default_feed: self.default_feed,
+            timeout: self.timeout,
+            user_agent: self.user_agent,
+            proxy: self.proxy,
+            root_certificates: self.root_certificates,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builder_defaults() {
+        let config = AlpacaConfig::builder()
+            .api_key("key")
+            .api_secret("secret")
+            .build()
+            .unwrap();
+        assert_eq!(config.trading_base_url, PAPER_TRADING_URL);
+        assert_eq!(config.market_data_base_url, MARKET_DATA_URL);
+        assert_eq!(config.default_feed, MarketDataFeed::Iex);
+        assert_eq!(config.timeout, Duration::from_secs(30));
+        assert_eq!(
+            config.user_agent,
+            format!("alpaca-rs/{}", env!("CARGO_PKG_VERSION"))
+        );
+    }
+
+    #[test]
+    fn builder_sandbox_market_data() {
+        let builder = AlpacaConfig::builder()
+            .api_key("key")
+            .api_secret("secret")
+            .sandbox_market_data(true);
+        let config = builder.clone().build().unwrap();
+        assert_eq!(config.market_data_base_url, MARKET_DATA_SANDBOX_URL);
+
+        let explicit = builder
+            .market_data_base_url("http://localhost:9000")
+            .build()
+            .unwrap();
+        assert_eq!(explicit.market_data_base_url, "http://localhost:9000");
+    }
+
+    #[test]
+    fn builder_overrides() {
+        let config = AlpacaConfig::builder()
+            .api_key("key")
+            .api_secret("secret")
+            .paper(false)
+            .default_feed(MarketDataFeed::Sip)
+            .timeout(Duration::from_secs(5))
+            .build()
+            .unwrap();
+        assert_eq!(config.trading_base_url, LIVE_TRADING_URL);
+        assert_eq!(config.default_feed, MarketDataFeed::Sip);
+        assert_eq!(config.timeout, Duration::from_secs(5));
+
+        let config = AlpacaConfig::builder()
+            .api_key("key")
+            .api_secret("secret")
+            .paper(false)
+            .trading_base_url("http://localhost:8080")
+            .build()
+            .unwrap();
+        assert_eq!(config.trading_base_url, "http://localhost:8080");
+    }
+
+    #[test]
+    fn builder_requires_credentials() {
+        let err = AlpacaConfig::builder().api_key("key").build().unwrap_err();
+        assert!(matches!(err, AlpacaError::Config(_)));
+    }
+
+    #[test]
+    fn builder_rejects_malformed_urls() {
+        let builder = AlpacaConfig::builder().api_key("key").api_secret("secret");
+        for (url, reason) in [
+            (
+                "htps://paper-api.alpaca.markets",
+                "scheme must be http or https",
+            ),
+            ("paper-api.alpaca.markets", "relative URL without a base"),
+            ("https://data.alpaca.markets?feed=sip", "query or fragment"),
+        ] {
+            let err = builder.clone().trading_base_url(url).build().unwrap_err();
+            assert!(
+                matches!(&err, AlpacaError::Config(msg) if msg.contains(reason)),
+                "{url}: {err}"
+            );
         }
+        let err = builder
+            .market_data_base_url("ftp://data.alpaca.markets")
+            .build()
+            .unwrap_err();
+        assert!(err.to_string().contains("market data base URL"), "{err}");
+    }
+
+    #[test]
+    fn builder_strips_trailing_slashes() {
+        let config = AlpacaConfig::builder()
+            .api_key("key")
+            .api_secret("secret")
+            .trading_base_url("https://paper-api.alpaca.markets/")
+            .market_data_base_url("http://localhost:9000/proxy//")
+            .build()
+            .unwrap();
+        assert_eq!(config.trading_base_url, PAPER_TRADING_URL);
+        assert_eq!(config.market_data_base_url, "http://localhost:9000/proxy");
     }
 }
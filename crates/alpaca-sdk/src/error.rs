@@ -1,3 +1,4 @@
+use serde::Deserialize;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -8,6 +9,31 @@ pub enum AlpacaError {
     #[error("Alpaca API error {status}: {body}")]
     Api { status: u16, body: String },
 
+    /// A 404: the order, position or asset doesn't exist. `resource` is
+    /// Alpaca's error message (e.g. `position does not exist`), or the raw
+    /// body when it isn't structured.
+    #[error("Not found: {resource}")]
+    NotFound { resource: String },
+
+    /// An order was rejected because the account can't afford it.
+    #[error("Insufficient buying power: {}", error.message)]
+    InsufficientBuyingPower { status: u16, error: AlpacaApiError },
+
+    /// An order was rejected because it would sell more than is available,
+    /// e.g. shares already held by another open order.
+    #[error("Insufficient quantity: {}", error.message)]
+    InsufficientQty { status: u16, error: AlpacaApiError },
+
+    /// An order was rejected as a potential wash trade against an opposite
+    /// open order in the same symbol.
+    #[error("Potential wash trade: {}", error.message)]
+    WashTrade { status: u16, error: AlpacaApiError },
+
+    /// An order was rejected because the market is closed for its time in
+    /// force or session.
+    #[error("Market closed: {}", error.message)]
+    MarketClosed { status: u16, error: AlpacaApiError },
+
     #[error("JSON deserialization error: {0}")]
     Deserialize(#[from] serde_json::Error),
 
@@ -19,14 +45,197 @@ pub enum AlpacaError {
 
     #[error("WebSocket error: {0}")]
     WebSocket(String),
+
+    #[error("WebSocket closed by server ({code}): {reason}")]
+    Closed { code: u16, reason: String },
+
+    /// The stream rejected a subscribe because the plan's symbol limit was hit.
+    #[error("Stream symbol limit exceeded: {msg}")]
+    SymbolLimitExceeded { msg: String },
+
+    /// Writing or parsing a CSV export failed; see [`export`](crate::export).
+    #[error("CSV error: {0}")]
+    Csv(String),
+
+    /// Writing an export failed, e.g. in
+    /// [`download_bars_to_writer`](crate::AlpacaClient::download_bars_to_writer)
+    /// or its async counterpart.
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// A client-side wait, e.g. [`wait_until_open`](crate::AlpacaClient::wait_until_open),
+    /// gave up after the given duration.
+    #[error("Timed out after {0:?}")]
+    Timeout(std::time::Duration),
+}
+
+/// Structured error body returned by Alpaca, e.g.
+/// `{"code":40310000,"message":"insufficient buying power"}`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct AlpacaApiError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl AlpacaApiError {
+    /// Parse an error response body, returning `None` if it isn't Alpaca's
+    /// `{code, message}` shape.
+    pub fn parse(body: &str) -> Option<Self> {
+        serde_json::from_str(body).ok()
+    }
+}
+
+/// Map a 403/422 order rejection to a typed variant. Alpaca reuses codes
+/// like `40310000` for several rejections, so the message decides:
+///
+/// | Message contains                    | Variant                   |
+/// |-------------------------------------|---------------------------|
+/// | `wash trade`                        | `WashTrade`               |
+/// | `buying power`                      | `InsufficientBuyingPower` |
+/// | `insufficient qty`                  | `InsufficientQty`         |
+/// | `market is closed`, `market closed` | `MarketClosed`            |
+///
+/// Anything else stays an `Api` error.
+fn classify_rejection(status: u16, body: &str) -> Option<AlpacaError> {
+    if !matches!(status, 403 | 422) {
+        return None;
+    }
+    let error = AlpacaApiError::parse(body)?;
+    let message = error.message.to_lowercase();
+    let typed = if message.contains("wash trade") {
+        AlpacaError::WashTrade { status, error }
+    } else if message.contains("buying power") {
+        AlpacaError::InsufficientBuyingPower { status, error }
+    } else if message.contains("insufficient qty") {
+        AlpacaError::InsufficientQty { status, error }
+    } else if message.contains("market is closed") || message.contains("market closed") {
+        AlpacaError::MarketClosed { status, error }
+    } else {
+        return None;
+    };
+    Some(typed)
+}
+
+impl AlpacaError {
+    /// The structured Alpaca error for an `Api` error with a `{code, message}`
+    /// body, or for a typed order rejection.
+    pub fn api_error(&self) -> Option<AlpacaApiError> {
+        match self {
+            AlpacaError::Api { body, .. } => AlpacaApiError::parse(body),
+            _ => self.rejection().map(|(_, error)| error.clone()),
+        }
+    }
+
+    /// Status and body of a typed order rejection such as
+    /// [`InsufficientBuyingPower`](Self::InsufficientBuyingPower).
+    fn rejection(&self) -> Option<(u16, &AlpacaApiError)> {
+        match self {
+            AlpacaError::InsufficientBuyingPower { status, error }
+            | AlpacaError::InsufficientQty { status, error }
+            | AlpacaError::WashTrade { status, error }
+            | AlpacaError::MarketClosed { status, error } => Some((*status, error)),
+            _ => None,
+        }
+    }
+
+    /// Whether this is an order rejection the SDK recognises, e.g. for
+    /// buying power or a wash trade.
+    pub fn is_order_rejection(&self) -> bool {
+        self.rejection().is_some()
+    }
+
+    /// Alpaca's numeric error code, if the response body carried one.
+    pub fn code(&self) -> Option<i64> {
+        self.api_error().map(|e| e.code)
+    }
+
+    /// The API error message: the parsed `message` field, or the raw body
+    /// when it isn't structured. `None` for non-API errors.
+    pub fn message(&self) -> Option<String> {
+        match self {
+            AlpacaError::Api { body, .. } => Some(
+                AlpacaApiError::parse(body)
+                    .map(|e| e.message)
+                    .unwrap_or_else(|| body.clone()),
+            ),
+            AlpacaError::NotFound { resource } => Some(resource.clone()),
+            _ => self.rejection().map(|(_, error)| error.message.clone()),
+        }
+    }
+
+    /// The HTTP status behind this error, if there was a response.
+    pub fn status_code(&self) -> Option<u16> {
+        match self {
+            AlpacaError::Api { status, .. } => Some(*status),
+            AlpacaError::NotFound { .. } => Some(404),
+            AlpacaError::RateLimited { .. } => Some(429),
+            AlpacaError::Http(e) => e.status().map(|s| s.as_u16()),
+            _ => self.rejection().map(|(status, _)| status),
+        }
+    }
+
+    /// Whether the request timed out (connect or overall request timeout),
+    /// or a client-side wait ran out.
+    pub fn is_timeout(&self) -> bool {
+        match self {
+            AlpacaError::Http(e) => e.is_timeout(),
+            AlpacaError::Timeout(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Whether the connection to Alpaca could not be established.
+    pub fn is_connect(&self) -> bool {
+        matches!(self, AlpacaError::Http(e) if e.is_connect())
+    }
+
+    /// Whether reading or decoding the response body failed.
+    pub fn is_body(&self) -> bool {
+        matches!(self, AlpacaError::Http(e) if e.is_body() || e.is_decode())
+    }
+
+    /// Whether the requested order, position or asset doesn't exist.
+    pub fn is_not_found(&self) -> bool {
+        matches!(
+            self,
+            AlpacaError::NotFound { .. } | AlpacaError::Api { status: 404, .. }
+        )
+    }
+
+    /// Whether the request hit Alpaca's rate limit.
+    pub fn is_rate_limited(&self) -> bool {
+        matches!(self, AlpacaError::RateLimited { .. })
+    }
+
+    /// Whether retrying the same request may succeed: rate limits, transport
+    /// failures and timeouts, and 5xx responses. Client errors (4xx), bad
+    /// payloads and configuration problems are not retryable.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            AlpacaError::RateLimited { .. } => true,
+            AlpacaError::Http(e) => {
+                e.is_timeout()
+                    || e.is_connect()
+                    || e.is_request()
+                    || e.status().is_some_and(|s| s.is_server_error())
+            }
+            AlpacaError::Api { status, .. } => (500..600).contains(status),
+            _ => false,
+        }
+    }
 }
 
 impl From<api_client_core::ApiClientError> for AlpacaError {
     fn from(err: api_client_core::ApiClientError) -> Self {
         match err {
             api_client_core::ApiClientError::Http(e) => AlpacaError::Http(e),
+            api_client_core::ApiClientError::Api { status: 404, body } => AlpacaError::NotFound {
+                resource: AlpacaApiError::parse(&body)
+                    .map(|e| e.message)
+                    .unwrap_or(body),
+            },
             api_client_core::ApiClientError::Api { status, body } => {
-                AlpacaError::Api { status, body }
+                classify_rejection(status, &body).unwrap_or(AlpacaError::Api { status, body })
             }
             api_client_core::ApiClientError::Deserialize(e) => AlpacaError::Deserialize(e),
             api_client_core::ApiClientError::RateLimited { retry_after_secs } => {
@@ -34,6 +243,9 @@ impl From<api_client_core::ApiClientError> for AlpacaError {
             }
             api_client_core::ApiClientError::Config(msg) => AlpacaError::Config(msg),
             api_client_core::ApiClientError::WebSocket(msg) => AlpacaError::WebSocket(msg),
+            api_client_core::ApiClientError::Closed { code, reason } => {
+                AlpacaError::Closed { code, reason }
+            }
         }
     }
 }
@@ -110,4 +322,228 @@ mod tests {
             _ => panic!("expected WebSocket variant"),
         }
     }
+
+    #[test]
+    fn from_core_closed() {
+        let core_err = api_client_core::ApiClientError::Closed {
+            code: 4001,
+            reason: "connection limit exceeded".to_string(),
+        };
+        let alpaca_err: AlpacaError = core_err.into();
+        assert_eq!(
+            alpaca_err.to_string(),
+            "WebSocket closed by server (4001): connection limit exceeded"
+        );
+        match alpaca_err {
+            AlpacaError::Closed { code, .. } => assert_eq!(code, 4001),
+            _ => panic!("expected Closed variant"),
+        }
+    }
+
+    #[test]
+    fn structured_api_error_body() {
+        let err = AlpacaError::Api {
+            status: 403,
+            body: r#"{"code":40310000,"message":"insufficient buying power"}"#.to_string(),
+        };
+        assert_eq!(
+            err.api_error(),
+            Some(AlpacaApiError {
+                code: 40310000,
+                message: "insufficient buying power".to_string(),
+            })
+        );
+        assert_eq!(err.code(), Some(40310000));
+        assert_eq!(err.message().as_deref(), Some("insufficient buying power"));
+    }
+
+    #[test]
+    fn unstructured_api_error_body() {
+        let err = AlpacaError::Api {
+            status: 502,
+            body: "<html>Bad Gateway</html>".to_string(),
+        };
+        assert_eq!(err.api_error(), None);
+        assert_eq!(err.code(), None);
+        assert_eq!(err.message().as_deref(), Some("<html>Bad Gateway</html>"));
+
+        let other = AlpacaError::Config("missing key".to_string());
+        assert_eq!(other.code(), None);
+        assert_eq!(other.message(), None);
+    }
+
+    #[test]
+    fn retry_classification() {
+        let server_error = AlpacaError::Api {
+            status: 500,
+            body: "internal error".to_string(),
+        };
+        assert!(server_error.is_retryable());
+        assert!(!server_error.is_rate_limited());
+        assert_eq!(server_error.status_code(), Some(500));
+
+        let unprocessable = AlpacaError::Api {
+            status: 422,
+            body: r#"{"code":42210000,"message":"qty must be > 0"}"#.to_string(),
+        };
+        assert!(!unprocessable.is_retryable());
+        assert_eq!(unprocessable.status_code(), Some(422));
+
+        let rate_limited = AlpacaError::RateLimited {
+            retry_after_secs: 3,
+        };
+        assert!(rate_limited.is_retryable());
+        assert!(rate_limited.is_rate_limited());
+        assert_eq!(rate_limited.status_code(), Some(429));
+
+        let deserialize: AlpacaError = serde_json::from_str::<AlpacaApiError>("{")
+            .unwrap_err()
+            .into();
+        assert!(!deserialize.is_retryable());
+        assert_eq!(deserialize.status_code(), None);
+    }
+
+    #[tokio::test]
+    async fn connect_failure_is_retryable() {
+        // Bind then drop a listener so the port is (almost certainly) closed.
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let err: AlpacaError = reqwest::get(format!("http://{addr}/"))
+            .await
+            .unwrap_err()
+            .into();
+        assert!(err.is_retryable());
+        assert!(err.is_connect());
+        assert!(!err.is_timeout());
+        assert_eq!(err.status_code(), None);
+    }
+
+    #[tokio::test]
+    async fn timeout_is_classified() {
+        // Accepted by the kernel backlog but never answered.
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let http = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_millis(50))
+            .build()
+            .unwrap();
+        let err: AlpacaError = http
+            .get(format!("http://{addr}/"))
+            .send()
+            .await
+            .unwrap_err()
+            .into();
+        assert!(err.is_timeout());
+        assert!(!err.is_connect());
+        assert!(!err.is_body());
+        assert!(err.is_retryable());
+        drop(listener);
+    }
+
+    #[test]
+    fn non_http_errors_have_no_transport_kind() {
+        let err = AlpacaError::Api {
+            status: 503,
+            body: "unavailable".to_string(),
+        };
+        assert!(!err.is_timeout());
+        assert!(!err.is_connect());
+        assert!(!err.is_body());
+    }
+
+    #[test]
+    fn from_core_404_is_not_found() {
+        let alpaca_err: AlpacaError = api_client_core::ApiClientError::Api {
+            status: 404,
+            body: r#"{"code":40410000,"message":"position does not exist"}"#.to_string(),
+        }
+        .into();
+        match &alpaca_err {
+            AlpacaError::NotFound { resource } => assert_eq!(resource, "position does not exist"),
+            other => panic!("expected NotFound, got {other:?}"),
+        }
+        assert!(alpaca_err.is_not_found());
+        assert_eq!(alpaca_err.status_code(), Some(404));
+        assert_eq!(
+            alpaca_err.message().as_deref(),
+            Some("position does not exist")
+        );
+        assert!(!alpaca_err.is_retryable());
+        assert_eq!(alpaca_err.to_string(), "Not found: position does not exist");
+
+        let unstructured: AlpacaError = api_client_core::ApiClientError::Api {
+            status: 404,
+            body: "Not Found".to_string(),
+        }
+        .into();
+        assert!(
+            matches!(unstructured, AlpacaError::NotFound { ref resource } if resource == "Not Found")
+        );
+
+        let forbidden: AlpacaError = api_client_core::ApiClientError::Api {
+            status: 403,
+            body: "forbidden".to_string(),
+        }
+        .into();
+        assert!(!forbidden.is_not_found());
+    }
+
+    #[test]
+    fn order_rejections_map_to_typed_variants() {
+        let reject = |status: u16, body: &str| -> AlpacaError {
+            api_client_core::ApiClientError::Api {
+                status,
+                body: body.to_string(),
+            }
+            .into()
+        };
+
+        let err = reject(
+            403,
+            r#"{"code":40310000,"message":"insufficient buying power"}"#,
+        );
+        assert!(matches!(err, AlpacaError::InsufficientBuyingPower { .. }));
+        assert!(err.is_order_rejection());
+        assert_eq!(err.code(), Some(40310000));
+        assert_eq!(err.status_code(), Some(403));
+        assert_eq!(
+            err.to_string(),
+            "Insufficient buying power: insufficient buying power"
+        );
+        assert!(!err.is_retryable());
+
+        let err = reject(
+            403,
+            r#"{"code":40310000,"message":"potential wash trade detected. use complex orders","reject_reason":"opposite side market/stop order exists"}"#,
+        );
+        assert!(matches!(err, AlpacaError::WashTrade { .. }));
+        assert_eq!(
+            err.message().as_deref(),
+            Some("potential wash trade detected. use complex orders")
+        );
+
+        let err = reject(
+            403,
+            r#"{"code":40310000,"message":"insufficient qty available for order (requested: 10, available: 4)"}"#,
+        );
+        assert!(matches!(err, AlpacaError::InsufficientQty { .. }));
+
+        let err = reject(
+            422,
+            r#"{"code":42210000,"message":"opg orders are rejected when the market is closed"}"#,
+        );
+        assert!(matches!(err, AlpacaError::MarketClosed { status: 422, .. }));
+
+        // Unrecognised rejections and other statuses stay generic.
+        let err = reject(422, r#"{"code":40010001,"message":"qty must be > 0"}"#);
+        assert!(matches!(err, AlpacaError::Api { status: 422, .. }));
+        assert!(!err.is_order_rejection());
+        let err = reject(
+            500,
+            r#"{"code":50010000,"message":"buying power service down"}"#,
+        );
+        assert!(matches!(err, AlpacaError::Api { status: 500, .. }));
+    }
 }
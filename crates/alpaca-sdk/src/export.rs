@@ -0,0 +1,345 @@
+//! Bar series in formats other tools consume, e.g. to hand the output of
+//! [`AlpacaClient::get_bars`](crate::AlpacaClient::get_bars) to a dataframe
+//! library or a spreadsheet.
+//!
+//! CSV rows are `timestamp,open,high,low,close,volume` under a header line,
+//! with RFC 3339 UTC timestamps and plain decimal prices. NDJSON lines use
+//! the API's bar shape, e.g. `{"t":"2024-06-03T04:00:00Z","o":"191.05",...}`.
+
+use std::io;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+
+use crate::error::AlpacaError;
+use crate::types::AlpacaBar;
+
+/// Header written by [`write_bars_csv`] and expected by [`from_csv`].
+pub const BAR_CSV_HEADER: [&str; 6] = ["timestamp", "open", "high", "low", "close", "volume"];
+
+/// Write `bars` as CSV, header first, to `writer`.
+pub fn write_bars_csv<W: io::Write>(bars: &[AlpacaBar], writer: W) -> Result<(), AlpacaError> {
+    let mut writer = BarWriter::new(writer, BarFormat::Csv)?;
+    for bar in bars {
+        writer.write(bar)?;
+    }
+    writer.finish()
+}
+
+/// Output format of a [`BarWriter`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BarFormat {
+    /// One JSON bar per line.
+    #[default]
+    Ndjson,
+    /// [`BAR_CSV_HEADER`] followed by one row per bar.
+    Csv,
+}
+
+/// Writes bars one at a time, so a long series can go straight to a file
+/// without being held in memory.
+pub struct BarWriter<W: io::Write> {
+    inner: Sink<W>,
+}
+
+enum Sink<W: io::Write> {
+    Ndjson(W),
+    Csv(Box<csv::Writer<W>>),
+}
+
+impl<W: io::Write> BarWriter<W> {
+    /// Start writing to `writer`; the CSV header is written immediately.
+    pub fn new(writer: W, format: BarFormat) -> Result<Self, AlpacaError> {
+        let inner = match format {
+            BarFormat::Ndjson => Sink::Ndjson(writer),
+            BarFormat::Csv => {
+                let mut writer = csv::Writer::from_writer(writer);
+                writer.write_record(BAR_CSV_HEADER).map_err(csv_error)?;
+                Sink::Csv(Box::new(writer))
+            }
+        };
+        Ok(Self { inner })
+    }
+
+    pub fn write(&mut self, bar: &AlpacaBar) -> Result<(), AlpacaError> {
+        match &mut self.inner {
+            Sink::Ndjson(writer) => {
+                serde_json::to_writer(&mut *writer, bar)?;
+                writer.write_all(b"\n")?;
+            }
+            Sink::Csv(writer) => writer.write_record(csv_record(bar)).map_err(csv_error)?,
+        }
+        Ok(())
+    }
+
+    /// Flush any buffered output.
+    pub fn finish(self) -> Result<(), AlpacaError> {
+        match self.inner {
+            Sink::Ndjson(mut writer) => writer.flush()?,
+            Sink::Csv(mut writer) => writer
+                .flush()
+                .map_err(|e| AlpacaError::Csv(e.to_string()))?,
+        }
+        Ok(())
+    }
+}
+
+/// The bytes [`BarWriter`] would write for `bar`, for writers it can't wrap,
+/// e.g. a `tokio::io::AsyncWrite`.
+pub(crate) fn encode_bar(bar: &AlpacaBar, format: BarFormat) -> Result<Vec<u8>, AlpacaError> {
+    match format {
+        BarFormat::Ndjson => {
+            let mut line = serde_json::to_vec(bar)?;
+            line.push(b'\n');
+            Ok(line)
+        }
+        BarFormat::Csv => encode_csv_record(csv_record(bar)),
+    }
+}
+
+/// The CSV header line [`BarWriter`] starts with.
+pub(crate) fn encode_csv_header() -> Result<Vec<u8>, AlpacaError> {
+    encode_csv_record(BAR_CSV_HEADER)
+}
+
+fn encode_csv_record<I, T>(record: I) -> Result<Vec<u8>, AlpacaError>
+where
+    I: IntoIterator<Item = T>,
+    T: AsRef<[u8]>,
+{
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(record).map_err(csv_error)?;
+    writer
+        .into_inner()
+        .map_err(|e| AlpacaError::Csv(e.to_string()))
+}
+
+fn csv_record(bar: &AlpacaBar) -> [String; 6] {
+    [
+        bar.timestamp.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+        bar.open.to_string(),
+        bar.high.to_string(),
+        bar.low.to_string(),
+        bar.close.to_string(),
+        bar.volume.to_string(),
+    ]
+}
+
+/// `bars` as a CSV document, header first.
+pub fn to_csv_rows(bars: &[AlpacaBar]) -> String {
+    let mut buf = Vec::new();
+    write_bars_csv(bars, &mut buf).expect("writing CSV to memory cannot fail");
+    String::from_utf8(buf).expect("CSV output is UTF-8")
+}
+
+/// Parse a CSV document written by [`write_bars_csv`] back into bars.
+///
+/// Columns are matched by header name, so their order doesn't matter and
+/// extra columns are ignored.
+pub fn from_csv<R: io::Read>(reader: R) -> Result<Vec<AlpacaBar>, AlpacaError> {
+    let mut reader = csv::Reader::from_reader(reader);
+    let headers = reader.headers().map_err(csv_error)?.clone();
+    let index = |name: &str| {
+        headers
+            .iter()
+            .position(|h| h.trim() == name)
+            .ok_or_else(|| AlpacaError::Csv(format!("missing column '{name}'")))
+    };
+    let [t, o, h, l, c, v] = [
+        index("timestamp")?,
+        index("open")?,
+        index("high")?,
+        index("low")?,
+        index("close")?,
+        index("volume")?,
+    ];
+
+    let mut bars = Vec::new();
+    for (row, record) in reader.records().enumerate() {
+        let record = record.map_err(csv_error)?;
+        // Row 1 is the header.
+        let line = row + 2;
+        let field = |i: usize| record.get(i).unwrap_or_default().trim();
+        let invalid = |name: &str, i: usize, e: &dyn std::fmt::Display| {
+            AlpacaError::Csv(format!("line {line}: invalid {name} '{}': {e}", field(i)))
+        };
+        let decimal = |i: usize, name: &str| {
+            field(i)
+                .parse()
+                .map_err(|e: rust_decimal::Error| invalid(name, i, &e))
+        };
+        bars.push(AlpacaBar {
+            timestamp: DateTime::parse_from_rfc3339(field(t))
+                .map_err(|e| invalid("timestamp", t, &e))?
+                .with_timezone(&Utc),
+            open: decimal(o, "open")?,
+            high: decimal(h, "high")?,
+            low: decimal(l, "low")?,
+            close: decimal(c, "close")?,
+            volume: field(v)
+                .parse()
+                .map_err(|e: std::num::ParseIntError| invalid("volume", v, &e))?,
+        });
+    }
+    Ok(bars)
+}
+
+/// A bar series as parallel columns, one element per bar, ready to build a
+/// dataframe from (e.g. `polars::Series::new("close", columns.closes)`).
+///
+/// Prices are converted to the nearest `f64`, so values with more
+/// significant digits than `f64` holds lose precision; keep the
+/// [`AlpacaBar`]s when exact decimals matter.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BarColumns {
+    pub timestamps: Vec<DateTime<Utc>>,
+    pub opens: Vec<f64>,
+    pub highs: Vec<f64>,
+    pub lows: Vec<f64>,
+    pub closes: Vec<f64>,
+    pub volumes: Vec<i64>,
+}
+
+impl BarColumns {
+    pub fn len(&self) -> usize {
+        self.timestamps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.timestamps.is_empty()
+    }
+
+    /// Timestamps as milliseconds since the Unix epoch, the usual unit for a
+    /// dataframe datetime column.
+    pub fn timestamps_millis(&self) -> Vec<i64> {
+        self.timestamps
+            .iter()
+            .map(|t| t.timestamp_millis())
+            .collect()
+    }
+}
+
+/// Transpose `bars` into [`BarColumns`].
+pub fn to_columns(bars: &[AlpacaBar]) -> BarColumns {
+    let mut columns = BarColumns {
+        timestamps: Vec::with_capacity(bars.len()),
+        opens: Vec::with_capacity(bars.len()),
+        highs: Vec::with_capacity(bars.len()),
+        lows: Vec::with_capacity(bars.len()),
+        closes: Vec::with_capacity(bars.len()),
+        volumes: Vec::with_capacity(bars.len()),
+    };
+    for bar in bars {
+        columns.timestamps.push(bar.timestamp);
+        columns.opens.push(to_f64(bar.open));
+        columns.highs.push(to_f64(bar.high));
+        columns.lows.push(to_f64(bar.low));
+        columns.closes.push(to_f64(bar.close));
+        columns.volumes.push(bar.volume);
+    }
+    columns
+}
+
+fn to_f64(value: Decimal) -> f64 {
+    // Every Decimal is within f64 range, so this only rounds.
+    value.to_f64().unwrap_or(f64::NAN)
+}
+
+fn csv_error(e: csv::Error) -> AlpacaError {
+    AlpacaError::Csv(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn bars() -> Vec<AlpacaBar> {
+        vec![
+            AlpacaBar {
+                timestamp: Utc.with_ymd_and_hms(2024, 6, 3, 4, 0, 0).unwrap(),
+                open: Decimal::new(19105, 2),
+                high: Decimal::new(19250, 2),
+                low: Decimal::new(1905, 1),
+                close: Decimal::new(1921234, 4),
+                volume: 1_200_300,
+            },
+            AlpacaBar {
+                timestamp: Utc.with_ymd_and_hms(2024, 6, 4, 4, 0, 0).unwrap(),
+                open: Decimal::from(192),
+                high: Decimal::from(194),
+                low: Decimal::from(191),
+                close: Decimal::from(193),
+                volume: 0,
+            },
+        ]
+    }
+
+    #[test]
+    fn csv_round_trips() {
+        let csv = to_csv_rows(&bars());
+        assert_eq!(
+            csv,
+            "timestamp,open,high,low,close,volume\n\
+             2024-06-03T04:00:00Z,191.05,192.50,190.5,192.1234,1200300\n\
+             2024-06-04T04:00:00Z,192,194,191,193,0\n"
+        );
+
+        let parsed = from_csv(csv.as_bytes()).unwrap();
+        assert_eq!(parsed.len(), 2);
+        for (a, b) in parsed.iter().zip(bars()) {
+            assert_eq!(
+                (a.timestamp, a.open, a.high, a.low, a.close, a.volume),
+                (b.timestamp, b.open, b.high, b.low, b.close, b.volume)
+            );
+        }
+    }
+
+    #[test]
+    fn from_csv_matches_columns_by_name() {
+        let csv = "volume,close,low,high,open,timestamp,vwap\n\
+                   10,2,1,3,1.5,2024-06-03T09:30:00-04:00,2\n";
+        let bars = from_csv(csv.as_bytes()).unwrap();
+        assert_eq!(
+            bars[0].timestamp,
+            Utc.with_ymd_and_hms(2024, 6, 3, 13, 30, 0).unwrap()
+        );
+        assert_eq!(bars[0].open, Decimal::new(15, 1));
+        assert_eq!(bars[0].volume, 10);
+    }
+
+    #[test]
+    fn from_csv_reports_bad_rows() {
+        let err = from_csv("timestamp,open,high,low,close\n".as_bytes()).unwrap_err();
+        assert!(err.to_string().contains("missing column 'volume'"), "{err}");
+
+        let csv = "timestamp,open,high,low,close,volume\n\
+                   2024-06-03T04:00:00Z,abc,2,1,2,10\n";
+        let err = from_csv(csv.as_bytes()).unwrap_err();
+        assert!(
+            err.to_string().contains("line 2: invalid open 'abc'"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn columns_are_parallel() {
+        let columns = to_columns(&bars());
+        assert_eq!(columns.len(), 2);
+        for len in [
+            columns.opens.len(),
+            columns.highs.len(),
+            columns.lows.len(),
+            columns.closes.len(),
+            columns.volumes.len(),
+        ] {
+            assert_eq!(len, 2);
+        }
+        assert_eq!(columns.opens[0], 191.05);
+        assert_eq!(columns.closes[0], 192.1234);
+        assert_eq!(columns.volumes, vec![1_200_300, 0]);
+        assert_eq!(columns.timestamps_millis()[1], 1_717_473_600_000);
+        assert!(to_columns(&[]).is_empty());
+    }
+}
@@ -1,10 +1,29 @@
+pub mod api;
+pub mod bars;
 pub mod client;
+pub mod clock;
+pub mod condition;
 pub mod config;
 pub mod error;
+pub mod export;
 pub mod stream;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 pub mod types;
 
+pub use api::TradingApi;
+#[cfg(feature = "testing")]
+pub use api_client_core::MockTransport;
+pub use api_client_core::{
+    HttpTransport, OverflowPolicy, ParseMode, RateLimiter, RequestObserver, WebSocketConfig,
+};
 pub use client::AlpacaClient;
-pub use config::AlpacaConfig;
-pub use error::AlpacaError;
-pub use stream::{AlpacaStream, MarketDataFeed};
+#[cfg(feature = "testing")]
+pub use clock::MockClock;
+pub use clock::{Clock, SystemClock};
+pub use config::{AlpacaConfig, AlpacaConfigBuilder};
+pub use error::{AlpacaApiError, AlpacaError};
+pub use stream::{
+    AlpacaStream, CheckedMessage, MarketDataFeed, OrderTracker, ReconnectObserver, ReconnectPolicy,
+    StreamEncoding, StreamOptions, SubscriptionSet,
+};
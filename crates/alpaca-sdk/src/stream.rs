@@ -1,5 +1,17 @@
-use api_client_core::WebSocketClient;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use api_client_core::{
+    ConnectOptions, Keepalive, OverflowPolicy, WebSocketClient, WebSocketConfig, WsFrame,
+};
+use chrono::{DateTime, Utc};
+use futures_util::{Stream, StreamExt};
 use serde_json::json;
+use tracing::warn;
 
 use crate::config::AlpacaConfig;
 use crate::error::AlpacaError;
@@ -8,31 +20,466 @@ use crate::types::{AlpacaStreamMessage, AlpacaTradeUpdate};
 const MARKET_DATA_STREAM_SIP: &str = "wss://stream.data.alpaca.markets/v2/sip";
 const MARKET_DATA_STREAM_IEX: &str = "wss://stream.data.alpaca.markets/v2/iex";
 const MARKET_DATA_STREAM_TEST: &str = "wss://stream.data.alpaca.markets/v2/test";
+const MARKET_DATA_STREAM_DELAYED_SIP: &str = "wss://stream.data.alpaca.markets/v2/delayed_sip";
+const MARKET_DATA_STREAM_BOATS: &str = "wss://stream.data.alpaca.markets/v1beta1/boats";
+const CRYPTO_STREAM_US: &str = "wss://stream.data.alpaca.markets/v1beta3/crypto/us";
+const NEWS_STREAM: &str = "wss://stream.data.alpaca.markets/v1beta1/news";
+
+/// Stream error code Alpaca sends when a subscribe exceeds the symbol limit.
+const SYMBOL_LIMIT_EXCEEDED: i32 = 405;
+
+const RECONNECT_INITIAL_DELAY: Duration = Duration::from_secs(1);
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(30);
+const RECONNECT_MULTIPLIER: f64 = 2.0;
+const RECONNECT_MAX_ATTEMPTS: u32 = 10;
+
+type ReconnectFuture = Pin<Box<dyn Future<Output = Result<WebSocketClient, AlpacaError>> + Send>>;
 
 /// Alpaca WebSocket streaming client for real-time market data and trade updates.
 ///
 /// Built on `api_client_core::WebSocketClient` for standardized WebSocket handling.
+/// Implements [`Stream`] over market data messages, so `StreamExt` combinators
+/// can be used in place of a manual [`recv`](Self::recv) loop.
 pub struct AlpacaStream {
     ws: WebSocketClient,
+    subscriptions: SubscriptionSet,
+    listening_trade_updates: bool,
+    reconnect: Option<ReconnectPolicy>,
+    pending_reconnect: Option<ReconnectFuture>,
+    encoding: StreamEncoding,
+    symbol_limit: Option<usize>,
+    /// Messages decoded from a multi-message frame but not yet yielded.
+    buffered: VecDeque<AlpacaStreamMessage>,
+    /// Event times seen by [`recv_checked`](Self::recv_checked).
+    order_tracker: OrderTracker,
+}
+
+/// Symbols subscribed on each market data channel.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SubscriptionSet {
+    pub trades: BTreeSet<String>,
+    pub quotes: BTreeSet<String>,
+    pub bars: BTreeSet<String>,
+    pub updated_bars: BTreeSet<String>,
+    pub daily_bars: BTreeSet<String>,
+    pub news: BTreeSet<String>,
+}
+
+impl SubscriptionSet {
+    /// The channel lists of a [`Subscription`](AlpacaStreamMessage::Subscription)
+    /// confirmation; `None` for any other message.
+    pub fn from_confirmation(msg: &AlpacaStreamMessage) -> Option<Self> {
+        let AlpacaStreamMessage::Subscription {
+            trades,
+            quotes,
+            bars,
+            updated_bars,
+            daily_bars,
+        } = msg
+        else {
+            return None;
+        };
+        let set = |symbols: &Option<Vec<String>>| symbols.iter().flatten().cloned().collect();
+        Some(Self {
+            trades: set(trades),
+            quotes: set(quotes),
+            bars: set(bars),
+            updated_bars: set(updated_bars),
+            daily_bars: set(daily_bars),
+            news: BTreeSet::new(),
+        })
+    }
+
+    fn channel_mut(&mut self, channel: &str) -> Option<&mut BTreeSet<String>> {
+        match channel {
+            "trades" => Some(&mut self.trades),
+            "quotes" => Some(&mut self.quotes),
+            "bars" => Some(&mut self.bars),
+            "updatedBars" => Some(&mut self.updated_bars),
+            "dailyBars" => Some(&mut self.daily_bars),
+            "news" => Some(&mut self.news),
+            _ => None,
+        }
+    }
+
+    fn channels(&self) -> [(&'static str, &BTreeSet<String>); 6] {
+        [
+            ("trades", &self.trades),
+            ("quotes", &self.quotes),
+            ("bars", &self.bars),
+            ("updatedBars", &self.updated_bars),
+            ("dailyBars", &self.daily_bars),
+            ("news", &self.news),
+        ]
+    }
+
+    /// Adopt the market data channels the server confirmed, which may differ
+    /// from what was requested, e.g. after a rejected symbol. News is kept as
+    /// confirmations don't report it.
+    fn sync(&mut self, confirmed: SubscriptionSet) {
+        *self = SubscriptionSet {
+            news: std::mem::take(&mut self.news),
+            ..confirmed
+        };
+    }
+
+    /// Record a subscribe or unsubscribe that was sent to the server.
+    fn apply(&mut self, action: &str, channels: &[(&str, &[&str])]) {
+        for (channel, symbols) in channels {
+            let Some(set) = self.channel_mut(channel) else {
+                continue;
+            };
+            if action == "subscribe" {
+                set.extend(symbols.iter().map(|s| s.to_string()));
+            } else {
+                set.retain(|s| !symbols.contains(&s.as_str()));
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.channels().iter().all(|(_, set)| set.is_empty())
+    }
+
+    /// Distinct market data symbols across all channels (news excluded).
+    fn symbol_count(&self) -> usize {
+        self.channels()
+            .into_iter()
+            .filter(|(channel, _)| *channel != "news")
+            .flat_map(|(_, set)| set.iter())
+            .collect::<BTreeSet<_>>()
+            .len()
+    }
+
+    /// The message that re-establishes this subscription set on a fresh connection.
+    ///
+    /// Only non-empty channels are included.
+    fn resubscribe_message(&self) -> serde_json::Value {
+        let mut msg = serde_json::Map::new();
+        msg.insert("action".into(), "subscribe".into());
+        for (channel, set) in self.channels() {
+            if !set.is_empty() {
+                msg.insert(channel.into(), json!(set));
+            }
+        }
+        serde_json::Value::Object(msg)
+    }
+}
+
+/// Tracks the latest event time per symbol and channel to spot messages that
+/// arrive older than one already seen, e.g. a bar delivered out of order.
+#[derive(Debug, Default)]
+pub struct OrderTracker {
+    last_seen: HashMap<(&'static str, String), DateTime<Utc>>,
+}
+
+impl OrderTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record `msg`, returning `true` if it is older than the previous
+    /// message for the same symbol on the same channel. Equal timestamps are
+    /// in order; messages without a symbol and time are ignored.
+    pub fn observe(&mut self, msg: &AlpacaStreamMessage) -> bool {
+        let Some((channel, symbol, timestamp)) = msg.market_event() else {
+            return false;
+        };
+        match self.last_seen.get_mut(&(channel, symbol.to_string())) {
+            Some(last) if timestamp < *last => true,
+            Some(last) => {
+                *last = timestamp;
+                false
+            }
+            None => {
+                self.last_seen
+                    .insert((channel, symbol.to_string()), timestamp);
+                false
+            }
+        }
+    }
+}
+
+/// A stream message flagged by [`AlpacaStream::recv_checked`] or
+/// [`AlpacaStream::with_order_check`].
+#[derive(Debug, Clone)]
+pub struct CheckedMessage {
+    pub message: AlpacaStreamMessage,
+    /// Older than an earlier message for the same symbol and channel.
+    pub out_of_order: bool,
+}
+
+/// Hooks called as an auto-reconnecting [`AlpacaStream`] recovers from a
+/// dropped connection, e.g. to alert on a prolonged disconnect.
+///
+/// All methods default to doing nothing.
+pub trait ReconnectObserver: Send + Sync {
+    /// Called before each reconnect attempt; `attempt` starts at 1.
+    fn on_reconnect_attempt(&self, _attempt: u32) {}
+
+    /// Called when attempt number `attempts` succeeded.
+    fn on_reconnected(&self, _attempts: u32) {}
+
+    /// Called once the policy's attempts are used up; the stream then yields
+    /// `error` and ends.
+    fn on_reconnect_failed(&self, _attempts: u32, _error: &AlpacaError) {}
+}
+
+struct NoopReconnectObserver;
+
+impl ReconnectObserver for NoopReconnectObserver {}
+
+/// Exponential backoff between reconnect attempts, and when to give up.
+///
+/// The default waits 1s, then doubles up to 30s, giving up after 10
+/// attempts.
+#[derive(Clone)]
+pub struct ReconnectPolicy {
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    /// Growth factor between consecutive delays; values below 1 act as 1.
+    pub multiplier: f64,
+    /// Attempts before giving up; `None` retries forever.
+    pub max_attempts: Option<u32>,
+    observer: Arc<dyn ReconnectObserver>,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: RECONNECT_INITIAL_DELAY,
+            max_delay: RECONNECT_MAX_DELAY,
+            multiplier: RECONNECT_MULTIPLIER,
+            max_attempts: Some(RECONNECT_MAX_ATTEMPTS),
+            observer: Arc::new(NoopReconnectObserver),
+        }
+    }
+}
+
+impl std::fmt::Debug for ReconnectPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReconnectPolicy")
+            .field("initial_delay", &self.initial_delay)
+            .field("max_delay", &self.max_delay)
+            .field("multiplier", &self.multiplier)
+            .field("max_attempts", &self.max_attempts)
+            .finish_non_exhaustive()
+    }
 }
 
-/// Feed source for market data streams.
-#[derive(Debug, Clone, Copy)]
+impl ReconnectPolicy {
+    pub fn initial_delay(mut self, delay: Duration) -> Self {
+        self.initial_delay = delay;
+        self
+    }
+
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    pub fn max_attempts(mut self, attempts: Option<u32>) -> Self {
+        self.max_attempts = attempts;
+        self
+    }
+
+    /// Report reconnect attempts and their outcome to `observer`.
+    pub fn observer(mut self, observer: Arc<dyn ReconnectObserver>) -> Self {
+        self.observer = observer;
+        self
+    }
+
+    /// The wait after failed attempt number `attempt` (starting at 1):
+    /// `initial_delay * multiplier^(attempt - 1)`, capped at `max_delay`.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let exponent = i32::try_from(attempt.saturating_sub(1)).unwrap_or(i32::MAX);
+        let secs = self.initial_delay.as_secs_f64() * self.multiplier.max(1.0).powi(exponent);
+        Duration::try_from_secs_f64(secs)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay)
+    }
+
+    fn gives_up_after(&self, attempt: u32) -> bool {
+        self.max_attempts.is_some_and(|max| attempt >= max)
+    }
+}
+
+/// Outcome of reading one frame, after any transparent reconnect.
+enum Frame {
+    Text(String),
+    Binary(Vec<u8>),
+    Reconnected,
+}
+
+/// Wire encoding negotiated for a market data stream.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StreamEncoding {
+    /// JSON text frames (the default).
+    #[default]
+    Json,
+    /// MessagePack binary frames, which are smaller and faster to decode.
+    MsgPack,
+}
+
+/// Options for connecting to a market data stream.
+#[derive(Debug, Clone, Default)]
+pub struct StreamOptions {
+    /// Wire encoding requested from the server.
+    pub encoding: StreamEncoding,
+    /// Client heartbeat; a silent connection fails with `heartbeat timeout`.
+    pub keepalive: Option<Keepalive>,
+    /// Symbol limit of the account's data plan (e.g. 30 on free IEX). A
+    /// subscribe that would exceed it logs a warning before it is sent.
+    pub symbol_limit: Option<usize>,
+    /// `http://` proxy to connect through; defaults to the config's proxy.
+    pub proxy: Option<String>,
+    /// Messages buffered ahead of the consumer; 256 if unset. Busy SIP
+    /// subscriptions may need more.
+    pub channel_capacity: Option<usize>,
+    /// What to do when the consumer lags and the buffer fills.
+    pub overflow: OverflowPolicy,
+    /// WebSocket protocol limits and buffer sizes; see
+    /// [`ConnectOptions::websocket_config`]. Streams are never compressed.
+    pub websocket_config: Option<WebSocketConfig>,
+    /// Deadline for establishing each (re)connection; 10s if unset.
+    pub connect_timeout: Option<Duration>,
+    /// If set, connecting waits up to this long for the server to confirm
+    /// authentication and fails if it reports an error instead.
+    pub confirm_auth: Option<Duration>,
+}
+
+impl StreamOptions {
+    pub fn encoding(mut self, encoding: StreamEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    pub fn keepalive(mut self, interval: Duration, timeout: Duration) -> Self {
+        self.keepalive = Some(Keepalive { interval, timeout });
+        self
+    }
+
+    pub fn symbol_limit(mut self, limit: usize) -> Self {
+        self.symbol_limit = Some(limit);
+        self
+    }
+
+    pub fn proxy(mut self, url: impl Into<String>) -> Self {
+        self.proxy = Some(url.into());
+        self
+    }
+
+    pub fn channel_capacity(mut self, capacity: usize) -> Self {
+        self.channel_capacity = Some(capacity);
+        self
+    }
+
+    pub fn overflow(mut self, policy: OverflowPolicy) -> Self {
+        self.overflow = policy;
+        self
+    }
+
+    pub fn websocket_config(mut self, config: WebSocketConfig) -> Self {
+        self.websocket_config = Some(config);
+        self
+    }
+
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Make connecting fail fast on bad credentials: wait up to `timeout` for
+    /// the `authenticated` confirmation before returning the stream. That
+    /// confirmation is consumed; messages received before it, such as
+    /// `connected`, are still yielded afterwards.
+    pub fn confirm_auth(mut self, timeout: Duration) -> Self {
+        self.confirm_auth = Some(timeout);
+        self
+    }
+
+    /// Fill in settings left unset from `config`.
+    fn with_config(mut self, config: &AlpacaConfig) -> Self {
+        if self.proxy.is_none() {
+            self.proxy = config.proxy.clone();
+        }
+        self
+    }
+
+    fn connect_options(&self) -> ConnectOptions {
+        let mut options = ConnectOptions {
+            keepalive: self.keepalive,
+            proxy: self.proxy.clone(),
+            channel_capacity: self.channel_capacity,
+            overflow: self.overflow,
+            websocket_config: self.websocket_config,
+            connect_timeout: self.connect_timeout,
+            ..ConnectOptions::default()
+        };
+        if self.encoding == StreamEncoding::MsgPack {
+            options = options.header("Content-Type", "application/msgpack");
+        }
+        options
+    }
+}
+
+/// Feed source for market data streams and historical data requests.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum MarketDataFeed {
     /// SIP (Securities Information Processor) - all US exchanges, requires paid plan
     Sip,
     /// IEX (Investors Exchange) - free tier
+    #[default]
     Iex,
     /// Test feed for development
     Test,
+    /// SIP delayed by 15 minutes - full market coverage on the free tier
+    DelayedSip,
+    /// Blue Ocean ATS (BOATS) - overnight US equity trading
+    Boats,
 }
 
 impl MarketDataFeed {
+    /// The `feed` query parameter value for historical data requests.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MarketDataFeed::Sip => "sip",
+            MarketDataFeed::Iex => "iex",
+            MarketDataFeed::Test => "test",
+            MarketDataFeed::DelayedSip => "delayed_sip",
+            MarketDataFeed::Boats => "boats",
+        }
+    }
+
     fn url(&self) -> &'static str {
         match self {
             MarketDataFeed::Sip => MARKET_DATA_STREAM_SIP,
             MarketDataFeed::Iex => MARKET_DATA_STREAM_IEX,
             MarketDataFeed::Test => MARKET_DATA_STREAM_TEST,
+            MarketDataFeed::DelayedSip => MARKET_DATA_STREAM_DELAYED_SIP,
+            MarketDataFeed::Boats => MARKET_DATA_STREAM_BOATS,
+        }
+    }
+}
+
+impl std::str::FromStr for MarketDataFeed {
+    type Err = AlpacaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "sip" => Ok(MarketDataFeed::Sip),
+            "iex" => Ok(MarketDataFeed::Iex),
+            "test" => Ok(MarketDataFeed::Test),
+            "delayed_sip" => Ok(MarketDataFeed::DelayedSip),
+            "boats" => Ok(MarketDataFeed::Boats),
+            other => Err(AlpacaError::Config(format!(
+                "Unknown market data feed: {other}"
+            ))),
         }
     }
 }
@@ -45,17 +492,74 @@ impl AlpacaStream {
         config: &AlpacaConfig,
         feed: MarketDataFeed,
     ) -> Result<Self, AlpacaError> {
-        let auth = json!({
-            "action": "auth",
-            "key": config.api_key_id,
-            "secret": config.api_secret_key,
-        });
+        Self::connect_market_data_with_options(config, feed, StreamOptions::default()).await
+    }
+
+    /// Connect to the market data stream with explicit options, e.g. to
+    /// request MessagePack encoding or a client heartbeat.
+    pub async fn connect_market_data_with_options(
+        config: &AlpacaConfig,
+        feed: MarketDataFeed,
+        options: StreamOptions,
+    ) -> Result<Self, AlpacaError> {
+        Self::connect_url_with_options(
+            feed.url(),
+            market_data_auth(config),
+            options.with_config(config),
+        )
+        .await
+    }
+
+    /// Connect to Alpaca's US crypto market data stream.
+    ///
+    /// Trades, quotes, and bars are subscribed with the same methods as the
+    /// equity stream, using crypto pairs such as `"BTC/USD"`.
+    pub async fn connect_crypto_stream(config: &AlpacaConfig) -> Result<Self, AlpacaError> {
+        Self::connect_url_with_options(
+            CRYPTO_STREAM_US,
+            market_data_auth(config),
+            StreamOptions::default().with_config(config),
+        )
+        .await
+    }
+
+    /// Connect to Alpaca's real-time news stream.
+    ///
+    /// Use [`subscribe_news`](Self::subscribe_news) to choose symbols; articles
+    /// arrive as [`AlpacaStreamMessage::News`].
+    pub async fn connect_news_stream(config: &AlpacaConfig) -> Result<Self, AlpacaError> {
+        Self::connect_url_with_options(
+            NEWS_STREAM,
+            market_data_auth(config),
+            StreamOptions::default().with_config(config),
+        )
+        .await
+    }
 
-        let ws = WebSocketClient::connect(feed.url(), Some(auth))
+    /// Connect to the market data stream with automatic reconnection.
+    ///
+    /// If the socket drops, the stream reconnects with exponential backoff,
+    /// re-authenticates, re-sends the current subscriptions, and yields an
+    /// [`AlpacaStreamMessage::Reconnected`] so callers know a gap occurred.
+    pub async fn connect_market_data_with_reconnect(
+        config: &AlpacaConfig,
+        feed: MarketDataFeed,
+    ) -> Result<Self, AlpacaError> {
+        Self::connect_market_data_with_reconnect_policy(config, feed, ReconnectPolicy::default())
             .await
-            .map_err(AlpacaError::from)?;
+    }
 
-        Ok(Self { ws })
+    /// Like [`connect_market_data_with_reconnect`](Self::connect_market_data_with_reconnect),
+    /// retrying and giving up according to `policy`. Once it gives up, the
+    /// stream yields the final error and ends.
+    pub async fn connect_market_data_with_reconnect_policy(
+        config: &AlpacaConfig,
+        feed: MarketDataFeed,
+        policy: ReconnectPolicy,
+    ) -> Result<Self, AlpacaError> {
+        let mut stream = Self::connect_market_data(config, feed).await?;
+        stream.reconnect = Some(policy);
+        Ok(stream)
     }
 
     /// Connect to Alpaca's trade updates WebSocket stream (order fills, cancellations, etc).
@@ -71,26 +575,93 @@ impl AlpacaStream {
             }
         });
 
-        let ws = WebSocketClient::connect(&url, Some(auth))
+        Self::connect_url_with_options(&url, auth, StreamOptions::default().with_config(config))
+            .await
+    }
+
+    /// Connect to the trade updates stream with automatic reconnection.
+    ///
+    /// After a drop the stream re-authenticates and resumes listening for
+    /// trade updates if it was listening before.
+    pub async fn connect_trade_updates_with_reconnect(
+        config: &AlpacaConfig,
+    ) -> Result<Self, AlpacaError> {
+        Self::connect_trade_updates_with_reconnect_policy(config, ReconnectPolicy::default()).await
+    }
+
+    /// Like [`connect_trade_updates_with_reconnect`](Self::connect_trade_updates_with_reconnect),
+    /// retrying and giving up according to `policy`.
+    pub async fn connect_trade_updates_with_reconnect_policy(
+        config: &AlpacaConfig,
+        policy: ReconnectPolicy,
+    ) -> Result<Self, AlpacaError> {
+        let mut stream = Self::connect_trade_updates(config).await?;
+        stream.reconnect = Some(policy);
+        Ok(stream)
+    }
+
+    #[cfg(test)]
+    async fn connect_url(url: &str, auth: serde_json::Value) -> Result<Self, AlpacaError> {
+        Self::connect_url_with_options(url, auth, StreamOptions::default()).await
+    }
+
+    async fn connect_url_with_options(
+        url: &str,
+        auth: serde_json::Value,
+        options: StreamOptions,
+    ) -> Result<Self, AlpacaError> {
+        let ws = WebSocketClient::connect_with_options(url, Some(auth), options.connect_options())
             .await
             .map_err(AlpacaError::from)?;
 
-        Ok(Self { ws })
+        let mut stream = Self {
+            ws,
+            subscriptions: SubscriptionSet::default(),
+            listening_trade_updates: false,
+            reconnect: None,
+            pending_reconnect: None,
+            encoding: options.encoding,
+            symbol_limit: options.symbol_limit,
+            buffered: VecDeque::new(),
+            order_tracker: OrderTracker::new(),
+        };
+        if let Some(timeout) = options.confirm_auth {
+            stream
+                .await_control(timeout, "authentication", |msg| match msg {
+                    AlpacaStreamMessage::Success { msg } if msg == "authenticated" => Some(()),
+                    _ => None,
+                })
+                .await?;
+        }
+        Ok(stream)
     }
 
     /// Subscribe to real-time trades for the given symbols.
     pub async fn subscribe_trades(&mut self, symbols: &[&str]) -> Result<(), AlpacaError> {
-        self.send_subscription("subscribe", symbols, &[], &[]).await
+        self.subscribe(symbols, &[], &[]).await
     }
 
     /// Subscribe to real-time quotes for the given symbols.
     pub async fn subscribe_quotes(&mut self, symbols: &[&str]) -> Result<(), AlpacaError> {
-        self.send_subscription("subscribe", &[], symbols, &[]).await
+        self.subscribe(&[], symbols, &[]).await
     }
 
     /// Subscribe to real-time minute bars for the given symbols.
     pub async fn subscribe_bars(&mut self, symbols: &[&str]) -> Result<(), AlpacaError> {
-        self.send_subscription("subscribe", &[], &[], symbols).await
+        self.subscribe(&[], &[], symbols).await
+    }
+
+    /// Subscribe to bar updates for the given symbols: corrected minute bars
+    /// sent when late trades arrive after a bar was published.
+    pub async fn subscribe_updated_bars(&mut self, symbols: &[&str]) -> Result<(), AlpacaError> {
+        self.send_subscription("subscribe", &[("updatedBars", symbols)])
+            .await
+    }
+
+    /// Subscribe to running daily bars for the given symbols.
+    pub async fn subscribe_daily_bars(&mut self, symbols: &[&str]) -> Result<(), AlpacaError> {
+        self.send_subscription("subscribe", &[("dailyBars", symbols)])
+            .await
     }
 
     /// Subscribe to trades, quotes, and/or bars in a single message.
@@ -100,8 +671,11 @@ impl AlpacaStream {
         quotes: &[&str],
         bars: &[&str],
     ) -> Result<(), AlpacaError> {
-        self.send_subscription("subscribe", trades, quotes, bars)
-            .await
+        self.send_subscription(
+            "subscribe",
+            &[("trades", trades), ("quotes", quotes), ("bars", bars)],
+        )
+        .await
     }
 
     /// Unsubscribe from trades, quotes, and/or bars.
@@ -111,39 +685,145 @@ impl AlpacaStream {
         quotes: &[&str],
         bars: &[&str],
     ) -> Result<(), AlpacaError> {
-        self.send_subscription("unsubscribe", trades, quotes, bars)
+        self.send_subscription(
+            "unsubscribe",
+            &[("trades", trades), ("quotes", quotes), ("bars", bars)],
+        )
+        .await
+    }
+
+    /// Like [`subscribe`](Self::subscribe), then wait up to `timeout` for the
+    /// server's confirmation and return the channel lists it reports.
+    ///
+    /// Fails if the server answers with an error instead, e.g. for an invalid
+    /// symbol or an exceeded symbol limit. Other messages received while
+    /// waiting are still yielded afterwards.
+    pub async fn subscribe_and_confirm(
+        &mut self,
+        trades: &[&str],
+        quotes: &[&str],
+        bars: &[&str],
+        timeout: Duration,
+    ) -> Result<SubscriptionSet, AlpacaError> {
+        self.subscribe(trades, quotes, bars).await?;
+        self.await_control(timeout, "subscription", SubscriptionSet::from_confirmation)
+            .await
+    }
+
+    /// Like [`unsubscribe`](Self::unsubscribe), then wait for the server's
+    /// confirmation as [`subscribe_and_confirm`](Self::subscribe_and_confirm)
+    /// does.
+    pub async fn unsubscribe_and_confirm(
+        &mut self,
+        trades: &[&str],
+        quotes: &[&str],
+        bars: &[&str],
+        timeout: Duration,
+    ) -> Result<SubscriptionSet, AlpacaError> {
+        self.unsubscribe(trades, quotes, bars).await?;
+        self.await_control(timeout, "subscription", SubscriptionSet::from_confirmation)
+            .await
+    }
+
+    /// Subscribe to news articles for the given symbols (`"*"` for all news).
+    pub async fn subscribe_news(&mut self, symbols: &[&str]) -> Result<(), AlpacaError> {
+        self.send_subscription("subscribe", &[("news", symbols)])
+            .await
+    }
+
+    /// Unsubscribe from news articles for the given symbols.
+    pub async fn unsubscribe_news(&mut self, symbols: &[&str]) -> Result<(), AlpacaError> {
+        self.send_subscription("unsubscribe", &[("news", symbols)])
             .await
     }
 
     /// Listen for trade updates (for the account stream).
     pub async fn listen_trade_updates(&mut self) -> Result<(), AlpacaError> {
-        let msg = json!({
-            "action": "listen",
-            "data": {
-                "streams": ["trade_updates"]
-            }
-        });
-        self.ws.send(&msg).await.map_err(AlpacaError::from)
+        self.ws
+            .send(&listen_message())
+            .await
+            .map_err(AlpacaError::from)?;
+        self.listening_trade_updates = true;
+        Ok(())
     }
 
     /// Receive the next market data stream message.
+    ///
+    /// With auto-reconnect enabled, a dropped connection is re-established and
+    /// reported as [`AlpacaStreamMessage::Reconnected`] instead of `None`.
     pub async fn recv(&mut self) -> Option<Result<AlpacaStreamMessage, AlpacaError>> {
-        match self.ws.recv().await {
-            Some(Ok(text)) => Some(serde_json::from_str(&text).map_err(AlpacaError::Deserialize)),
-            Some(Err(e)) => Some(Err(AlpacaError::from(e))),
-            None => None,
-        }
+        self.next().await
     }
 
     /// Receive the next trade update message (for the account stream).
+    ///
+    /// With auto-reconnect enabled, a dropped connection is re-established
+    /// transparently.
     pub async fn recv_trade_update(&mut self) -> Option<Result<AlpacaTradeUpdate, AlpacaError>> {
-        match self.ws.recv().await {
-            Some(Ok(text)) => Some(serde_json::from_str(&text).map_err(AlpacaError::Deserialize)),
-            Some(Err(e)) => Some(Err(AlpacaError::from(e))),
-            None => None,
+        loop {
+            match std::future::poll_fn(|cx| self.poll_frame(cx)).await? {
+                Ok(Frame::Text(text)) => {
+                    return Some(serde_json::from_str(&text).map_err(AlpacaError::Deserialize))
+                }
+                Ok(Frame::Binary(data)) => {
+                    return Some(serde_json::from_slice(&data).map_err(AlpacaError::Deserialize))
+                }
+                Ok(Frame::Reconnected) => continue,
+                Err(e) => return Some(Err(e)),
+            }
         }
     }
 
+    /// Like [`recv`](Self::recv), flagging a message that arrives older than
+    /// an earlier one for the same symbol and channel, as tracked by an
+    /// [`OrderTracker`] kept on the stream. Flagged messages are also logged
+    /// as warnings. Only messages received through this method are tracked.
+    pub async fn recv_checked(&mut self) -> Option<Result<CheckedMessage, AlpacaError>> {
+        let message = match self.next().await? {
+            Ok(message) => message,
+            Err(e) => return Some(Err(e)),
+        };
+        let out_of_order = self.order_tracker.observe(&message);
+        if out_of_order {
+            if let Some((channel, symbol, timestamp)) = message.market_event() {
+                warn!(channel, symbol, %timestamp, "out-of-order stream message");
+            }
+        }
+        Some(Ok(CheckedMessage {
+            message,
+            out_of_order,
+        }))
+    }
+
+    /// Turn the stream into one yielding [`recv_checked`](Self::recv_checked)
+    /// results. Use `recv_checked` directly to keep subscribing on the same
+    /// stream.
+    pub fn with_order_check(self) -> impl Stream<Item = Result<CheckedMessage, AlpacaError>> {
+        futures_util::stream::unfold(self, |mut stream| async move {
+            let checked = stream.recv_checked().await?;
+            Some((checked, stream))
+        })
+    }
+
+    /// What this stream is subscribed to: updated on every subscribe and
+    /// unsubscribe, then replaced by the server's view whenever a
+    /// subscription confirmation is received. Restored on reconnect.
+    pub fn subscriptions(&self) -> &SubscriptionSet {
+        &self.subscriptions
+    }
+
+    /// Whether the underlying connection is still usable; `false` after the
+    /// server hung up or a subscribe/unsubscribe write failed.
+    pub fn is_connected(&self) -> bool {
+        self.ws.is_connected()
+    }
+
+    /// Frames discarded because the consumer fell behind with
+    /// [`OverflowPolicy::DropOldest`] set; always zero otherwise.
+    pub fn dropped_frames(&self) -> u64 {
+        self.ws.dropped_frames()
+    }
+
     /// Close the WebSocket connection.
     pub async fn close(self) -> Result<(), AlpacaError> {
         self.ws.close().await.map_err(AlpacaError::from)
@@ -152,18 +832,329 @@ impl AlpacaStream {
     async fn send_subscription(
         &mut self,
         action: &str,
-        trades: &[&str],
-        quotes: &[&str],
-        bars: &[&str],
+        channels: &[(&str, &[&str])],
     ) -> Result<(), AlpacaError> {
-        let msg = json!({
-            "action": action,
-            "trades": trades,
-            "quotes": quotes,
-            "bars": bars,
-        });
-        self.ws.send(&msg).await.map_err(AlpacaError::from)
+        if let (Some(limit), "subscribe") = (self.symbol_limit, action) {
+            let mut requested = self.subscriptions.clone();
+            requested.apply(action, channels);
+            let count = requested.symbol_count();
+            if count > limit {
+                warn!(count, limit, "subscription exceeds the stream symbol limit");
+            }
+        }
+        self.ws
+            .send(&subscription_message(action, channels))
+            .await
+            .map_err(AlpacaError::from)?;
+
+        self.subscriptions.apply(action, channels);
+        Ok(())
+    }
+
+    /// Read until `accept` matches a message, failing on a stream `error`
+    /// message. Other messages are held back and still yielded in order
+    /// afterwards. `what` names the awaited confirmation in errors.
+    async fn await_control<T>(
+        &mut self,
+        timeout: Duration,
+        what: &str,
+        accept: impl Fn(&AlpacaStreamMessage) -> Option<T>,
+    ) -> Result<T, AlpacaError> {
+        let mut held = Vec::new();
+        let result = tokio::time::timeout(timeout, async {
+            loop {
+                match self.next().await {
+                    Some(Ok(AlpacaStreamMessage::Error { code, msg })) => {
+                        return Err(AlpacaError::WebSocket(format!(
+                            "{what} rejected ({code}): {msg}"
+                        )))
+                    }
+                    Some(Ok(msg)) => match accept(&msg) {
+                        Some(confirmed) => return Ok(confirmed),
+                        None => held.push(msg),
+                    },
+                    Some(Err(e)) => return Err(e),
+                    None => {
+                        return Err(AlpacaError::WebSocket(format!(
+                            "stream ended before {what} was confirmed"
+                        )))
+                    }
+                }
+            }
+        })
+        .await
+        .unwrap_or(Err(AlpacaError::Timeout(timeout)));
+        for msg in held.into_iter().rev() {
+            self.buffered.push_front(msg);
+        }
+        result
+    }
+
+    fn poll_frame(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Frame, AlpacaError>>> {
+        loop {
+            if let Some(pending) = self.pending_reconnect.as_mut() {
+                let result = futures_util::ready!(pending.as_mut().poll(cx));
+                self.pending_reconnect = None;
+                return Poll::Ready(Some(match result {
+                    Ok(ws) => {
+                        self.ws = ws;
+                        Ok(Frame::Reconnected)
+                    }
+                    Err(e) => {
+                        self.reconnect = None;
+                        Err(e)
+                    }
+                }));
+            }
+
+            match futures_util::ready!(self.ws.poll_recv_frame(cx)) {
+                Some(Ok(WsFrame::Text(text))) => return Poll::Ready(Some(Ok(Frame::Text(text)))),
+                Some(Ok(WsFrame::Binary(data))) => {
+                    return Poll::Ready(Some(Ok(Frame::Binary(data))))
+                }
+                Some(Err(e)) => return Poll::Ready(Some(Err(AlpacaError::from(e)))),
+                None => match &self.reconnect {
+                    Some(policy) => {
+                        self.pending_reconnect = Some(self.start_reconnect(policy.clone()))
+                    }
+                    None => return Poll::Ready(None),
+                },
+            }
+        }
+    }
+
+    /// Build an owned future that reconnects and restores stream state.
+    fn start_reconnect(&self, policy: ReconnectPolicy) -> ReconnectFuture {
+        let url = self.ws.url().to_string();
+        let auth = self.ws.auth_message().cloned();
+        let options = self.ws.options().clone();
+        let mut restore = Vec::new();
+        if !self.subscriptions.is_empty() {
+            restore.push(self.subscriptions.resubscribe_message());
+        }
+        if self.listening_trade_updates {
+            restore.push(listen_message());
+        }
+
+        Box::pin(reconnect_with_backoff(url, auth, options, restore, policy))
+    }
+}
+
+impl Stream for AlpacaStream {
+    type Item = Result<AlpacaStreamMessage, AlpacaError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Some(msg) = self.buffered.pop_front() {
+                if let Some(confirmed) = SubscriptionSet::from_confirmation(&msg) {
+                    self.subscriptions.sync(confirmed);
+                }
+                return Poll::Ready(Some(check_message(msg)));
+            }
+
+            let frame = match futures_util::ready!(self.poll_frame(cx)) {
+                Some(Ok(frame)) => frame,
+                Some(Err(e)) => return Poll::Ready(Some(Err(e))),
+                None => return Poll::Ready(None),
+            };
+            match frame {
+                Frame::Text(text) => match parse_frame(&text) {
+                    Ok(messages) => self.buffered.extend(messages),
+                    Err(e) => return Poll::Ready(Some(Err(AlpacaError::Deserialize(e)))),
+                },
+                Frame::Binary(data) => {
+                    let parsed = match self.encoding {
+                        StreamEncoding::MsgPack => parse_msgpack_frame(&data),
+                        StreamEncoding::Json => match std::str::from_utf8(&data) {
+                            Ok(text) => parse_frame(text).map_err(AlpacaError::Deserialize),
+                            Err(e) => Err(AlpacaError::WebSocket(format!("Non-UTF8 frame: {e}"))),
+                        },
+                    };
+                    match parsed {
+                        Ok(messages) => self.buffered.extend(messages),
+                        Err(e) => return Poll::Ready(Some(Err(e))),
+                    }
+                }
+                Frame::Reconnected => {
+                    return Poll::Ready(Some(Ok(AlpacaStreamMessage::Reconnected)))
+                }
+            }
+        }
+    }
+}
+
+/// Surface stream errors the caller is expected to act on as typed errors.
+fn check_message(msg: AlpacaStreamMessage) -> Result<AlpacaStreamMessage, AlpacaError> {
+    match msg {
+        AlpacaStreamMessage::Error { code, msg } if code == SYMBOL_LIMIT_EXCEEDED => {
+            Err(AlpacaError::SymbolLimitExceeded { msg })
+        }
+        msg => Ok(msg),
+    }
+}
+
+/// Decode a market data frame, which Alpaca normally sends as a JSON array of
+/// messages. A bare object is accepted as a single-message frame.
+fn parse_frame(text: &str) -> Result<Vec<AlpacaStreamMessage>, serde_json::Error> {
+    if text.trim_start().starts_with('[') {
+        serde_json::from_str(text)
+    } else {
+        serde_json::from_str(text).map(|msg| vec![msg])
+    }
+}
+
+/// Decode a MessagePack market data frame.
+///
+/// The frame is converted to JSON first so the same message types serve both
+/// encodings; MessagePack timestamp extensions become RFC 3339 strings.
+fn parse_msgpack_frame(data: &[u8]) -> Result<Vec<AlpacaStreamMessage>, AlpacaError> {
+    let value = rmpv::decode::read_value(&mut &data[..])
+        .map_err(|e| AlpacaError::WebSocket(format!("Invalid MessagePack frame: {e}")))?;
+    let json = msgpack_to_json(value)?;
+    let result = if json.is_array() {
+        serde_json::from_value(json)
+    } else {
+        serde_json::from_value(json).map(|msg| vec![msg])
+    };
+    result.map_err(AlpacaError::Deserialize)
+}
+
+fn msgpack_to_json(value: rmpv::Value) -> Result<serde_json::Value, AlpacaError> {
+    use rmpv::Value;
+
+    Ok(match value {
+        Value::Nil => serde_json::Value::Null,
+        Value::Boolean(b) => b.into(),
+        Value::Integer(i) => match (i.as_i64(), i.as_u64()) {
+            (Some(n), _) => n.into(),
+            (None, Some(n)) => n.into(),
+            _ => serde_json::Value::Null,
+        },
+        Value::F32(f) => json!(f as f64),
+        Value::F64(f) => json!(f),
+        Value::String(s) => match s.into_str() {
+            Some(s) => s.into(),
+            None => return Err(AlpacaError::WebSocket("Non-UTF8 MessagePack string".into())),
+        },
+        Value::Binary(b) => json!(b),
+        Value::Array(items) => serde_json::Value::Array(
+            items
+                .into_iter()
+                .map(msgpack_to_json)
+                .collect::<Result<_, _>>()?,
+        ),
+        Value::Map(entries) => {
+            let mut map = serde_json::Map::new();
+            for (key, value) in entries {
+                let key = match key {
+                    Value::String(s) => s.into_str().unwrap_or_default(),
+                    other => other.to_string(),
+                };
+                map.insert(key, msgpack_to_json(value)?);
+            }
+            serde_json::Value::Object(map)
+        }
+        Value::Ext(-1, data) => msgpack_timestamp(&data)?.into(),
+        Value::Ext(kind, _) => {
+            return Err(AlpacaError::WebSocket(format!(
+                "Unsupported MessagePack extension type {kind}"
+            )))
+        }
+    })
+}
+
+/// Decode a MessagePack timestamp extension (type -1) as an RFC 3339 string.
+fn msgpack_timestamp(data: &[u8]) -> Result<String, AlpacaError> {
+    let (secs, nanos) = match data.len() {
+        4 => (u32::from_be_bytes(data.try_into().unwrap()) as i64, 0),
+        8 => {
+            let raw = u64::from_be_bytes(data.try_into().unwrap());
+            ((raw & 0x3_ffff_ffff) as i64, (raw >> 34) as u32)
+        }
+        12 => (
+            i64::from_be_bytes(data[4..].try_into().unwrap()),
+            u32::from_be_bytes(data[..4].try_into().unwrap()),
+        ),
+        len => {
+            return Err(AlpacaError::WebSocket(format!(
+                "Invalid MessagePack timestamp length {len}"
+            )))
+        }
+    };
+    chrono::DateTime::from_timestamp(secs, nanos)
+        .map(|ts| ts.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true))
+        .ok_or_else(|| AlpacaError::WebSocket("MessagePack timestamp out of range".into()))
+}
+
+async fn reconnect_with_backoff(
+    url: String,
+    auth: Option<serde_json::Value>,
+    options: ConnectOptions,
+    restore: Vec<serde_json::Value>,
+    policy: ReconnectPolicy,
+) -> Result<WebSocketClient, AlpacaError> {
+    let mut attempt = 1;
+    loop {
+        policy.observer.on_reconnect_attempt(attempt);
+        match reconnect_and_restore(&url, auth.clone(), options.clone(), &restore).await {
+            Ok(ws) => {
+                policy.observer.on_reconnected(attempt);
+                return Ok(ws);
+            }
+            Err(e) if policy.gives_up_after(attempt) => {
+                let err = AlpacaError::WebSocket(format!(
+                    "gave up reconnecting after {attempt} attempts: {e}"
+                ));
+                policy.observer.on_reconnect_failed(attempt, &err);
+                return Err(err);
+            }
+            Err(e) => {
+                let delay = policy.delay(attempt);
+                warn!("Reconnect attempt {attempt} failed: {e}; retrying in {delay:?}");
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+async fn reconnect_and_restore(
+    url: &str,
+    auth: Option<serde_json::Value>,
+    options: ConnectOptions,
+    restore: &[serde_json::Value],
+) -> Result<WebSocketClient, AlpacaError> {
+    let mut ws = WebSocketClient::connect_with_options(url, auth, options).await?;
+    for msg in restore {
+        ws.send(msg).await?;
+    }
+    Ok(ws)
+}
+
+fn subscription_message(action: &str, channels: &[(&str, &[&str])]) -> serde_json::Value {
+    let mut msg = serde_json::Map::new();
+    msg.insert("action".into(), action.into());
+    for (channel, symbols) in channels {
+        msg.insert(channel.to_string(), json!(symbols));
     }
+    serde_json::Value::Object(msg)
+}
+
+fn market_data_auth(config: &AlpacaConfig) -> serde_json::Value {
+    json!({
+        "action": "auth",
+        "key": config.api_key_id,
+        "secret": config.api_secret_key,
+    })
+}
+
+fn listen_message() -> serde_json::Value {
+    json!({
+        "action": "listen",
+        "data": {
+            "streams": ["trade_updates"]
+        }
+    })
 }
 
 #[cfg(test)]
@@ -184,6 +1175,48 @@ mod tests {
             MarketDataFeed::Test.url(),
             "wss://stream.data.alpaca.markets/v2/test"
         );
+        assert_eq!(
+            MarketDataFeed::DelayedSip.url(),
+            "wss://stream.data.alpaca.markets/v2/delayed_sip"
+        );
+        assert_eq!(
+            MarketDataFeed::Boats.url(),
+            "wss://stream.data.alpaca.markets/v1beta1/boats"
+        );
+    }
+
+    #[test]
+    fn market_data_feed_from_str() {
+        assert_eq!(
+            "sip".parse::<MarketDataFeed>().unwrap(),
+            MarketDataFeed::Sip
+        );
+        assert_eq!(
+            "IEX".parse::<MarketDataFeed>().unwrap(),
+            MarketDataFeed::Iex
+        );
+        assert_eq!(
+            "test".parse::<MarketDataFeed>().unwrap(),
+            MarketDataFeed::Test
+        );
+        assert_eq!(
+            "delayed_sip".parse::<MarketDataFeed>().unwrap(),
+            MarketDataFeed::DelayedSip
+        );
+        assert_eq!(
+            "boats".parse::<MarketDataFeed>().unwrap(),
+            MarketDataFeed::Boats
+        );
+        assert!("otc".parse::<MarketDataFeed>().is_err());
+    }
+
+    #[test]
+    fn crypto_and_news_stream_urls() {
+        assert_eq!(
+            CRYPTO_STREAM_US,
+            "wss://stream.data.alpaca.markets/v1beta3/crypto/us"
+        );
+        assert_eq!(NEWS_STREAM, "wss://stream.data.alpaca.markets/v1beta1/news");
     }
 
     #[test]
@@ -200,6 +1233,31 @@ mod tests {
         assert!(msg["bars"].as_array().unwrap().is_empty());
     }
 
+    #[test]
+    fn bar_channel_subscription_message_format() {
+        let msg = subscription_message(
+            "subscribe",
+            &[("updatedBars", &["SPY"]), ("dailyBars", &["SPY", "QQQ"])],
+        );
+        assert_eq!(
+            msg,
+            serde_json::json!({
+                "action": "subscribe",
+                "updatedBars": ["SPY"],
+                "dailyBars": ["SPY", "QQQ"],
+            })
+        );
+
+        let mut set = SubscriptionSet::default();
+        set.apply(
+            "subscribe",
+            &[("updatedBars", &["SPY"]), ("dailyBars", &["QQQ"])],
+        );
+        let restore = set.resubscribe_message();
+        assert_eq!(restore["updatedBars"], serde_json::json!(["SPY"]));
+        assert_eq!(restore["dailyBars"], serde_json::json!(["QQQ"]));
+    }
+
     #[test]
     fn auth_message_format() {
         let config = AlpacaConfig::paper("test_key".into(), "test_secret".into());
@@ -227,8 +1285,678 @@ mod tests {
             api_secret_key: "secret".into(),
             trading_base_url: "https://api.alpaca.markets".into(),
             market_data_base_url: "https://data.alpaca.markets".into(),
+            default_feed: MarketDataFeed::Iex,
+            timeout: Duration::from_secs(30),
+            user_agent: "test-agent".into(),
+            proxy: None,
+            root_certificates: Vec::new(),
         };
         let url = config.trading_base_url.replace("https://", "wss://") + "/stream";
         assert_eq!(url, "wss://api.alpaca.markets/stream");
     }
+
+    #[test]
+    fn subscription_set_tracks_subscribe_and_unsubscribe() {
+        let mut set = SubscriptionSet::default();
+        set.apply(
+            "subscribe",
+            &[("trades", &["AAPL", "TSLA"]), ("quotes", &["SPY"])],
+        );
+        set.apply("subscribe", &[("trades", &["AAPL"]), ("bars", &["QQQ"])]);
+        set.apply("subscribe", &[("news", &["*"])]);
+        set.apply("unsubscribe", &[("trades", &["TSLA"])]);
+
+        let msg = set.resubscribe_message();
+        assert_eq!(msg["action"], "subscribe");
+        assert_eq!(msg["trades"], serde_json::json!(["AAPL"]));
+        assert_eq!(msg["quotes"], serde_json::json!(["SPY"]));
+        assert_eq!(msg["bars"], serde_json::json!(["QQQ"]));
+        assert_eq!(msg["news"], serde_json::json!(["*"]));
+
+        set.apply(
+            "unsubscribe",
+            &[
+                ("trades", &["AAPL"]),
+                ("quotes", &["SPY"]),
+                ("bars", &["QQQ"]),
+                ("news", &["*"]),
+            ],
+        );
+        assert!(set.is_empty());
+    }
+
+    #[tokio::test]
+    async fn reconnect_restores_subscriptions_after_drop() {
+        use futures_util::{SinkExt, StreamExt};
+        use tokio_tungstenite::tungstenite::Message;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (resub_tx, mut resub_rx) = tokio::sync::mpsc::channel(1);
+
+        tokio::spawn(async move {
+            // First connection: accept auth + subscribe, then drop the socket.
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+            ws.next().await;
+            ws.next().await;
+            ws.close(None).await.unwrap();
+
+            // Second connection: capture the resubscribe message.
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+            ws.next().await;
+            if let Some(Ok(Message::Text(text))) = ws.next().await {
+                resub_tx.send(text.to_string()).await.unwrap();
+            }
+            ws.send(Message::Text(
+                r#"{"T":"success","msg":"authenticated"}"#.into(),
+            ))
+            .await
+            .unwrap();
+            ws.next().await;
+        });
+
+        let auth = serde_json::json!({"action": "auth", "key": "k", "secret": "s"});
+        let mut stream = AlpacaStream::connect_url(&url, auth).await.unwrap();
+        stream.reconnect = Some(
+            ReconnectPolicy::default()
+                .initial_delay(Duration::from_millis(10))
+                .max_delay(Duration::from_millis(10))
+                .max_attempts(Some(3)),
+        );
+        stream
+            .subscribe(&["AAPL"], &["SPY", "TSLA"], &[])
+            .await
+            .unwrap();
+
+        match stream.recv().await {
+            Some(Ok(AlpacaStreamMessage::Reconnected)) => {}
+            other => panic!("expected Reconnected, got {other:?}"),
+        }
+
+        let resub: serde_json::Value =
+            serde_json::from_str(&resub_rx.recv().await.unwrap()).unwrap();
+        assert_eq!(resub["action"], "subscribe");
+        assert_eq!(resub["trades"], serde_json::json!(["AAPL"]));
+        assert_eq!(resub["quotes"], serde_json::json!(["SPY", "TSLA"]));
+        assert!(resub.get("bars").is_none());
+
+        match stream.recv().await {
+            Some(Ok(AlpacaStreamMessage::Success { msg })) => assert_eq!(msg, "authenticated"),
+            other => panic!("expected Success, got {other:?}"),
+        }
+    }
+
+    /// Serve a single connection that sends `frames` after the auth message,
+    /// then closes.
+    async fn serve_frames(frames: Vec<&'static str>) -> String {
+        use futures_util::SinkExt;
+        use tokio_tungstenite::tungstenite::Message;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+
+        tokio::spawn(async move {
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+            ws.next().await;
+            for frame in frames {
+                ws.send(Message::Text(frame.into())).await.unwrap();
+            }
+            ws.close(None).await.unwrap();
+        });
+
+        url
+    }
+
+    fn test_auth() -> serde_json::Value {
+        serde_json::json!({"action": "auth", "key": "k", "secret": "s"})
+    }
+
+    #[test]
+    fn symbol_limit_error_is_typed() {
+        let mut messages =
+            parse_frame(r#"[{"T":"error","code":405,"msg":"symbol limit exceeded"}]"#).unwrap();
+        match check_message(messages.remove(0)) {
+            Err(AlpacaError::SymbolLimitExceeded { msg }) => {
+                assert_eq!(msg, "symbol limit exceeded")
+            }
+            other => panic!("expected SymbolLimitExceeded, got {other:?}"),
+        }
+
+        let other = AlpacaStreamMessage::Error {
+            code: 402,
+            msg: "auth failed".into(),
+        };
+        assert!(matches!(
+            check_message(other),
+            Ok(AlpacaStreamMessage::Error { code: 402, .. })
+        ));
+    }
+
+    #[test]
+    fn order_tracker_flags_older_messages_per_symbol() {
+        let bar = |symbol: &str, minute: u32| {
+            parse_frame(&format!(
+                r#"{{"T":"b","S":"{symbol}","o":"1","h":"1","l":"1","c":"1","v":1,"t":"2024-06-03T13:{minute:02}:00Z"}}"#
+            ))
+            .unwrap()
+            .remove(0)
+        };
+        let mut tracker = OrderTracker::new();
+        assert!(!tracker.observe(&bar("AAPL", 31)));
+        // Another symbol's older bar is not out of order.
+        assert!(!tracker.observe(&bar("TSLA", 30)));
+        assert!(tracker.observe(&bar("AAPL", 30)));
+        // The late bar doesn't rewind the high-water mark.
+        assert!(tracker.observe(&bar("AAPL", 30)));
+        assert!(!tracker.observe(&bar("AAPL", 31)));
+        assert!(!tracker.observe(&bar("AAPL", 32)));
+
+        let trade = parse_frame(
+            r#"{"T":"t","S":"AAPL","p":"1","s":1,"t":"2024-06-03T13:00:00Z","x":"V","i":1,"z":"C"}"#,
+        )
+        .unwrap()
+        .remove(0);
+        // Channels are tracked independently.
+        assert!(!tracker.observe(&trade));
+    }
+
+    #[tokio::test]
+    async fn recv_checked_flags_late_bars_without_consuming_the_stream() {
+        let url = serve_frames(vec![
+            r#"[{"T":"b","S":"AAPL","o":"1","h":"1","l":"1","c":"1","v":1,"t":"2024-06-03T13:31:00Z"}]"#,
+            r#"[{"T":"b","S":"AAPL","o":"1","h":"1","l":"1","c":"1","v":1,"t":"2024-06-03T13:30:00Z"}]"#,
+            r#"[{"T":"b","S":"AAPL","o":"1","h":"1","l":"1","c":"1","v":1,"t":"2024-06-03T13:32:00Z"}]"#,
+        ])
+        .await;
+        let mut stream =
+            AlpacaStream::connect_url_with_options(&url, test_auth(), StreamOptions::default())
+                .await
+                .unwrap();
+
+        let mut flags = Vec::new();
+        while let Some(checked) = stream.recv_checked().await {
+            flags.push(checked.unwrap().out_of_order);
+            // The stream stays usable between checked reads.
+            assert!(stream.subscriptions().bars.is_empty());
+        }
+        assert_eq!(flags, [false, true, false]);
+    }
+
+    #[test]
+    fn symbol_count_spans_channels_without_news() {
+        let mut set = SubscriptionSet::default();
+        set.apply(
+            "subscribe",
+            &[
+                ("trades", &["AAPL", "TSLA"]),
+                ("quotes", &["AAPL", "SPY"]),
+                ("news", &["*"]),
+            ],
+        );
+        assert_eq!(set.symbol_count(), 3);
+    }
+
+    #[test]
+    fn parse_frame_accepts_arrays_and_objects() {
+        let batch = parse_frame(
+            r#"[{"T":"success","msg":"connected"},{"T":"success","msg":"authenticated"}]"#,
+        )
+        .unwrap();
+        assert_eq!(batch.len(), 2);
+
+        let single = parse_frame(r#"{"T":"success","msg":"connected"}"#).unwrap();
+        assert_eq!(single.len(), 1);
+
+        assert!(parse_frame("[]").unwrap().is_empty());
+        assert!(parse_frame("not json").is_err());
+    }
+
+    #[tokio::test]
+    async fn batched_frame_yields_messages_in_order() {
+        let url = serve_frames(vec![
+            r#"[{"T":"t","S":"AAPL","p":"150.50","s":100,"t":"2024-06-01T14:30:00Z","x":"V","z":"C"},{"T":"q","S":"TSLA","ap":"250.00","as":50,"ax":"Q","bp":"249.95","bs":100,"bx":"Q","t":"2024-06-01T14:30:00Z","z":"C"}]"#,
+        ])
+        .await;
+        let mut stream = AlpacaStream::connect_url(&url, test_auth()).await.unwrap();
+
+        match stream.recv().await {
+            Some(Ok(AlpacaStreamMessage::Trade(t))) => assert_eq!(t.symbol, "AAPL"),
+            other => panic!("expected Trade, got {other:?}"),
+        }
+        match stream.recv().await {
+            Some(Ok(AlpacaStreamMessage::Quote(q))) => assert_eq!(q.symbol, "TSLA"),
+            other => panic!("expected Quote, got {other:?}"),
+        }
+        assert!(stream.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn stream_next_yields_messages_then_ends() {
+        let url = serve_frames(vec![
+            r#"[{"T":"success","msg":"connected"}]"#,
+            r#"[{"T":"success","msg":"authenticated"}]"#,
+        ])
+        .await;
+        let mut stream = AlpacaStream::connect_url(&url, test_auth()).await.unwrap();
+
+        match stream.next().await {
+            Some(Ok(AlpacaStreamMessage::Success { msg })) => assert_eq!(msg, "connected"),
+            other => panic!("expected Success, got {other:?}"),
+        }
+        match stream.next().await {
+            Some(Ok(AlpacaStreamMessage::Success { msg })) => assert_eq!(msg, "authenticated"),
+            other => panic!("expected Success, got {other:?}"),
+        }
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn stream_supports_combinators() {
+        let url = serve_frames(vec![
+            r#"{"T":"success","msg":"authenticated"}"#,
+            r#"{"T":"t","S":"AAPL","p":"150.50","s":100,"t":"2024-06-01T14:30:00Z","x":"V","z":"C"}"#,
+            r#"{"T":"t","S":"TSLA","p":"250.00","s":5,"t":"2024-06-01T14:30:01Z","x":"V","z":"C"}"#,
+            r#"{"T":"t","S":"SPY","p":"450.00","s":1,"t":"2024-06-01T14:30:02Z","x":"V","z":"C"}"#,
+        ])
+        .await;
+        let stream = AlpacaStream::connect_url(&url, test_auth()).await.unwrap();
+
+        let symbols: Vec<String> = stream
+            .filter_map(|msg| async move {
+                match msg {
+                    Ok(AlpacaStreamMessage::Trade(t)) => Some(t.symbol),
+                    _ => None,
+                }
+            })
+            .take(2)
+            .collect()
+            .await;
+
+        assert_eq!(symbols, vec!["AAPL", "TSLA"]);
+    }
+
+    fn sample_stream_bar() -> crate::types::AlpacaStreamBar {
+        serde_json::from_str(
+            r#"{"S":"AAPL","o":"150.00","h":"151.25","l":"149.50","c":"151.00","v":12345,"t":"2024-06-01T14:30:00Z","n":42,"vw":"150.75"}"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn msgpack_bar_round_trip() {
+        let bar = sample_stream_bar();
+        let encoded =
+            rmp_serde::to_vec_named(&vec![AlpacaStreamMessage::Bar(bar.clone())]).unwrap();
+
+        let decoded = parse_msgpack_frame(&encoded).unwrap();
+        assert_eq!(decoded.len(), 1);
+        match &decoded[0] {
+            AlpacaStreamMessage::Bar(b) => {
+                assert_eq!(b.symbol, bar.symbol);
+                assert_eq!(b.close, bar.close);
+                assert_eq!(b.volume, bar.volume);
+                assert_eq!(b.timestamp, bar.timestamp);
+                assert_eq!(b.vwap, bar.vwap);
+            }
+            other => panic!("expected Bar, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn msgpack_native_numbers_and_timestamps() {
+        // Alpaca's msgpack frames carry floats and timestamp extensions
+        // rather than the string forms used in JSON.
+        let nanos: u64 = 500_000_000;
+        let secs: u64 = 1_717_252_200;
+        let ts = rmpv::Value::Ext(-1, ((nanos << 34) | secs).to_be_bytes().to_vec());
+        let frame = rmpv::Value::Array(vec![rmpv::Value::Map(vec![
+            ("T".into(), "b".into()),
+            ("S".into(), "SPY".into()),
+            ("o".into(), 450.5.into()),
+            ("h".into(), 451.0.into()),
+            ("l".into(), 450.0.into()),
+            ("c".into(), 450.75.into()),
+            ("v".into(), 1000.into()),
+            ("t".into(), ts),
+        ])]);
+        let mut encoded = Vec::new();
+        rmpv::encode::write_value(&mut encoded, &frame).unwrap();
+
+        match parse_msgpack_frame(&encoded).unwrap().as_slice() {
+            [AlpacaStreamMessage::Bar(b)] => {
+                assert_eq!(b.symbol, "SPY");
+                assert_eq!(b.close.to_string(), "450.75");
+                assert_eq!(b.timestamp.to_rfc3339(), "2024-06-01T14:30:00.500+00:00");
+            }
+            other => panic!("expected Bar, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn msgpack_timestamp_formats() {
+        assert_eq!(
+            msgpack_timestamp(&1_717_252_200u32.to_be_bytes()).unwrap(),
+            "2024-06-01T14:30:00Z"
+        );
+        let mut wide = 7u32.to_be_bytes().to_vec();
+        wide.extend_from_slice(&1_717_252_200i64.to_be_bytes());
+        assert_eq!(
+            msgpack_timestamp(&wide).unwrap(),
+            "2024-06-01T14:30:00.000000007Z"
+        );
+        assert!(msgpack_timestamp(&[0; 3]).is_err());
+    }
+
+    #[tokio::test]
+    async fn msgpack_stream_negotiates_and_decodes_binary_frames() {
+        use futures_util::SinkExt;
+        use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+        use tokio_tungstenite::tungstenite::Message;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let frame =
+            rmp_serde::to_vec_named(&vec![AlpacaStreamMessage::Bar(sample_stream_bar())]).unwrap();
+
+        let server = tokio::spawn(async move {
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut content_type = None;
+            #[allow(clippy::result_large_err)]
+            let callback = |req: &Request, resp: Response| {
+                content_type = req
+                    .headers()
+                    .get("content-type")
+                    .map(|v| v.to_str().unwrap().to_string());
+                Ok(resp)
+            };
+            let mut ws = tokio_tungstenite::accept_hdr_async(tcp, callback)
+                .await
+                .unwrap();
+            ws.next().await;
+            ws.send(Message::Binary(frame.into())).await.unwrap();
+            ws.close(None).await.unwrap();
+            content_type
+        });
+
+        let options = StreamOptions::default().encoding(StreamEncoding::MsgPack);
+        let mut stream = AlpacaStream::connect_url_with_options(&url, test_auth(), options)
+            .await
+            .unwrap();
+
+        match stream.recv().await {
+            Some(Ok(AlpacaStreamMessage::Bar(b))) => assert_eq!(b.symbol, "AAPL"),
+            other => panic!("expected Bar, got {other:?}"),
+        }
+        assert!(stream.recv().await.is_none());
+        assert_eq!(
+            server.await.unwrap().as_deref(),
+            Some("application/msgpack")
+        );
+    }
+
+    #[test]
+    fn stream_options_pass_buffering_to_connect_options() {
+        let options = StreamOptions::default()
+            .channel_capacity(4096)
+            .overflow(OverflowPolicy::DropOldest)
+            .websocket_config(WebSocketConfig::default().max_message_size(Some(1 << 20)))
+            .connect_timeout(Duration::from_secs(3))
+            .connect_options();
+        assert_eq!(options.channel_capacity, Some(4096));
+        assert_eq!(options.connect_timeout, Some(Duration::from_secs(3)));
+        assert_eq!(
+            options.websocket_config.unwrap().max_message_size,
+            Some(1 << 20)
+        );
+        assert_eq!(options.overflow, OverflowPolicy::DropOldest);
+
+        let defaults = StreamOptions::default().connect_options();
+        assert_eq!(defaults.channel_capacity, None);
+        assert_eq!(defaults.overflow, OverflowPolicy::Block);
+        assert!(defaults.websocket_config.is_none());
+        assert!(defaults.connect_timeout.is_none());
+    }
+
+    #[test]
+    fn reconnect_delays_follow_policy() {
+        let policy = ReconnectPolicy::default()
+            .initial_delay(Duration::from_millis(100))
+            .max_delay(Duration::from_secs(2))
+            .multiplier(3.0);
+        let delays: Vec<_> = (1..=6).map(|attempt| policy.delay(attempt)).collect();
+        let ms = Duration::from_millis;
+        assert_eq!(
+            delays,
+            vec![ms(100), ms(300), ms(900), ms(2000), ms(2000), ms(2000)]
+        );
+        assert_eq!(policy.delay(u32::MAX), ms(2000));
+
+        let defaults = ReconnectPolicy::default();
+        assert_eq!(defaults.delay(1), Duration::from_secs(1));
+        assert_eq!(defaults.delay(3), Duration::from_secs(4));
+        assert!(defaults.gives_up_after(10));
+        assert!(!defaults.max_attempts(None).gives_up_after(u32::MAX));
+    }
+
+    #[derive(Default)]
+    struct CountingObserver {
+        attempts: std::sync::atomic::AtomicU32,
+        failures: std::sync::Mutex<Vec<(u32, String)>>,
+    }
+
+    impl ReconnectObserver for CountingObserver {
+        fn on_reconnect_attempt(&self, _attempt: u32) {
+            self.attempts
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        }
+
+        fn on_reconnect_failed(&self, attempts: u32, error: &AlpacaError) {
+            self.failures
+                .lock()
+                .unwrap()
+                .push((attempts, error.to_string()));
+        }
+    }
+
+    #[tokio::test]
+    async fn reconnect_gives_up_after_max_attempts() {
+        use futures_util::StreamExt;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            // Accept once, then hang up and stop listening so reconnects fail.
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+            ws.next().await;
+            ws.close(None).await.unwrap();
+        });
+
+        let auth = serde_json::json!({"action": "auth", "key": "k", "secret": "s"});
+        let mut stream = AlpacaStream::connect_url(&url, auth).await.unwrap();
+        let observer = Arc::new(CountingObserver::default());
+        stream.reconnect = Some(
+            ReconnectPolicy::default()
+                .initial_delay(Duration::from_millis(1))
+                .max_attempts(Some(3))
+                .observer(observer.clone()),
+        );
+
+        let err = match stream.recv().await {
+            Some(Err(e)) => e,
+            other => panic!("expected terminal error, got {other:?}"),
+        };
+        assert!(
+            err.to_string()
+                .contains("gave up reconnecting after 3 attempts"),
+            "{err}"
+        );
+        assert!(stream.recv().await.is_none());
+        assert_eq!(
+            observer.attempts.load(std::sync::atomic::Ordering::SeqCst),
+            3
+        );
+        let failures = observer.failures.lock().unwrap();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].0, 3);
+    }
+
+    /// Serve a single connection that, after the auth and one subscribe
+    /// message, sends `frames` and then stays open until the client hangs up.
+    async fn serve_after_subscribe(frames: Vec<&'static str>) -> String {
+        use futures_util::SinkExt;
+        use tokio_tungstenite::tungstenite::Message;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+
+        tokio::spawn(async move {
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+            ws.next().await;
+            ws.next().await;
+            for frame in frames {
+                ws.send(Message::Text(frame.into())).await.unwrap();
+            }
+            while let Some(Ok(_)) = ws.next().await {}
+        });
+
+        url
+    }
+
+    #[tokio::test]
+    async fn subscribe_and_confirm_returns_confirmed_channels() {
+        let url = serve_after_subscribe(vec![
+            r#"[{"T":"t","S":"MSFT","p":"400","s":1,"t":"2024-06-03T14:00:00Z","x":"V","z":"C"}]"#,
+            r#"[{"T":"subscription","trades":["AAPL","MSFT"],"quotes":["SPY"],"bars":[]}]"#,
+        ])
+        .await;
+        let mut stream = AlpacaStream::connect_url(&url, test_auth()).await.unwrap();
+
+        let confirmed = stream
+            .subscribe_and_confirm(&["AAPL"], &["SPY"], &[], Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(
+            confirmed.trades,
+            BTreeSet::from(["AAPL".to_string(), "MSFT".to_string()])
+        );
+        assert_eq!(confirmed.quotes, BTreeSet::from(["SPY".to_string()]));
+        assert!(confirmed.bars.is_empty());
+
+        // The trade that arrived before the confirmation is not lost.
+        match stream.recv().await {
+            Some(Ok(AlpacaStreamMessage::Trade(trade))) => assert_eq!(trade.symbol, "MSFT"),
+            other => panic!("expected Trade, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn subscribe_and_confirm_surfaces_rejection_and_timeout() {
+        let url = serve_after_subscribe(vec![
+            r#"[{"T":"error","code":410,"msg":"invalid subscribe action for this feed"}]"#,
+        ])
+        .await;
+        let mut stream = AlpacaStream::connect_url(&url, test_auth()).await.unwrap();
+        let err = stream
+            .subscribe_and_confirm(&[], &[], &["AAPL"], Duration::from_secs(5))
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("subscription rejected (410)"),
+            "{err}"
+        );
+
+        let url = serve_after_subscribe(Vec::new()).await;
+        let mut stream = AlpacaStream::connect_url(&url, test_auth()).await.unwrap();
+        let err = stream
+            .subscribe_and_confirm(&["AAPL"], &[], &[], Duration::from_millis(50))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AlpacaError::Timeout(_)), "{err}");
+    }
+
+    #[tokio::test]
+    async fn subscriptions_track_requests_and_confirmations() {
+        let url = serve_after_subscribe(vec![
+            r#"[{"T":"subscription","trades":["AAPL","MSFT"],"quotes":[],"bars":[]}]"#,
+        ])
+        .await;
+        let mut stream = AlpacaStream::connect_url(&url, test_auth()).await.unwrap();
+        assert!(stream.subscriptions().is_empty());
+
+        stream.subscribe(&["AAPL"], &["SPY"], &[]).await.unwrap();
+        stream.subscribe_news(&["*"]).await.unwrap();
+        assert_eq!(
+            stream.subscriptions().trades,
+            BTreeSet::from(["AAPL".to_string()])
+        );
+        assert_eq!(
+            stream.subscriptions().quotes,
+            BTreeSet::from(["SPY".to_string()])
+        );
+
+        stream.unsubscribe(&[], &["SPY"], &[]).await.unwrap();
+        assert!(stream.subscriptions().quotes.is_empty());
+        assert_eq!(stream.subscriptions().trades.len(), 1);
+
+        // The server's confirmation wins over what was requested.
+        match stream.recv().await {
+            Some(Ok(AlpacaStreamMessage::Subscription { .. })) => {}
+            other => panic!("expected Subscription, got {other:?}"),
+        }
+        assert_eq!(
+            stream.subscriptions().trades,
+            BTreeSet::from(["AAPL".to_string(), "MSFT".to_string()])
+        );
+        assert_eq!(
+            stream.subscriptions().news,
+            BTreeSet::from(["*".to_string()])
+        );
+    }
+
+    #[tokio::test]
+    async fn confirm_auth_fails_connect_on_auth_error() {
+        let url = serve_frames(vec![
+            r#"[{"T":"success","msg":"connected"}]"#,
+            r#"[{"T":"error","code":402,"msg":"auth failed"}]"#,
+        ])
+        .await;
+        let options = StreamOptions::default().confirm_auth(Duration::from_secs(5));
+        let err = match AlpacaStream::connect_url_with_options(&url, test_auth(), options).await {
+            Err(e) => e,
+            Ok(_) => panic!("expected auth failure"),
+        };
+        assert!(
+            err.to_string()
+                .contains("authentication rejected (402): auth failed"),
+            "{err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn confirm_auth_returns_stream_once_authenticated() {
+        let url = serve_frames(vec![
+            r#"[{"T":"success","msg":"connected"}]"#,
+            r#"[{"T":"success","msg":"authenticated"}]"#,
+            r#"[{"T":"t","S":"AAPL","p":"150.50","s":100,"t":"2024-06-01T14:30:00Z","x":"V","z":"C"}]"#,
+        ])
+        .await;
+        let options = StreamOptions::default().confirm_auth(Duration::from_secs(5));
+        let mut stream = AlpacaStream::connect_url_with_options(&url, test_auth(), options)
+            .await
+            .unwrap();
+        match stream.recv().await {
+            Some(Ok(AlpacaStreamMessage::Success { msg })) => assert_eq!(msg, "connected"),
+            other => panic!("expected Success, got {other:?}"),
+        }
+        // The `authenticated` confirmation itself isn't replayed.
+        match stream.recv().await {
+            Some(Ok(AlpacaStreamMessage::Trade(trade))) => assert_eq!(trade.symbol, "AAPL"),
+            other => panic!("expected Trade, got {other:?}"),
+        }
+    }
 }
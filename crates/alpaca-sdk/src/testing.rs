@@ -0,0 +1,67 @@
+//! Fixtures for tests written against the SDK: an [`AlpacaClient`] wired to a
+//! [`MockTransport`], and response bodies shaped like Alpaca's.
+//!
+//! Bodies are [`serde_json::Value`]s so a test can set the fields it cares
+//! about before passing `body.to_string()` to [`MockTransport::respond`].
+
+use std::sync::Arc;
+
+use api_client_core::MockTransport;
+use serde_json::{json, Value};
+
+use crate::client::AlpacaClient;
+use crate::config::AlpacaConfig;
+
+/// A paper-trading client whose requests all go to `transport`.
+pub fn mock_client(transport: MockTransport) -> (AlpacaClient, Arc<MockTransport>) {
+    let transport = Arc::new(transport);
+    let config = AlpacaConfig::paper("key".into(), "secret".into());
+    let client = AlpacaClient::with_transport(config, transport.clone()).unwrap();
+    (client, transport)
+}
+
+/// Account `PA123` with $10,000 cash and $20,000 buying power.
+pub fn account_json() -> Value {
+    json!({
+        "id": "acc-1", "account_number": "PA123", "status": "ACTIVE",
+        "currency": "USD", "buying_power": "20000", "cash": "10000",
+        "portfolio_value": "10000", "equity": "10000", "last_equity": "9900",
+        "long_market_value": "0", "short_market_value": "0",
+        "initial_margin": "0", "maintenance_margin": "0",
+        "daytrade_count": 0, "pattern_day_trader": false,
+        "trading_blocked": false, "transfers_blocked": false,
+        "account_blocked": false, "shorting_enabled": true,
+        "multiplier": "2", "created_at": "2024-01-02T15:04:05Z", "sma": "0"
+    })
+}
+
+/// A day order to buy 10 AAPL, created 2024-06-03T13:30:00Z.
+pub fn order_json(id: &str, status: &str) -> Value {
+    json!({
+        "id": id, "created_at": "2024-06-03T13:30:00Z", "symbol": "AAPL",
+        "qty": "10", "side": "buy", "status": status, "extended_hours": false
+    })
+}
+
+/// A filled order created and submitted at `submitted_at`, for walking the
+/// order history by time.
+pub fn order_at(id: &str, submitted_at: &str) -> Value {
+    let mut order = order_json(id, "filled");
+    order["created_at"] = submitted_at.into();
+    order["submitted_at"] = submitted_at.into();
+    order
+}
+
+/// A daily bar for 2024-01-`day` with every price at `close`.
+pub fn bar_json(day: u32, close: u32) -> Value {
+    json!({
+        "t": format!("2024-01-{day:02}T05:00:00Z"),
+        "o": close, "h": close, "l": close, "c": close, "v": 100
+    })
+}
+
+/// A page of daily bars for `days` in January 2024, followed by `next`.
+pub fn bars_page(days: &[u32], next: Option<&str>) -> String {
+    let bars: Vec<_> = days.iter().map(|&day| bar_json(day, 1)).collect();
+    json!({ "bars": bars, "next_page_token": next }).to_string()
+}
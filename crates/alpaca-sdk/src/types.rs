@@ -1,36 +1,101 @@
-use chrono::{DateTime, NaiveDate, Utc};
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
 // ── Account ──────────────────────────────────────────────────────────
 
+/// Account summary from `/v2/account`. Monetary amounts and `multiplier`
+/// arrive as strings and are parsed into [`Decimal`] on deserialization.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AlpacaAccountResponse {
     pub id: String,
     pub account_number: String,
     pub status: String,
     pub currency: String,
-    pub buying_power: String,
-    pub cash: String,
-    pub portfolio_value: String,
-    pub equity: String,
-    pub last_equity: String,
-    pub long_market_value: String,
-    pub short_market_value: String,
-    pub initial_margin: String,
-    pub maintenance_margin: String,
+    pub buying_power: Decimal,
+    pub cash: Decimal,
+    pub portfolio_value: Decimal,
+    pub equity: Decimal,
+    pub last_equity: Decimal,
+    pub long_market_value: Decimal,
+    pub short_market_value: Decimal,
+    pub initial_margin: Decimal,
+    pub maintenance_margin: Decimal,
     pub daytrade_count: i32,
     pub pattern_day_trader: bool,
     pub trading_blocked: bool,
     pub transfers_blocked: bool,
     pub account_blocked: bool,
     pub shorting_enabled: bool,
-    pub multiplier: String,
+    pub multiplier: Decimal,
     pub created_at: DateTime<Utc>,
-    #[serde(default)]
-    pub sma: Option<String>,
+    #[serde(default, with = "optional_decimal")]
+    pub sma: Option<Decimal>,
     #[serde(default)]
     pub crypto_status: Option<String>,
+    /// Options level in effect, 0 (disabled) to 3; absent on older payloads.
+    #[serde(default)]
+    pub options_trading_level: Option<u8>,
+    /// Highest options level the account is approved for.
+    #[serde(default)]
+    pub options_approved_level: Option<u8>,
+    #[serde(default, with = "optional_decimal")]
+    pub options_buying_power: Option<Decimal>,
+}
+
+/// Equity below which FINRA limits an account to three day trades in five
+/// business days.
+const PDT_MIN_EQUITY: i64 = 25_000;
+const PDT_MAX_DAY_TRADES: i32 = 3;
+
+impl AlpacaAccountResponse {
+    /// The options level in effect; 0 when options trading is off or the
+    /// payload doesn't say.
+    pub fn options_level(&self) -> u8 {
+        self.options_trading_level.unwrap_or(0)
+    }
+
+    /// Whether options orders at `level` (1–3) are allowed.
+    pub fn can_trade_options(&self, level: u8) -> bool {
+        level > 0 && self.options_level() >= level
+    }
+
+    /// Day trades left in the rolling five-day window before the pattern day
+    /// trader rule applies; `None` when unrestricted because equity is at
+    /// least $25,000.
+    pub fn day_trades_remaining(&self) -> Option<u32> {
+        if self.equity >= Decimal::from(PDT_MIN_EQUITY) {
+            return None;
+        }
+        Some((PDT_MAX_DAY_TRADES - self.daytrade_count).max(0) as u32)
+    }
+}
+
+/// An entry from `/v2/account/activities`: a fill (`FILL`) or a non-trade
+/// activity such as a dividend (`DIV`) or transfer. Fields not shared by
+/// every activity type are optional.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlpacaAccountActivity {
+    pub id: String,
+    pub activity_type: String,
+    #[serde(default)]
+    pub transaction_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub date: Option<NaiveDate>,
+    #[serde(default)]
+    pub symbol: Option<String>,
+    #[serde(default)]
+    pub side: Option<String>,
+    #[serde(default, with = "optional_decimal")]
+    pub qty: Option<Decimal>,
+    #[serde(default, with = "optional_decimal")]
+    pub price: Option<Decimal>,
+    #[serde(default, with = "optional_decimal")]
+    pub net_amount: Option<Decimal>,
+    #[serde(default)]
+    pub order_id: Option<String>,
 }
 
 // ── Orders ───────────────────────────────────────────────────────────
@@ -73,6 +138,79 @@ pub struct AlpacaOrderResponse {
     pub hwm: Option<String>,
 }
 
+impl AlpacaOrderResponse {
+    /// Whether the order can no longer change: `filled`, `canceled`,
+    /// `rejected`, `expired`, or `replaced` by another order.
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self.status.as_str(),
+            "filled" | "canceled" | "rejected" | "expired" | "replaced"
+        )
+    }
+
+    /// Quantity filled so far; zero when nothing has filled.
+    pub fn filled_qty_decimal(&self) -> Decimal {
+        parse_decimal(self.filled_qty.as_deref()).unwrap_or_default()
+    }
+
+    /// Quantity still to fill, never negative.
+    pub fn remaining_qty_decimal(&self) -> Decimal {
+        let qty = parse_decimal(Some(&self.qty)).unwrap_or_default();
+        (qty - self.filled_qty_decimal()).max(Decimal::ZERO)
+    }
+
+    /// Average fill price; `None` until something has filled.
+    pub fn filled_avg_price_decimal(&self) -> Option<Decimal> {
+        parse_decimal(self.filled_avg_price.as_deref())
+    }
+}
+
+/// Filters for [`AlpacaClient::list_all_orders`](crate::AlpacaClient::list_all_orders).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ListOrdersParams {
+    /// `open`, `closed` or `all`; the API defaults to `open`.
+    pub status: Option<String>,
+    /// Orders per request, at most 500 (the default).
+    pub limit: Option<u32>,
+    /// Only orders submitted after this instant.
+    pub after: Option<DateTime<Utc>>,
+    /// Only orders submitted before this instant; the walk starts here.
+    pub until: Option<DateTime<Utc>>,
+    /// Only orders for these symbols.
+    pub symbols: Vec<String>,
+}
+
+impl ListOrdersParams {
+    pub fn status(mut self, status: impl Into<String>) -> Self {
+        self.status = Some(status.into());
+        self
+    }
+
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn after(mut self, after: DateTime<Utc>) -> Self {
+        self.after = Some(after);
+        self
+    }
+
+    pub fn until(mut self, until: DateTime<Utc>) -> Self {
+        self.until = Some(until);
+        self
+    }
+
+    pub fn symbols(mut self, symbols: &[&str]) -> Self {
+        self.symbols = symbols.iter().map(|s| s.to_string()).collect();
+        self
+    }
+}
+
+fn parse_decimal(value: Option<&str>) -> Option<Decimal> {
+    value.and_then(|v| v.parse().ok())
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct AlpacaOrderRequest {
     pub symbol: String,
@@ -83,7 +221,151 @@ pub struct AlpacaOrderRequest {
     pub time_in_force: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub limit_price: Option<Decimal>,
+    /// Trigger price for `stop` and `stop_limit` orders.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_price: Option<Decimal>,
+    /// Trail, in percent of the high-water mark, for `trailing_stop` orders.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trail_percent: Option<Decimal>,
+    /// Trail, in dollars, for `trailing_stop` orders.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trail_price: Option<Decimal>,
     pub extended_hours: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_order_id: Option<String>,
+    /// e.g. `simple`, `bracket`, `oco`, `oto`; Alpaca assumes `simple`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_class: Option<String>,
+    /// e.g. `buy_to_open`, `sell_to_close`; needed for some options flows.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position_intent: Option<String>,
+}
+
+/// Fluent builder for an [`AlpacaOrderRequest`], submitted with
+/// [`AlpacaClient::submit`](crate::AlpacaClient::submit).
+///
+/// Starts as a `day` market order. [`limit`](Self::limit) and
+/// [`stop`](Self::stop) set the price and the matching type (both together
+/// make a `stop_limit`); [`trailing_percent`](Self::trailing_percent) and
+/// [`trailing_price`](Self::trailing_price) make a `trailing_stop`. The last
+/// call wins: switching between a priced and a trailing order clears the
+/// other kind's prices.
+#[derive(Debug, Clone)]
+pub struct OrderBuilder {
+    request: AlpacaOrderRequest,
+}
+
+impl OrderBuilder {
+    pub fn new(symbol: impl Into<String>, qty: i32, side: impl Into<String>) -> Self {
+        Self {
+            request: AlpacaOrderRequest {
+                symbol: symbol.into(),
+                qty,
+                side: side.into(),
+                order_type: "market".to_string(),
+                time_in_force: "day".to_string(),
+                limit_price: None,
+                stop_price: None,
+                trail_percent: None,
+                trail_price: None,
+                extended_hours: false,
+                client_order_id: None,
+                order_class: None,
+                position_intent: None,
+            },
+        }
+    }
+
+    pub fn buy(symbol: impl Into<String>, qty: i32) -> Self {
+        Self::new(symbol, qty, "buy")
+    }
+
+    pub fn sell(symbol: impl Into<String>, qty: i32) -> Self {
+        Self::new(symbol, qty, "sell")
+    }
+
+    pub fn limit(mut self, price: Decimal) -> Self {
+        self.request.limit_price = Some(price);
+        self.set_price_type();
+        self
+    }
+
+    pub fn stop(mut self, price: Decimal) -> Self {
+        self.request.stop_price = Some(price);
+        self.set_price_type();
+        self
+    }
+
+    /// Trail by `percent` of the high-water mark, e.g. `5` for 5%.
+    pub fn trailing_percent(mut self, percent: Decimal) -> Self {
+        self.set_trailing();
+        self.request.trail_percent = Some(percent);
+        self
+    }
+
+    /// Trail by a fixed dollar amount.
+    pub fn trailing_price(mut self, price: Decimal) -> Self {
+        self.set_trailing();
+        self.request.trail_price = Some(price);
+        self
+    }
+
+    pub fn time_in_force(mut self, time_in_force: impl Into<String>) -> Self {
+        self.request.time_in_force = time_in_force.into();
+        self
+    }
+
+    /// Good 'til canceled instead of the default `day`.
+    pub fn gtc(self) -> Self {
+        self.time_in_force("gtc")
+    }
+
+    /// Allow the order to fill in pre- and post-market sessions; Alpaca only
+    /// accepts this on `day` or `gtc` limit orders.
+    pub fn extended_hours(mut self) -> Self {
+        self.request.extended_hours = true;
+        self
+    }
+
+    pub fn client_order_id(mut self, id: impl Into<String>) -> Self {
+        self.request.client_order_id = Some(id.into());
+        self
+    }
+
+    pub fn position_intent(mut self, intent: impl Into<String>) -> Self {
+        self.request.position_intent = Some(intent.into());
+        self
+    }
+
+    pub fn build(self) -> AlpacaOrderRequest {
+        self.request
+    }
+
+    fn set_price_type(&mut self) {
+        self.request.trail_percent = None;
+        self.request.trail_price = None;
+        let order_type = match (self.request.limit_price, self.request.stop_price) {
+            (Some(_), Some(_)) => "stop_limit",
+            (Some(_), None) => "limit",
+            (None, Some(_)) => "stop",
+            (None, None) => "market",
+        };
+        self.request.order_type = order_type.to_string();
+    }
+
+    fn set_trailing(&mut self) {
+        self.request.order_type = "trailing_stop".to_string();
+        self.request.limit_price = None;
+        self.request.stop_price = None;
+        self.request.trail_percent = None;
+        self.request.trail_price = None;
+    }
+}
+
+impl From<OrderBuilder> for AlpacaOrderRequest {
+    fn from(builder: OrderBuilder) -> Self {
+        builder.build()
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -96,27 +378,121 @@ pub struct AlpacaReplaceOrderRequest {
     pub time_in_force: Option<String>,
 }
 
+/// One leg of a multi-leg options order.
+#[derive(Debug, Clone, Serialize)]
+pub struct OptionLeg {
+    /// OCC contract symbol, e.g. `AAPL240621C00190000`.
+    pub symbol: String,
+    /// Contracts of this leg per unit of the order's `qty`.
+    pub ratio_qty: u32,
+    pub side: String,
+    /// e.g. `buy_to_open`, `sell_to_close`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position_intent: Option<String>,
+}
+
+/// Body for an `mleg` order: the legs are filled together as one unit.
+#[derive(Debug, Clone, Serialize)]
+pub struct AlpacaMultilegOrderRequest {
+    pub order_class: String,
+    pub qty: i32,
+    #[serde(rename = "type")]
+    pub order_type: String,
+    pub time_in_force: String,
+    /// Net debit (positive) or credit (negative) for the whole spread.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit_price: Option<Decimal>,
+    pub legs: Vec<OptionLeg>,
+}
+
+/// Per-order outcome reported by `cancel_all_orders`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlpacaCancelOrderStatus {
+    pub id: String,
+    /// HTTP status of the individual cancel request.
+    pub status: u16,
+    #[serde(default)]
+    pub body: Option<serde_json::Value>,
+}
+
+impl AlpacaCancelOrderStatus {
+    /// Whether this order's cancel request succeeded.
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
 // ── Positions ────────────────────────────────────────────────────────
 
+/// Per-position outcome reported by `close_all_positions`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlpacaClosePositionStatus {
+    pub symbol: String,
+    /// HTTP status of the individual close request.
+    pub status: u16,
+    /// The closing order on success, or Alpaca's error body on failure.
+    #[serde(default)]
+    pub body: Option<serde_json::Value>,
+}
+
+impl AlpacaClosePositionStatus {
+    /// Whether this position's close request succeeded.
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AlpacaPositionResponse {
     pub asset_id: String,
     pub symbol: String,
     pub exchange: String,
     pub asset_class: String,
-    pub qty: String,
-    pub avg_entry_price: String,
+    pub qty: Decimal,
+    pub avg_entry_price: Decimal,
     pub side: String,
-    pub market_value: Option<String>,
-    pub cost_basis: String,
-    pub unrealized_pl: Option<String>,
-    pub unrealized_plpc: Option<String>,
-    pub unrealized_intraday_pl: Option<String>,
-    pub unrealized_intraday_plpc: Option<String>,
-    pub current_price: Option<String>,
-    pub lastday_price: Option<String>,
-    pub change_today: Option<String>,
-    pub qty_available: Option<String>,
+    #[serde(default, with = "optional_decimal")]
+    pub market_value: Option<Decimal>,
+    pub cost_basis: Decimal,
+    #[serde(default, with = "optional_decimal")]
+    pub unrealized_pl: Option<Decimal>,
+    #[serde(default, with = "optional_decimal")]
+    pub unrealized_plpc: Option<Decimal>,
+    #[serde(default, with = "optional_decimal")]
+    pub unrealized_intraday_pl: Option<Decimal>,
+    #[serde(default, with = "optional_decimal")]
+    pub unrealized_intraday_plpc: Option<Decimal>,
+    #[serde(default, with = "optional_decimal")]
+    pub current_price: Option<Decimal>,
+    #[serde(default, with = "optional_decimal")]
+    pub lastday_price: Option<Decimal>,
+    #[serde(default, with = "optional_decimal")]
+    pub change_today: Option<Decimal>,
+    #[serde(default, with = "optional_decimal")]
+    pub qty_available: Option<Decimal>,
+}
+
+impl AlpacaPositionResponse {
+    /// Unrealized P/L, treating a missing value as zero.
+    pub fn unrealized_pl_decimal(&self) -> Decimal {
+        self.unrealized_pl.unwrap_or_default()
+    }
+
+    /// Market value, treating a missing value as zero. Negative for shorts.
+    pub fn market_value_decimal(&self) -> Decimal {
+        self.market_value.unwrap_or_default()
+    }
+
+    /// Shares not held by open orders, i.e. what can be sold or covered now;
+    /// falls back to `qty` when the field is absent.
+    pub fn qty_available_decimal(&self) -> Decimal {
+        self.qty_available.unwrap_or(self.qty)
+    }
+}
+
+/// Sum of unrealized P/L across positions; positions without a value count as zero.
+pub fn total_unrealized_pl(positions: &[AlpacaPositionResponse]) -> Decimal {
+    positions.iter().map(|p| p.unrealized_pl_decimal()).sum()
 }
 
 // ── Assets ───────────────────────────────────────────────────────────
@@ -146,14 +522,40 @@ pub struct AlpacaAssetResponse {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AlpacaCalendarDay {
     pub date: NaiveDate,
-    pub open: String,
-    pub close: String,
+    /// Regular session open, US/Eastern.
+    #[serde(with = "eastern_time")]
+    pub open: NaiveTime,
+    /// Regular session close, US/Eastern; `13:00` on early-close days.
+    #[serde(with = "eastern_time")]
+    pub close: NaiveTime,
     #[serde(default)]
     pub session_open: Option<String>,
     #[serde(default)]
     pub session_close: Option<String>,
 }
 
+impl AlpacaCalendarDay {
+    /// Regular session open and close as UTC instants.
+    pub fn session_bounds(&self) -> (DateTime<Utc>, DateTime<Utc>) {
+        (
+            self.eastern_to_utc(self.open),
+            self.eastern_to_utc(self.close),
+        )
+    }
+
+    fn eastern_to_utc(&self, time: NaiveTime) -> DateTime<Utc> {
+        let local = self.date.and_time(time);
+        // Sessions never fall in a DST gap; `earliest` just avoids a panic.
+        match local
+            .and_local_timezone(chrono_tz::America::New_York)
+            .earliest()
+        {
+            Some(eastern) => eastern.with_timezone(&Utc),
+            None => local.and_utc(),
+        }
+    }
+}
+
 // ── Quotes ───────────────────────────────────────────────────────────
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -162,26 +564,100 @@ pub struct AlpacaQuoteResponse {
     pub quote: AlpacaQuote,
 }
 
+/// One page of historical quotes from `/v2/stocks/{symbol}/quotes`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlpacaQuotesPageResponse {
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub quotes: Vec<AlpacaQuote>,
+    pub symbol: Option<String>,
+    pub next_page_token: Option<String>,
+}
+
+/// Latest crypto quotes keyed by pair, e.g. `"BTC/USD"`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlpacaCryptoQuotesResponse {
+    pub quotes: std::collections::HashMap<String, AlpacaQuote>,
+}
+
+/// Latest crypto order books keyed by pair, e.g. `"BTC/USD"`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlpacaCryptoOrderbooksResponse {
+    pub orderbooks: std::collections::HashMap<String, AlpacaOrderbook>,
+}
+
+/// Bid and ask depth for one crypto pair, best price first on each side.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlpacaOrderbook {
+    #[serde(rename = "t")]
+    pub timestamp: DateTime<Utc>,
+    #[serde(rename = "b", default)]
+    pub bids: Vec<AlpacaOrderbookLevel>,
+    #[serde(rename = "a", default)]
+    pub asks: Vec<AlpacaOrderbookLevel>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AlpacaOrderbookLevel {
+    #[serde(rename = "p")]
+    pub price: Decimal,
+    #[serde(rename = "s")]
+    pub size: Decimal,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AlpacaQuote {
     #[serde(rename = "ap")]
     pub ask_price: Decimal,
     #[serde(rename = "as")]
-    pub ask_size: i32,
-    #[serde(rename = "ax")]
-    pub ask_exchange: String,
+    pub ask_size: Decimal,
+    /// `None` when the feed omits it (crypto, some sparse equity quotes).
+    #[serde(
+        rename = "ax",
+        default,
+        deserialize_with = "deserialize_optional_code",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub ask_exchange: Option<Exchange>,
     #[serde(rename = "bp")]
     pub bid_price: Decimal,
     #[serde(rename = "bs")]
-    pub bid_size: i32,
-    #[serde(rename = "bx")]
-    pub bid_exchange: String,
+    pub bid_size: Decimal,
+    #[serde(
+        rename = "bx",
+        default,
+        deserialize_with = "deserialize_optional_code",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub bid_exchange: Option<Exchange>,
     #[serde(rename = "c", default)]
     pub conditions: Option<Vec<String>>,
     #[serde(rename = "t")]
     pub timestamp: DateTime<Utc>,
-    #[serde(rename = "z")]
-    pub tape: String,
+    #[serde(
+        rename = "z",
+        default,
+        deserialize_with = "deserialize_optional_code",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub tape: Option<Tape>,
+}
+
+impl AlpacaQuote {
+    /// Midpoint of bid and ask; `None` when either side is empty (zero), as
+    /// happens outside market hours.
+    pub fn mid_price(&self) -> Option<Decimal> {
+        self.is_two_sided()
+            .then(|| (self.bid_price + self.ask_price) / Decimal::TWO)
+    }
+
+    /// Ask minus bid; `None` when either side is empty.
+    pub fn spread(&self) -> Option<Decimal> {
+        self.is_two_sided().then(|| self.ask_price - self.bid_price)
+    }
+
+    fn is_two_sided(&self) -> bool {
+        self.bid_price > Decimal::ZERO && self.ask_price > Decimal::ZERO
+    }
 }
 
 // ── Trades ───────────────────────────────────────────────────────────
@@ -198,16 +674,37 @@ pub struct AlpacaTrade {
     pub timestamp: DateTime<Utc>,
     #[serde(rename = "p")]
     pub price: Decimal,
+    /// Fractional for crypto, e.g. `0.0015` BTC.
     #[serde(rename = "s")]
-    pub size: i64,
-    #[serde(rename = "x")]
-    pub exchange: String,
+    pub size: Decimal,
+    /// `None` for crypto trades, which carry no exchange.
+    #[serde(
+        rename = "x",
+        default,
+        deserialize_with = "deserialize_optional_code",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub exchange: Option<Exchange>,
     #[serde(rename = "i")]
     pub id: i64,
     #[serde(rename = "c", default)]
     pub conditions: Option<Vec<String>>,
-    #[serde(rename = "z")]
-    pub tape: String,
+    /// `None` for crypto trades, which carry no tape.
+    #[serde(
+        rename = "z",
+        default,
+        deserialize_with = "deserialize_optional_code",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub tape: Option<Tape>,
+}
+
+impl AlpacaTrade {
+    /// Whether this is a regular sale; see
+    /// [`is_regular_trade`](crate::condition::is_regular_trade).
+    pub fn is_regular(&self) -> bool {
+        crate::condition::is_regular_trade(self.conditions.as_deref())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -243,6 +740,18 @@ where
     Ok(Option::deserialize(deserializer)?.unwrap_or_default())
 }
 
+/// A tape or exchange code that feeds may omit (crypto, sparse quotes) or send
+/// empty; both become `None`.
+fn deserialize_optional_code<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: From<String>,
+{
+    Ok(Option::<String>::deserialize(deserializer)?
+        .filter(|code| !code.is_empty())
+        .map(T::from))
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AlpacaBar {
     #[serde(rename = "t")]
@@ -259,6 +768,596 @@ pub struct AlpacaBar {
     pub volume: i64,
 }
 
+/// Ordering of historical results by timestamp.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortDirection {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl SortDirection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortDirection::Asc => "asc",
+            SortDirection::Desc => "desc",
+        }
+    }
+}
+
+impl std::fmt::Display for SortDirection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Corporate-action adjustment applied to historical bar prices.
+///
+/// Defaults to [`Split`](Self::Split): prices comparable across splits but
+/// not dividends. Use [`All`](Self::All) for total-return series and
+/// [`Raw`](Self::Raw) for prices as traded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Adjustment {
+    Raw,
+    #[default]
+    Split,
+    Dividend,
+    All,
+}
+
+impl Adjustment {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Adjustment::Raw => "raw",
+            Adjustment::Split => "split",
+            Adjustment::Dividend => "dividend",
+            Adjustment::All => "all",
+        }
+    }
+}
+
+impl std::fmt::Display for Adjustment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for Adjustment {
+    type Err = crate::error::AlpacaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "raw" => Ok(Adjustment::Raw),
+            "split" => Ok(Adjustment::Split),
+            "dividend" => Ok(Adjustment::Dividend),
+            "all" => Ok(Adjustment::All),
+            _ => Err(crate::error::AlpacaError::Config(format!(
+                "Invalid adjustment: {s:?}"
+            ))),
+        }
+    }
+}
+
+/// Consolidated tape a US equity trades on.
+///
+/// Unrecognized codes are kept as [`Unknown`](Self::Unknown), so parsing
+/// never fails.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum Tape {
+    /// `A`: NYSE-listed securities.
+    A,
+    /// `B`: NYSE Arca, NYSE American, and other regional listings.
+    B,
+    /// `C`: Nasdaq-listed securities.
+    C,
+    /// `O`: over-the-counter securities.
+    Otc,
+    Unknown(String),
+}
+
+impl Tape {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Tape::A => "A",
+            Tape::B => "B",
+            Tape::C => "C",
+            Tape::Otc => "O",
+            Tape::Unknown(code) => code,
+        }
+    }
+
+    /// Human-readable description of the tape.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Tape::A => "Tape A (NYSE)",
+            Tape::B => "Tape B (NYSE Arca and regional)",
+            Tape::C => "Tape C (Nasdaq)",
+            Tape::Otc => "OTC",
+            Tape::Unknown(_) => "Unknown",
+        }
+    }
+}
+
+impl From<String> for Tape {
+    fn from(code: String) -> Self {
+        match code.as_str() {
+            "A" => Tape::A,
+            "B" => Tape::B,
+            "C" => Tape::C,
+            "O" => Tape::Otc,
+            _ => Tape::Unknown(code),
+        }
+    }
+}
+
+impl From<Tape> for String {
+    fn from(tape: Tape) -> Self {
+        tape.as_str().to_string()
+    }
+}
+
+impl std::fmt::Display for Tape {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for Tape {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(s.to_string().into())
+    }
+}
+
+/// Exchange a US equity trade or quote was reported by, from its
+/// single-letter code.
+///
+/// Unrecognized codes are kept as [`Unknown`](Self::Unknown), so parsing
+/// never fails.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum Exchange {
+    /// `A`
+    NyseAmerican,
+    /// `B`
+    NasdaqBx,
+    /// `C`
+    Nsx,
+    /// `D`
+    FinraAdf,
+    /// `E`
+    MarketIndependent,
+    /// `H`
+    Miax,
+    /// `I`
+    Ise,
+    /// `J`
+    CboeEdga,
+    /// `K`
+    CboeEdgx,
+    /// `L`
+    Ltse,
+    /// `M`
+    Chicago,
+    /// `N`
+    Nyse,
+    /// `P`
+    NyseArca,
+    /// `Q`
+    Nasdaq,
+    /// `S`
+    NasdaqSmallCap,
+    /// `T`
+    NasdaqInt,
+    /// `U`
+    Memx,
+    /// `V`
+    Iex,
+    /// `W`
+    Cboe,
+    /// `X`
+    NasdaqPsx,
+    /// `Y`
+    CboeByx,
+    /// `Z`
+    CboeBzx,
+    Unknown(String),
+}
+
+impl Exchange {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Exchange::NyseAmerican => "A",
+            Exchange::NasdaqBx => "B",
+            Exchange::Nsx => "C",
+            Exchange::FinraAdf => "D",
+            Exchange::MarketIndependent => "E",
+            Exchange::Miax => "H",
+            Exchange::Ise => "I",
+            Exchange::CboeEdga => "J",
+            Exchange::CboeEdgx => "K",
+            Exchange::Ltse => "L",
+            Exchange::Chicago => "M",
+            Exchange::Nyse => "N",
+            Exchange::NyseArca => "P",
+            Exchange::Nasdaq => "Q",
+            Exchange::NasdaqSmallCap => "S",
+            Exchange::NasdaqInt => "T",
+            Exchange::Memx => "U",
+            Exchange::Iex => "V",
+            Exchange::Cboe => "W",
+            Exchange::NasdaqPsx => "X",
+            Exchange::CboeByx => "Y",
+            Exchange::CboeBzx => "Z",
+            Exchange::Unknown(code) => code,
+        }
+    }
+
+    /// Human-readable exchange name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Exchange::NyseAmerican => "NYSE American (AMEX)",
+            Exchange::NasdaqBx => "NASDAQ OMX BX",
+            Exchange::Nsx => "National Stock Exchange",
+            Exchange::FinraAdf => "FINRA ADF",
+            Exchange::MarketIndependent => "Market Independent",
+            Exchange::Miax => "MIAX",
+            Exchange::Ise => "International Securities Exchange",
+            Exchange::CboeEdga => "Cboe EDGA",
+            Exchange::CboeEdgx => "Cboe EDGX",
+            Exchange::Ltse => "Long Term Stock Exchange",
+            Exchange::Chicago => "Chicago Stock Exchange",
+            Exchange::Nyse => "New York Stock Exchange",
+            Exchange::NyseArca => "NYSE Arca",
+            Exchange::Nasdaq => "NASDAQ OMX",
+            Exchange::NasdaqSmallCap => "NASDAQ Small Cap",
+            Exchange::NasdaqInt => "NASDAQ Int",
+            Exchange::Memx => "Members Exchange",
+            Exchange::Iex => "IEX",
+            Exchange::Cboe => "CBOE",
+            Exchange::NasdaqPsx => "NASDAQ OMX PSX",
+            Exchange::CboeByx => "Cboe BYX",
+            Exchange::CboeBzx => "Cboe BZX",
+            Exchange::Unknown(_) => "Unknown",
+        }
+    }
+}
+
+impl From<String> for Exchange {
+    fn from(code: String) -> Self {
+        match code.as_str() {
+            "A" => Exchange::NyseAmerican,
+            "B" => Exchange::NasdaqBx,
+            "C" => Exchange::Nsx,
+            "D" => Exchange::FinraAdf,
+            "E" => Exchange::MarketIndependent,
+            "H" => Exchange::Miax,
+            "I" => Exchange::Ise,
+            "J" => Exchange::CboeEdga,
+            "K" => Exchange::CboeEdgx,
+            "L" => Exchange::Ltse,
+            "M" => Exchange::Chicago,
+            "N" => Exchange::Nyse,
+            "P" => Exchange::NyseArca,
+            "Q" => Exchange::Nasdaq,
+            "S" => Exchange::NasdaqSmallCap,
+            "T" => Exchange::NasdaqInt,
+            "U" => Exchange::Memx,
+            "V" => Exchange::Iex,
+            "W" => Exchange::Cboe,
+            "X" => Exchange::NasdaqPsx,
+            "Y" => Exchange::CboeByx,
+            "Z" => Exchange::CboeBzx,
+            _ => Exchange::Unknown(code),
+        }
+    }
+}
+
+impl From<Exchange> for String {
+    fn from(exchange: Exchange) -> Self {
+        exchange.as_str().to_string()
+    }
+}
+
+impl std::fmt::Display for Exchange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for Exchange {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(s.to_string().into())
+    }
+}
+
+/// Start or end of a historical data range: a whole date or an exact instant.
+///
+/// Dates are sent as `YYYY-MM-DD`; instants as RFC 3339 in UTC with a `Z`
+/// suffix, e.g. `2024-06-03T13:30:00Z`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeBound {
+    Date(NaiveDate),
+    DateTime(DateTime<Utc>),
+}
+
+impl TimeBound {
+    /// The calendar date (UTC) this bound falls on.
+    pub fn date(&self) -> NaiveDate {
+        match self {
+            TimeBound::Date(date) => *date,
+            TimeBound::DateTime(ts) => ts.date_naive(),
+        }
+    }
+}
+
+impl From<NaiveDate> for TimeBound {
+    fn from(date: NaiveDate) -> Self {
+        TimeBound::Date(date)
+    }
+}
+
+impl From<DateTime<Utc>> for TimeBound {
+    fn from(ts: DateTime<Utc>) -> Self {
+        TimeBound::DateTime(ts)
+    }
+}
+
+impl std::fmt::Display for TimeBound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimeBound::Date(date) => write!(f, "{date}"),
+            TimeBound::DateTime(ts) => {
+                f.write_str(&ts.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true))
+            }
+        }
+    }
+}
+
+/// Bar aggregation period, e.g. `5Min`, `1Hour`, `1Day`.
+///
+/// Alpaca accepts 1–59 minutes, 1–23 hours, one day, one week, and 1, 2, 3,
+/// 4, 6, or 12 months. Parsing is case-insensitive and rejects anything else,
+/// so typos fail before a request is sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimeFrame {
+    Minute(u32),
+    Hour(u32),
+    Day,
+    Week,
+    Month(u32),
+}
+
+impl TimeFrame {
+    pub const MIN_1: TimeFrame = TimeFrame::Minute(1);
+    pub const MIN_5: TimeFrame = TimeFrame::Minute(5);
+    pub const MIN_15: TimeFrame = TimeFrame::Minute(15);
+    pub const MIN_30: TimeFrame = TimeFrame::Minute(30);
+    pub const HOUR_1: TimeFrame = TimeFrame::Hour(1);
+    pub const HOUR_2: TimeFrame = TimeFrame::Hour(2);
+    pub const HOUR_4: TimeFrame = TimeFrame::Hour(4);
+    pub const DAY_1: TimeFrame = TimeFrame::Day;
+    pub const WEEK_1: TimeFrame = TimeFrame::Week;
+    pub const MONTH_1: TimeFrame = TimeFrame::Month(1);
+
+    fn is_valid(&self) -> bool {
+        match *self {
+            TimeFrame::Minute(n) => (1..=59).contains(&n),
+            TimeFrame::Hour(n) => (1..=23).contains(&n),
+            TimeFrame::Day | TimeFrame::Week => true,
+            TimeFrame::Month(n) => matches!(n, 1 | 2 | 3 | 4 | 6 | 12),
+        }
+    }
+}
+
+impl std::fmt::Display for TimeFrame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimeFrame::Minute(n) => write!(f, "{n}Min"),
+            TimeFrame::Hour(n) => write!(f, "{n}Hour"),
+            TimeFrame::Day => write!(f, "1Day"),
+            TimeFrame::Week => write!(f, "1Week"),
+            TimeFrame::Month(n) => write!(f, "{n}Month"),
+        }
+    }
+}
+
+impl std::str::FromStr for TimeFrame {
+    type Err = crate::error::AlpacaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || crate::error::AlpacaError::Config(format!("Invalid timeframe: {s:?}"));
+
+        let split = s.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?;
+        let amount: u32 = s[..split].parse().map_err(|_| invalid())?;
+        let timeframe = match s[split..].to_ascii_lowercase().as_str() {
+            "min" => TimeFrame::Minute(amount),
+            "hour" => TimeFrame::Hour(amount),
+            "day" if amount == 1 => TimeFrame::Day,
+            "week" if amount == 1 => TimeFrame::Week,
+            "month" => TimeFrame::Month(amount),
+            _ => return Err(invalid()),
+        };
+        if timeframe.is_valid() {
+            Ok(timeframe)
+        } else {
+            Err(invalid())
+        }
+    }
+}
+
+/// Range and options for
+/// [`AlpacaClient::get_bars`](crate::AlpacaClient::get_bars) and its page,
+/// stream and multi-symbol variants. Unset options are left to the client
+/// (feed) or the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BarsRequest {
+    pub start: TimeBound,
+    pub end: TimeBound,
+    pub timeframe: TimeFrame,
+    /// Data feed, e.g. `iex` or `sip`; the client's default feed when unset.
+    pub feed: Option<String>,
+    /// Defaults to [`Adjustment::Split`].
+    pub adjustment: Option<Adjustment>,
+    /// Timestamp order; the server default is ascending.
+    pub sort: Option<SortDirection>,
+    /// Price currency; the server default is USD.
+    pub currency: Option<String>,
+    /// Bars per API call (default 10000). Doesn't limit the result.
+    pub page_size: Option<u32>,
+    /// Total bars to return across all pages; `None` walks every page.
+    pub max_bars: Option<usize>,
+    /// Resolve the symbol as it was named on this date, so history follows
+    /// the company across ticker changes.
+    pub asof: Option<NaiveDate>,
+    /// Overrides the client timeout for each page request.
+    pub timeout: Option<Duration>,
+}
+
+impl BarsRequest {
+    pub fn new(
+        start: impl Into<TimeBound>,
+        end: impl Into<TimeBound>,
+        timeframe: TimeFrame,
+    ) -> Self {
+        Self {
+            start: start.into(),
+            end: end.into(),
+            timeframe,
+            feed: None,
+            adjustment: None,
+            sort: None,
+            currency: None,
+            page_size: None,
+            max_bars: None,
+            asof: None,
+            timeout: None,
+        }
+    }
+
+    pub fn feed(mut self, feed: impl Into<String>) -> Self {
+        self.feed = Some(feed.into());
+        self
+    }
+
+    pub fn adjustment(mut self, adjustment: Adjustment) -> Self {
+        self.adjustment = Some(adjustment);
+        self
+    }
+
+    pub fn sort(mut self, sort: SortDirection) -> Self {
+        self.sort = Some(sort);
+        self
+    }
+
+    pub fn currency(mut self, currency: impl Into<String>) -> Self {
+        self.currency = Some(currency.into());
+        self
+    }
+
+    pub fn page_size(mut self, page_size: u32) -> Self {
+        self.page_size = Some(page_size);
+        self
+    }
+
+    pub fn max_bars(mut self, max_bars: usize) -> Self {
+        self.max_bars = Some(max_bars);
+        self
+    }
+
+    pub fn asof(mut self, asof: NaiveDate) -> Self {
+        self.asof = Some(asof);
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+}
+
+/// Range and options for
+/// [`AlpacaClient::get_trades`](crate::AlpacaClient::get_trades),
+/// [`get_trades_page`](crate::AlpacaClient::get_trades_page) and
+/// [`get_quotes`](crate::AlpacaClient::get_quotes). Unset options are left to
+/// the client (feed) or the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TicksRequest {
+    pub start: TimeBound,
+    pub end: TimeBound,
+    /// Data feed, e.g. `iex` or `sip`; the client's default feed when unset.
+    pub feed: Option<String>,
+    /// Timestamp order; the server default is ascending.
+    pub sort: Option<SortDirection>,
+    /// Price currency; the server default is USD.
+    pub currency: Option<String>,
+    /// Records per API call (default 10000). Doesn't limit the result.
+    pub page_size: Option<u32>,
+    /// Total records to return across all pages; `None` walks every page.
+    /// Ignored by the single-page call.
+    pub limit: Option<usize>,
+    /// Resolve the symbol as it was named on this date, as for
+    /// [`BarsRequest::asof`].
+    pub asof: Option<NaiveDate>,
+    /// Overrides the client timeout for each page request.
+    pub timeout: Option<Duration>,
+}
+
+impl TicksRequest {
+    pub fn new(start: impl Into<TimeBound>, end: impl Into<TimeBound>) -> Self {
+        Self {
+            start: start.into(),
+            end: end.into(),
+            feed: None,
+            sort: None,
+            currency: None,
+            page_size: None,
+            limit: None,
+            asof: None,
+            timeout: None,
+        }
+    }
+
+    pub fn feed(mut self, feed: impl Into<String>) -> Self {
+        self.feed = Some(feed.into());
+        self
+    }
+
+    pub fn sort(mut self, sort: SortDirection) -> Self {
+        self.sort = Some(sort);
+        self
+    }
+
+    pub fn currency(mut self, currency: impl Into<String>) -> Self {
+        self.currency = Some(currency.into());
+        self
+    }
+
+    pub fn page_size(mut self, page_size: u32) -> Self {
+        self.page_size = Some(page_size);
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn asof(mut self, asof: NaiveDate) -> Self {
+        self.asof = Some(asof);
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+}
+
 // ── Snapshot ─────────────────────────────────────────────────────────
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -275,6 +1374,62 @@ pub struct AlpacaSnapshot {
     pub prev_daily_bar: Option<AlpacaBar>,
 }
 
+impl AlpacaSnapshot {
+    /// Midpoint of the latest quote's bid and ask.
+    pub fn mid_price(&self) -> Option<Decimal> {
+        self.latest_quote.as_ref()?.mid_price()
+    }
+
+    /// Price of the latest trade.
+    pub fn last_price(&self) -> Option<Decimal> {
+        self.latest_trade.as_ref().map(|t| t.price)
+    }
+
+    /// Ask minus bid of the latest quote.
+    pub fn spread(&self) -> Option<Decimal> {
+        self.latest_quote.as_ref()?.spread()
+    }
+}
+
+// ── Screener ─────────────────────────────────────────────────────────
+
+/// Top market movers from `/v1beta1/screener/stocks/movers`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlpacaMoversResponse {
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub gainers: Vec<AlpacaMover>,
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub losers: Vec<AlpacaMover>,
+    #[serde(default)]
+    pub market_type: Option<String>,
+    #[serde(default)]
+    pub last_updated: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlpacaMover {
+    pub symbol: String,
+    pub price: Decimal,
+    pub change: Decimal,
+    pub percent_change: Decimal,
+}
+
+/// Most active stocks from `/v1beta1/screener/stocks/most-actives`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlpacaMostActivesResponse {
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub most_actives: Vec<AlpacaMostActive>,
+    #[serde(default)]
+    pub last_updated: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlpacaMostActive {
+    pub symbol: String,
+    pub volume: i64,
+    pub trade_count: i64,
+}
+
 // ── Clock ────────────────────────────────────────────────────────────
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -285,8 +1440,31 @@ pub struct AlpacaClockResponse {
     pub next_close: DateTime<Utc>,
 }
 
+impl AlpacaClockResponse {
+    /// Time from the server's `timestamp` until `next_open`; zero while the
+    /// market is open.
+    pub fn time_until_open(&self) -> std::time::Duration {
+        self.time_until_open_at(self.timestamp)
+    }
+
+    /// Time from `now` until `next_open`, e.g. with `now` from a
+    /// [`Clock`](crate::clock::Clock); zero while the market is open or once
+    /// `now` has passed `next_open`.
+    pub fn time_until_open_at(&self, now: DateTime<Utc>) -> std::time::Duration {
+        if self.is_open {
+            return std::time::Duration::ZERO;
+        }
+        (self.next_open - now).to_std().unwrap_or_default()
+    }
+}
+
 // ── Stream Messages ──────────────────────────────────────────────────
 
+/// A single message from the market data stream, discriminated by its `T` field.
+///
+/// Message types the SDK doesn't model (and known types whose payload doesn't
+/// match the expected shape) deserialize into [`Unknown`](Self::Unknown) so a
+/// new message type never tears down the stream.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "T")]
 pub enum AlpacaStreamMessage {
@@ -299,6 +1477,10 @@ pub enum AlpacaStreamMessage {
         trades: Option<Vec<String>>,
         quotes: Option<Vec<String>>,
         bars: Option<Vec<String>>,
+        #[serde(rename = "updatedBars", default)]
+        updated_bars: Option<Vec<String>>,
+        #[serde(rename = "dailyBars", default)]
+        daily_bars: Option<Vec<String>>,
     },
     #[serde(rename = "t")]
     Trade(AlpacaStreamTrade),
@@ -306,6 +1488,60 @@ pub enum AlpacaStreamMessage {
     Quote(AlpacaStreamQuote),
     #[serde(rename = "b")]
     Bar(AlpacaStreamBar),
+    #[serde(rename = "d")]
+    DailyBar(AlpacaStreamBar),
+    #[serde(rename = "u")]
+    UpdatedBar(AlpacaStreamBar),
+    #[serde(rename = "c")]
+    Correction(AlpacaStreamCorrection),
+    #[serde(rename = "x")]
+    CancelError(AlpacaStreamCancelError),
+    #[serde(rename = "s")]
+    Status(AlpacaStreamStatus),
+    #[serde(rename = "l")]
+    Luld(AlpacaStreamLuld),
+    #[serde(rename = "n")]
+    News(AlpacaNewsArticle),
+    /// Server liveness ping (sent periodically by the test feed); carries no
+    /// market data.
+    #[serde(rename = "heartbeat")]
+    Heartbeat(ControlMessage),
+    /// Synthetic event emitted after an automatic reconnect; never sent by Alpaca.
+    #[serde(rename = "reconnected", skip_deserializing)]
+    Reconnected,
+    /// Any message the SDK doesn't recognize, kept as raw JSON.
+    #[serde(untagged)]
+    Unknown(serde_json::Value),
+}
+
+impl AlpacaStreamMessage {
+    /// The subscription channel, symbol, and event time of a market data
+    /// message; `None` for control messages and news.
+    pub fn market_event(&self) -> Option<(&'static str, &str, DateTime<Utc>)> {
+        match self {
+            Self::Trade(t) => Some(("trades", &t.symbol, t.timestamp)),
+            Self::Quote(q) => Some(("quotes", &q.symbol, q.timestamp)),
+            Self::Bar(b) => Some(("bars", &b.symbol, b.timestamp)),
+            Self::DailyBar(b) => Some(("dailyBars", &b.symbol, b.timestamp)),
+            Self::UpdatedBar(b) => Some(("updatedBars", &b.symbol, b.timestamp)),
+            Self::Correction(c) => Some(("corrections", &c.symbol, c.timestamp)),
+            Self::CancelError(c) => Some(("cancelErrors", &c.symbol, c.timestamp)),
+            Self::Status(s) => Some(("statuses", &s.symbol, s.timestamp)),
+            Self::Luld(l) => Some(("lulds", &l.symbol, l.timestamp)),
+            _ => None,
+        }
+    }
+}
+
+/// Body of a connection-level control message such as
+/// [`Heartbeat`](AlpacaStreamMessage::Heartbeat).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ControlMessage {
+    /// Server time the message was sent, when included.
+    #[serde(rename = "t", default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub msg: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -314,58 +1550,206 @@ pub struct AlpacaStreamTrade {
     pub symbol: String,
     #[serde(rename = "p")]
     pub price: Decimal,
+    /// Fractional for crypto, e.g. `0.0015` BTC.
     #[serde(rename = "s")]
-    pub size: i64,
+    pub size: Decimal,
     #[serde(rename = "t")]
     pub timestamp: DateTime<Utc>,
-    #[serde(rename = "x")]
-    pub exchange: String,
+    /// `None` for crypto trades, which carry no exchange.
+    #[serde(
+        rename = "x",
+        default,
+        deserialize_with = "deserialize_optional_code",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub exchange: Option<Exchange>,
+    #[serde(rename = "c", default)]
+    pub conditions: Option<Vec<String>>,
+    /// `None` for crypto trades, which carry no tape.
+    #[serde(
+        rename = "z",
+        default,
+        deserialize_with = "deserialize_optional_code",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub tape: Option<Tape>,
+}
+
+impl AlpacaStreamTrade {
+    /// Whether this is a regular sale; see
+    /// [`is_regular_trade`](crate::condition::is_regular_trade).
+    pub fn is_regular(&self) -> bool {
+        crate::condition::is_regular_trade(self.conditions.as_deref())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlpacaStreamQuote {
+    #[serde(rename = "S")]
+    pub symbol: String,
+    #[serde(rename = "ap")]
+    pub ask_price: Decimal,
+    #[serde(rename = "as")]
+    pub ask_size: Decimal,
+    /// `None` when the feed omits it (crypto, some sparse equity quotes).
+    #[serde(
+        rename = "ax",
+        default,
+        deserialize_with = "deserialize_optional_code",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub ask_exchange: Option<Exchange>,
+    #[serde(rename = "bp")]
+    pub bid_price: Decimal,
+    #[serde(rename = "bs")]
+    pub bid_size: Decimal,
+    #[serde(
+        rename = "bx",
+        default,
+        deserialize_with = "deserialize_optional_code",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub bid_exchange: Option<Exchange>,
     #[serde(rename = "c", default)]
     pub conditions: Option<Vec<String>>,
+    #[serde(rename = "t")]
+    pub timestamp: DateTime<Utc>,
+    #[serde(
+        rename = "z",
+        default,
+        deserialize_with = "deserialize_optional_code",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub tape: Option<Tape>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlpacaStreamBar {
+    #[serde(rename = "S")]
+    pub symbol: String,
+    #[serde(rename = "o")]
+    pub open: Decimal,
+    #[serde(rename = "h")]
+    pub high: Decimal,
+    #[serde(rename = "l")]
+    pub low: Decimal,
+    #[serde(rename = "c")]
+    pub close: Decimal,
+    #[serde(rename = "v")]
+    pub volume: i64,
+    #[serde(rename = "t")]
+    pub timestamp: DateTime<Utc>,
+    #[serde(rename = "n", default)]
+    pub trade_count: Option<i64>,
+    #[serde(rename = "vw", default)]
+    pub vwap: Option<Decimal>,
+}
+
+/// Correction to a previously reported trade.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlpacaStreamCorrection {
+    #[serde(rename = "S")]
+    pub symbol: String,
+    #[serde(rename = "x")]
+    pub exchange: Exchange,
+    #[serde(rename = "oi")]
+    pub original_id: i64,
+    #[serde(rename = "op")]
+    pub original_price: Decimal,
+    #[serde(rename = "os")]
+    pub original_size: Decimal,
+    #[serde(rename = "oc", default)]
+    pub original_conditions: Option<Vec<String>>,
+    #[serde(rename = "ci")]
+    pub corrected_id: i64,
+    #[serde(rename = "cp")]
+    pub corrected_price: Decimal,
+    #[serde(rename = "cs")]
+    pub corrected_size: Decimal,
+    #[serde(rename = "cc", default)]
+    pub corrected_conditions: Option<Vec<String>>,
+    #[serde(rename = "t")]
+    pub timestamp: DateTime<Utc>,
+    #[serde(rename = "z")]
+    pub tape: Tape,
+}
+
+/// Cancellation of, or error in, a previously reported trade.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlpacaStreamCancelError {
+    #[serde(rename = "S")]
+    pub symbol: String,
+    #[serde(rename = "i")]
+    pub id: i64,
+    #[serde(rename = "x")]
+    pub exchange: Exchange,
+    #[serde(rename = "p")]
+    pub price: Decimal,
+    #[serde(rename = "s")]
+    pub size: Decimal,
+    /// `"C"` for a cancel, `"E"` for an error.
+    #[serde(rename = "a")]
+    pub action: String,
+    #[serde(rename = "t")]
+    pub timestamp: DateTime<Utc>,
     #[serde(rename = "z")]
-    pub tape: String,
+    pub tape: Tape,
 }
 
+/// Trading status change for a symbol (halts, resumptions, etc).
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AlpacaStreamQuote {
+pub struct AlpacaStreamStatus {
     #[serde(rename = "S")]
     pub symbol: String,
-    #[serde(rename = "ap")]
-    pub ask_price: Decimal,
-    #[serde(rename = "as")]
-    pub ask_size: i32,
-    #[serde(rename = "ax")]
-    pub ask_exchange: String,
-    #[serde(rename = "bp")]
-    pub bid_price: Decimal,
-    #[serde(rename = "bs")]
-    pub bid_size: i32,
-    #[serde(rename = "bx")]
-    pub bid_exchange: String,
-    #[serde(rename = "c", default)]
-    pub conditions: Option<Vec<String>>,
+    #[serde(rename = "sc")]
+    pub status_code: String,
+    #[serde(rename = "sm")]
+    pub status_message: String,
+    #[serde(rename = "rc")]
+    pub reason_code: String,
+    #[serde(rename = "rm")]
+    pub reason_message: String,
     #[serde(rename = "t")]
     pub timestamp: DateTime<Utc>,
     #[serde(rename = "z")]
-    pub tape: String,
+    pub tape: Tape,
 }
 
+/// Limit Up-Limit Down price band update.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AlpacaStreamBar {
+pub struct AlpacaStreamLuld {
     #[serde(rename = "S")]
     pub symbol: String,
-    #[serde(rename = "o")]
-    pub open: Decimal,
-    #[serde(rename = "h")]
-    pub high: Decimal,
-    #[serde(rename = "l")]
-    pub low: Decimal,
-    #[serde(rename = "c")]
-    pub close: Decimal,
-    #[serde(rename = "v")]
-    pub volume: i64,
+    #[serde(rename = "u")]
+    pub limit_up_price: Decimal,
+    #[serde(rename = "d")]
+    pub limit_down_price: Decimal,
+    #[serde(rename = "i")]
+    pub indicator: String,
     #[serde(rename = "t")]
     pub timestamp: DateTime<Utc>,
+    #[serde(rename = "z")]
+    pub tape: Tape,
+}
+
+/// News article from the news stream.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlpacaNewsArticle {
+    pub id: i64,
+    pub headline: String,
+    #[serde(default)]
+    pub summary: String,
+    #[serde(default)]
+    pub author: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub content: String,
+    #[serde(default)]
+    pub symbols: Vec<String>,
+    pub source: String,
 }
 
 // ── Trade Updates (Account Stream) ───────────────────────────────────
@@ -384,6 +1768,45 @@ pub struct AlpacaTradeUpdate {
     pub qty: Option<String>,
 }
 
+/// Serde helpers for calendar times sent as `"HH:MM"`.
+mod eastern_time {
+    use chrono::NaiveTime;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &NaiveTime, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&value.format("%H:%M").to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<NaiveTime, D::Error> {
+        let raw = String::deserialize(d)?;
+        NaiveTime::parse_from_str(&raw, "%H:%M")
+            .or_else(|_| NaiveTime::parse_from_str(&raw, "%H:%M:%S"))
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Serde helpers for nullable decimal strings, where Alpaca may send `null`,
+/// omit the field, or send `""` for "no value".
+mod optional_decimal {
+    use rust_decimal::Decimal;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &Option<Decimal>, s: S) -> Result<S::Ok, S::Error> {
+        value.serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Decimal>, D::Error> {
+        match Option::<String>::deserialize(d)? {
+            Some(raw) if !raw.trim().is_empty() => raw
+                .trim()
+                .parse()
+                .map(Some)
+                .map_err(serde::de::Error::custom),
+            _ => Ok(None),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -415,11 +1838,47 @@ mod tests {
         }"#;
         let account: AlpacaAccountResponse = serde_json::from_str(json).unwrap();
         assert_eq!(account.id, "abc-123");
+        let buying_power: Decimal = account.buying_power;
+        assert_eq!(buying_power, Decimal::new(10000000, 2));
+        assert_eq!(account.cash, Decimal::from(50000));
+        assert_eq!(account.equity, Decimal::from(75000));
+        assert_eq!(account.portfolio_value, Decimal::from(75000));
+        assert_eq!(account.multiplier, Decimal::from(4));
         assert_eq!(account.daytrade_count, 2);
         assert!(!account.pattern_day_trader);
         assert!(account.shorting_enabled);
         assert!(account.sma.is_none());
         assert!(account.crypto_status.is_none());
+        assert_eq!(account.options_trading_level, None);
+        assert_eq!(account.options_level(), 0);
+        assert!(!account.can_trade_options(1));
+        assert_eq!(account.day_trades_remaining(), None);
+    }
+
+    #[test]
+    fn deserialize_account_options_levels() {
+        let json = r#"{
+            "id": "abc-123", "account_number": "PA123", "status": "ACTIVE",
+            "currency": "USD", "buying_power": "20000", "cash": "20000",
+            "portfolio_value": "20000", "equity": "20000", "last_equity": "20000",
+            "long_market_value": "0", "short_market_value": "0",
+            "initial_margin": "0", "maintenance_margin": "0",
+            "daytrade_count": 2, "pattern_day_trader": false,
+            "trading_blocked": false, "transfers_blocked": false,
+            "account_blocked": false, "shorting_enabled": false,
+            "multiplier": "1", "created_at": "2024-01-15T10:30:00Z",
+            "crypto_status": "ACTIVE",
+            "options_trading_level": 2, "options_approved_level": 3,
+            "options_buying_power": "10000.50"
+        }"#;
+        let account: AlpacaAccountResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(account.options_trading_level, Some(2));
+        assert_eq!(account.options_approved_level, Some(3));
+        assert_eq!(account.options_buying_power, Some(Decimal::new(1000050, 2)));
+        assert!(account.can_trade_options(2));
+        assert!(!account.can_trade_options(3));
+        assert!(!account.can_trade_options(0));
+        assert_eq!(account.day_trades_remaining(), Some(1));
     }
 
     #[test]
@@ -450,13 +1909,44 @@ mod tests {
             order_type: "market".to_string(),
             time_in_force: "day".to_string(),
             limit_price: None,
+            stop_price: None,
+            trail_percent: None,
+            trail_price: None,
             extended_hours: false,
+            client_order_id: None,
+            order_class: None,
+            position_intent: None,
         };
         let json = serde_json::to_value(&req).unwrap();
         assert_eq!(json["symbol"], "TSLA");
         assert_eq!(json["qty"], 5);
         assert_eq!(json["type"], "market");
         assert!(json.get("limit_price").is_none());
+        assert!(json.get("client_order_id").is_none());
+        assert!(json.get("order_class").is_none());
+        assert!(json.get("position_intent").is_none());
+    }
+
+    #[test]
+    fn serialize_order_request_with_position_intent() {
+        let req = AlpacaOrderRequest {
+            symbol: "AAPL240621C00190000".to_string(),
+            qty: 2,
+            side: "sell".to_string(),
+            order_type: "market".to_string(),
+            time_in_force: "day".to_string(),
+            limit_price: None,
+            stop_price: None,
+            trail_percent: None,
+            trail_price: None,
+            extended_hours: false,
+            client_order_id: None,
+            order_class: Some("simple".to_string()),
+            position_intent: Some("sell_to_close".to_string()),
+        };
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(json["position_intent"], "sell_to_close");
+        assert_eq!(json["order_class"], "simple");
     }
 
     #[test]
@@ -468,13 +1958,106 @@ mod tests {
             order_type: "limit".to_string(),
             time_in_force: "gtc".to_string(),
             limit_price: Some(Decimal::new(15050, 2)),
+            stop_price: None,
+            trail_percent: None,
+            trail_price: None,
             extended_hours: true,
+            client_order_id: Some("my-order-1".to_string()),
+            order_class: None,
+            position_intent: None,
         };
         let json = serde_json::to_value(&req).unwrap();
         assert_eq!(json["limit_price"], "150.50");
+        assert_eq!(json["client_order_id"], "my-order-1");
         assert_eq!(json["extended_hours"], true);
     }
 
+    #[test]
+    fn order_builder_market_order() {
+        let json = serde_json::to_value(OrderBuilder::buy("AAPL", 10).build()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "symbol": "AAPL",
+                "qty": 10,
+                "side": "buy",
+                "type": "market",
+                "time_in_force": "day",
+                "extended_hours": false
+            })
+        );
+    }
+
+    #[test]
+    fn order_builder_limit_order() {
+        let order = OrderBuilder::sell("AAPL", 5)
+            .limit(Decimal::new(18950, 2))
+            .gtc()
+            .extended_hours()
+            .client_order_id("exit-1")
+            .build();
+        let json = serde_json::to_value(&order).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "symbol": "AAPL",
+                "qty": 5,
+                "side": "sell",
+                "type": "limit",
+                "time_in_force": "gtc",
+                "limit_price": "189.50",
+                "extended_hours": true,
+                "client_order_id": "exit-1"
+            })
+        );
+    }
+
+    #[test]
+    fn order_builder_stop_and_trailing_types() {
+        let stop = OrderBuilder::sell("AAPL", 1)
+            .stop(Decimal::from(180))
+            .build();
+        assert_eq!(stop.order_type, "stop");
+        assert_eq!(stop.stop_price, Some(Decimal::from(180)));
+
+        let stop_limit = OrderBuilder::sell("AAPL", 1)
+            .stop(Decimal::from(180))
+            .limit(Decimal::from(179))
+            .build();
+        assert_eq!(stop_limit.order_type, "stop_limit");
+
+        let trailing = OrderBuilder::sell("AAPL", 1)
+            .trailing_percent(Decimal::from(5))
+            .build();
+        let json = serde_json::to_value(&trailing).unwrap();
+        assert_eq!(json["type"], "trailing_stop");
+        assert_eq!(json["trail_percent"], "5");
+        assert!(json.get("trail_price").is_none());
+    }
+
+    #[test]
+    fn order_builder_clears_prices_when_switching_type() {
+        let trailing = OrderBuilder::sell("AAPL", 1)
+            .limit(Decimal::from(179))
+            .stop(Decimal::from(180))
+            .trailing_price(Decimal::from(2))
+            .build();
+        assert_eq!(trailing.order_type, "trailing_stop");
+        assert_eq!(trailing.trail_price, Some(Decimal::from(2)));
+        assert_eq!(trailing.limit_price, None);
+        assert_eq!(trailing.stop_price, None);
+
+        let stop = OrderBuilder::sell("AAPL", 1)
+            .trailing_percent(Decimal::from(5))
+            .stop(Decimal::from(180))
+            .build();
+        assert_eq!(stop.order_type, "stop");
+        assert_eq!(stop.trail_percent, None);
+        let json = serde_json::to_value(&stop).unwrap();
+        assert!(json.get("trail_percent").is_none());
+        assert!(json.get("limit_price").is_none());
+    }
+
     #[test]
     fn serialize_replace_order_request() {
         let req = AlpacaReplaceOrderRequest {
@@ -502,8 +2085,75 @@ mod tests {
         }"#;
         let pos: AlpacaPositionResponse = serde_json::from_str(json).unwrap();
         assert_eq!(pos.symbol, "SPY");
-        assert_eq!(pos.qty, "100");
+        assert_eq!(pos.qty, Decimal::from(100));
+        assert_eq!(pos.avg_entry_price, Decimal::new(45025, 2));
         assert_eq!(pos.side, "long");
+        assert!(pos.unrealized_pl.is_none());
+    }
+
+    #[test]
+    fn deserialize_realistic_account_and_position() {
+        let json = r#"{
+            "id": "e6fe16f3-64a4-4921-8928-cadf02f92f98",
+            "account_number": "PA3QR8XVZ2LD",
+            "status": "ACTIVE",
+            "currency": "USD",
+            "buying_power": "262113.632",
+            "cash": "-23140.2",
+            "portfolio_value": "103820.56",
+            "equity": "103820.56",
+            "last_equity": "103529.24",
+            "long_market_value": "126960.76",
+            "short_market_value": "0",
+            "initial_margin": "63480.38",
+            "maintenance_margin": "38088.228",
+            "daytrade_count": 0,
+            "pattern_day_trader": false,
+            "trading_blocked": false,
+            "transfers_blocked": false,
+            "account_blocked": false,
+            "shorting_enabled": true,
+            "multiplier": "4",
+            "created_at": "2019-06-12T22:47:07.99658Z",
+            "sma": "",
+            "crypto_status": "ACTIVE"
+        }"#;
+        let account: AlpacaAccountResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            account.buying_power,
+            "262113.632".parse::<Decimal>().unwrap()
+        );
+        assert_eq!(account.cash, "-23140.2".parse::<Decimal>().unwrap());
+        assert!(account.sma.is_none());
+
+        let json = r#"{
+            "asset_id": "904837e3-3b76-47ec-b432-046db621571b",
+            "symbol": "AAPL",
+            "exchange": "NASDAQ",
+            "asset_class": "us_equity",
+            "qty": "2.5",
+            "avg_entry_price": "100.0",
+            "side": "long",
+            "market_value": "600.0",
+            "cost_basis": "250.0",
+            "unrealized_pl": "350.0",
+            "unrealized_plpc": "1.4",
+            "unrealized_intraday_pl": "10.0",
+            "unrealized_intraday_plpc": "0.0169",
+            "current_price": "240.0",
+            "lastday_price": "236.0",
+            "change_today": "0.0169",
+            "qty_available": ""
+        }"#;
+        let pos: AlpacaPositionResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(pos.qty, Decimal::new(25, 1));
+        assert_eq!(pos.unrealized_pl, Some(Decimal::new(3500, 1)));
+        assert_eq!(pos.current_price, Some(Decimal::from(240)));
+        assert!(pos.qty_available.is_none());
+        assert_eq!(pos.qty_available_decimal(), Decimal::new(25, 1));
+
+        let round_trip = serde_json::to_value(&pos).unwrap();
+        assert_eq!(round_trip["market_value"], "600.0");
     }
 
     #[test]
@@ -537,9 +2187,39 @@ mod tests {
         }"#;
         let day: AlpacaCalendarDay = serde_json::from_str(json).unwrap();
         assert_eq!(day.date, NaiveDate::from_ymd_opt(2024, 6, 15).unwrap());
-        assert_eq!(day.open, "09:30");
-        assert_eq!(day.close, "16:00");
+        assert_eq!(day.open, NaiveTime::from_hms_opt(9, 30, 0).unwrap());
+        assert_eq!(day.close, NaiveTime::from_hms_opt(16, 0, 0).unwrap());
         assert!(day.session_open.is_none());
+        assert_eq!(serde_json::to_value(&day).unwrap()["open"], "09:30");
+    }
+
+    #[test]
+    fn calendar_session_bounds_in_utc() {
+        use chrono::TimeZone;
+
+        // Summer: EDT is UTC-4.
+        let day: AlpacaCalendarDay =
+            serde_json::from_str(r#"{"date": "2024-06-03", "open": "09:30", "close": "16:00"}"#)
+                .unwrap();
+        assert_eq!(
+            day.session_bounds(),
+            (
+                Utc.with_ymd_and_hms(2024, 6, 3, 13, 30, 0).unwrap(),
+                Utc.with_ymd_and_hms(2024, 6, 3, 20, 0, 0).unwrap(),
+            )
+        );
+
+        // Day after Thanksgiving closes early; EST is UTC-5.
+        let half_day: AlpacaCalendarDay =
+            serde_json::from_str(r#"{"date": "2024-11-29", "open": "09:30", "close": "13:00"}"#)
+                .unwrap();
+        assert_eq!(
+            half_day.session_bounds(),
+            (
+                Utc.with_ymd_and_hms(2024, 11, 29, 14, 30, 0).unwrap(),
+                Utc.with_ymd_and_hms(2024, 11, 29, 18, 0, 0).unwrap(),
+            )
+        );
     }
 
     #[test]
@@ -557,7 +2237,44 @@ mod tests {
         let quote: AlpacaQuote = serde_json::from_str(json).unwrap();
         assert_eq!(quote.ask_price, Decimal::new(15100, 2));
         assert_eq!(quote.bid_price, Decimal::new(15098, 2));
-        assert_eq!(quote.tape, "C");
+        assert_eq!(quote.tape, Some(Tape::C));
+        assert_eq!(quote.ask_exchange, Some(Exchange::Nasdaq));
+    }
+
+    #[test]
+    fn parse_tape_and_exchange_codes() {
+        for (code, tape) in [
+            ("A", Tape::A),
+            ("B", Tape::B),
+            ("C", Tape::C),
+            ("O", Tape::Otc),
+        ] {
+            let parsed: Tape = code.parse().unwrap();
+            assert_eq!(parsed, tape);
+            assert_eq!(parsed.to_string(), code);
+        }
+        let odd: Tape = "Q".parse().unwrap();
+        assert_eq!(odd, Tape::Unknown("Q".into()));
+        assert_eq!(odd.to_string(), "Q");
+        assert_eq!(odd.name(), "Unknown");
+
+        assert_eq!("V".parse::<Exchange>().unwrap(), Exchange::Iex);
+        assert_eq!(Exchange::Nyse.name(), "New York Stock Exchange");
+        assert_eq!(
+            "?".parse::<Exchange>().unwrap(),
+            Exchange::Unknown("?".into())
+        );
+
+        // Unexpected codes survive a round trip instead of failing the message.
+        let trade: AlpacaTrade = serde_json::from_str(
+            r#"{"t":"2024-06-03T15:00:00Z","p":190.1,"s":100,"x":"9","i":1,"z":"E"}"#,
+        )
+        .unwrap();
+        assert_eq!(trade.exchange, Some(Exchange::Unknown("9".into())));
+        assert_eq!(trade.tape, Some(Tape::Unknown("E".into())));
+        let json = serde_json::to_value(&trade).unwrap();
+        assert_eq!(json["x"], "9");
+        assert_eq!(json["z"], "E");
     }
 
     #[test]
@@ -572,7 +2289,7 @@ mod tests {
         }"#;
         let trade: AlpacaTrade = serde_json::from_str(json).unwrap();
         assert_eq!(trade.price, Decimal::new(15050, 2));
-        assert_eq!(trade.size, 100);
+        assert_eq!(trade.size, Decimal::from(100));
         assert_eq!(trade.id, 12345);
     }
 
@@ -656,6 +2373,9 @@ mod tests {
             }
         }"#;
         let snap: AlpacaSnapshot = serde_json::from_str(json).unwrap();
+        assert_eq!(snap.mid_price(), Some(Decimal::new(15099, 2)));
+        assert_eq!(snap.last_price(), Some(Decimal::new(15050, 2)));
+        assert_eq!(snap.spread(), Some(Decimal::new(2, 2)));
         assert!(snap.latest_trade.is_some());
         assert!(snap.latest_quote.is_some());
         assert!(snap.minute_bar.is_some());
@@ -709,15 +2429,35 @@ mod tests {
                 trades,
                 quotes,
                 bars,
+                updated_bars,
+                daily_bars,
             } => {
                 assert_eq!(trades.unwrap(), vec!["AAPL"]);
                 assert_eq!(quotes.unwrap(), vec!["AAPL", "TSLA"]);
                 assert!(bars.unwrap().is_empty());
+                assert!(updated_bars.is_none());
+                assert!(daily_bars.is_none());
             }
             _ => panic!("expected Subscription"),
         }
     }
 
+    #[test]
+    fn deserialize_stream_subscription_with_bar_channels() {
+        let json = r#"{"T":"subscription","trades":[],"quotes":[],"bars":["SPY"],"updatedBars":["SPY"],"dailyBars":["SPY","QQQ"]}"#;
+        match serde_json::from_str(json).unwrap() {
+            AlpacaStreamMessage::Subscription {
+                updated_bars,
+                daily_bars,
+                ..
+            } => {
+                assert_eq!(updated_bars.unwrap(), vec!["SPY"]);
+                assert_eq!(daily_bars.unwrap(), vec!["SPY", "QQQ"]);
+            }
+            other => panic!("expected Subscription, got {other:?}"),
+        }
+    }
+
     #[test]
     fn deserialize_stream_trade() {
         let json = r#"{
@@ -785,6 +2525,133 @@ mod tests {
         }
     }
 
+    #[test]
+    fn deserialize_stream_daily_and_updated_bars() {
+        let daily = r#"{"T":"d","S":"SPY","o":"450.00","h":"455.00","l":"449.00","c":"454.00","v":90000000,"t":"2024-06-01T20:00:00Z","n":1000,"vw":"452.10"}"#;
+        match serde_json::from_str(daily).unwrap() {
+            AlpacaStreamMessage::DailyBar(b) => {
+                assert_eq!(b.symbol, "SPY");
+                assert_eq!(b.trade_count, Some(1000));
+                assert_eq!(b.vwap, Some(Decimal::new(45210, 2)));
+            }
+            other => panic!("expected DailyBar, got {other:?}"),
+        }
+
+        let updated = r#"{"T":"u","S":"SPY","o":"450.00","h":"451.00","l":"449.50","c":"450.75","v":100500,"t":"2024-06-01T14:30:00Z"}"#;
+        match serde_json::from_str(updated).unwrap() {
+            AlpacaStreamMessage::UpdatedBar(b) => assert_eq!(b.volume, 100500),
+            other => panic!("expected UpdatedBar, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn deserialize_stream_correction_and_cancel() {
+        let correction = r#"{"T":"c","S":"AAPL","x":"V","oi":1,"op":"150.50","os":100,"oc":["@"],"ci":2,"cp":"150.55","cs":100,"cc":["@"],"t":"2024-06-01T14:30:00Z","z":"C"}"#;
+        match serde_json::from_str(correction).unwrap() {
+            AlpacaStreamMessage::Correction(c) => {
+                assert_eq!(c.original_id, 1);
+                assert_eq!(c.corrected_price, Decimal::new(15055, 2));
+            }
+            other => panic!("expected Correction, got {other:?}"),
+        }
+
+        let cancel = r#"{"T":"x","S":"AAPL","i":1,"x":"V","p":"150.50","s":100,"a":"C","t":"2024-06-01T14:30:00Z","z":"C"}"#;
+        match serde_json::from_str(cancel).unwrap() {
+            AlpacaStreamMessage::CancelError(x) => assert_eq!(x.action, "C"),
+            other => panic!("expected CancelError, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn deserialize_stream_status() {
+        let json = r#"{
+            "T": "s",
+            "S": "AAPL",
+            "sc": "H",
+            "sm": "Trading Halt",
+            "rc": "T12",
+            "rm": "Trading Halted; For information requested by NASDAQ",
+            "t": "2024-06-01T14:30:00Z",
+            "z": "C"
+        }"#;
+        match serde_json::from_str(json).unwrap() {
+            AlpacaStreamMessage::Status(s) => {
+                assert_eq!(s.symbol, "AAPL");
+                assert_eq!(s.status_code, "H");
+                assert_eq!(s.reason_code, "T12");
+            }
+            other => panic!("expected Status, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn deserialize_stream_luld() {
+        let json = r#"{"T":"l","S":"AAPL","u":"157.00","d":"143.00","i":"B","t":"2024-06-01T14:30:00Z","z":"C"}"#;
+        match serde_json::from_str(json).unwrap() {
+            AlpacaStreamMessage::Luld(l) => {
+                assert_eq!(l.limit_up_price, Decimal::new(157, 0));
+                assert_eq!(l.limit_down_price, Decimal::new(143, 0));
+            }
+            other => panic!("expected Luld, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn deserialize_stream_news() {
+        let json = r#"{
+            "T": "n",
+            "id": 24918784,
+            "headline": "Apple Announces New Product",
+            "summary": "Apple unveiled a new device today.",
+            "author": "Benzinga Newsdesk",
+            "created_at": "2024-06-01T14:30:00Z",
+            "updated_at": "2024-06-01T14:31:00Z",
+            "url": "https://www.benzinga.com/news/24918784",
+            "content": "",
+            "symbols": ["AAPL"],
+            "source": "benzinga"
+        }"#;
+        match serde_json::from_str(json).unwrap() {
+            AlpacaStreamMessage::News(n) => {
+                assert_eq!(n.id, 24918784);
+                assert_eq!(n.headline, "Apple Announces New Product");
+                assert_eq!(n.symbols, vec!["AAPL"]);
+                assert_eq!(n.source, "benzinga");
+            }
+            other => panic!("expected News, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn deserialize_stream_heartbeat() {
+        let json = r#"{"T": "heartbeat", "t": "2024-06-03T13:30:00Z"}"#;
+        match serde_json::from_str(json).unwrap() {
+            AlpacaStreamMessage::Heartbeat(control) => {
+                assert_eq!(
+                    control.timestamp,
+                    Some("2024-06-03T13:30:00Z".parse().unwrap())
+                );
+                assert_eq!(control.msg, None);
+            }
+            other => panic!("expected Heartbeat, got {other:?}"),
+        }
+        let bare: AlpacaStreamMessage = serde_json::from_str(r#"{"T":"heartbeat"}"#).unwrap();
+        assert!(matches!(bare, AlpacaStreamMessage::Heartbeat(_)));
+        assert!(bare.market_event().is_none());
+    }
+
+    #[test]
+    fn deserialize_stream_unknown_type() {
+        let json = r#"{"T": "zz", "S": "AAPL", "foo": 1}"#;
+        match serde_json::from_str(json).unwrap() {
+            AlpacaStreamMessage::Unknown(value) => {
+                assert_eq!(value["T"], "zz");
+                assert_eq!(value["foo"], 1);
+            }
+            other => panic!("expected Unknown, got {other:?}"),
+        }
+    }
+
     #[test]
     fn deserialize_trade_update() {
         let json = r#"{
@@ -824,4 +2691,332 @@ mod tests {
         assert_eq!(parsed.close, bar.close);
         assert_eq!(parsed.volume, bar.volume);
     }
+
+    fn position(json: &str) -> AlpacaPositionResponse {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn position_pl_helpers() {
+        let long = position(
+            r#"{"asset_id":"a","symbol":"AAPL","exchange":"NASDAQ","asset_class":"us_equity","qty":"10","avg_entry_price":"100","side":"long","cost_basis":"1000","market_value":"1250.50","unrealized_pl":"250.50"}"#,
+        );
+        let short = position(
+            r#"{"asset_id":"b","symbol":"TSLA","exchange":"NASDAQ","asset_class":"us_equity","qty":"-5","avg_entry_price":"200","side":"short","cost_basis":"-1000","market_value":"-1100","unrealized_pl":"-100"}"#,
+        );
+        let pending = position(
+            r#"{"asset_id":"c","symbol":"SPY","exchange":"ARCA","asset_class":"us_equity","qty":"1","avg_entry_price":"450","side":"long","cost_basis":"450"}"#,
+        );
+
+        assert_eq!(long.unrealized_pl_decimal(), Decimal::new(25050, 2));
+        assert_eq!(short.market_value_decimal(), Decimal::from(-1100));
+        assert_eq!(pending.unrealized_pl_decimal(), Decimal::ZERO);
+        assert_eq!(pending.market_value_decimal(), Decimal::ZERO);
+
+        assert_eq!(
+            total_unrealized_pl(&[long, short, pending]),
+            Decimal::new(15050, 2)
+        );
+        assert_eq!(total_unrealized_pl(&[]), Decimal::ZERO);
+    }
+
+    #[test]
+    fn deserialize_cancel_all_statuses() {
+        let json = r#"[
+            {"id": "order-1", "status": 200, "body": {"id": "order-1", "status": "pending_cancel"}},
+            {"id": "order-2", "status": 500}
+        ]"#;
+        let statuses: Vec<AlpacaCancelOrderStatus> = serde_json::from_str(json).unwrap();
+        assert_eq!(statuses.len(), 2);
+        assert_eq!(statuses[0].status, 200);
+        assert!(statuses[1].body.is_none());
+    }
+
+    #[test]
+    fn parse_valid_timeframes() {
+        for (input, expected) in [
+            ("1Min", TimeFrame::MIN_1),
+            ("5Min", TimeFrame::MIN_5),
+            ("15Min", TimeFrame::MIN_15),
+            ("30Min", TimeFrame::MIN_30),
+            ("1Hour", TimeFrame::HOUR_1),
+            ("4Hour", TimeFrame::HOUR_4),
+            ("1Day", TimeFrame::DAY_1),
+            ("1Week", TimeFrame::WEEK_1),
+            ("1Month", TimeFrame::MONTH_1),
+            ("45Min", TimeFrame::Minute(45)),
+            ("12Hour", TimeFrame::Hour(12)),
+            ("3Month", TimeFrame::Month(3)),
+            ("1min", TimeFrame::MIN_1),
+        ] {
+            let parsed: TimeFrame = input.parse().unwrap();
+            assert_eq!(parsed, expected, "{input}");
+        }
+        assert_eq!(TimeFrame::Minute(15).to_string(), "15Min");
+        assert_eq!(TimeFrame::Day.to_string(), "1Day");
+    }
+
+    #[test]
+    fn reject_invalid_timeframes() {
+        for input in [
+            "", "Min", "0Min", "60Min", "24Hour", "2Day", "2Week", "5Month", "1Sec", "5 Min",
+        ] {
+            assert!(input.parse::<TimeFrame>().is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn time_bound_formats_dates_and_instants() {
+        let date = NaiveDate::from_ymd_opt(2024, 6, 3).unwrap();
+        assert_eq!(TimeBound::from(date).to_string(), "2024-06-03");
+
+        let open = date.and_hms_opt(13, 30, 0).unwrap().and_utc();
+        assert_eq!(TimeBound::from(open).to_string(), "2024-06-03T13:30:00Z");
+
+        let precise = date.and_hms_milli_opt(13, 30, 0, 250).unwrap().and_utc();
+        assert_eq!(
+            TimeBound::from(precise).to_string(),
+            "2024-06-03T13:30:00.250Z"
+        );
+        assert_eq!(TimeBound::from(precise).date(), date);
+    }
+
+    #[test]
+    fn deserialize_movers() {
+        let json = r#"{
+            "gainers": [{"symbol":"ABCD","percent_change":48.25,"change":1.93,"price":5.93}],
+            "losers": [{"symbol":"WXYZ","percent_change":-31.5,"change":-0.63,"price":1.37}],
+            "market_type": "stocks",
+            "last_updated": "2024-06-03T19:59:00.123Z"
+        }"#;
+        let movers: AlpacaMoversResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(movers.gainers.len(), 1);
+        assert_eq!(movers.gainers[0].symbol, "ABCD");
+        assert_eq!(movers.gainers[0].price, Decimal::new(593, 2));
+        assert_eq!(movers.gainers[0].percent_change, Decimal::new(4825, 2));
+        assert_eq!(movers.losers[0].change, Decimal::new(-63, 2));
+        assert_eq!(movers.market_type.as_deref(), Some("stocks"));
+        assert!(movers.last_updated.is_some());
+    }
+
+    #[test]
+    fn deserialize_most_actives() {
+        let json = r#"{
+            "most_actives": [
+                {"symbol":"NVDA","volume":412345678,"trade_count":3456789},
+                {"symbol":"TSLA","volume":98765432,"trade_count":1234567}
+            ],
+            "last_updated": "2024-06-03T19:59:00Z"
+        }"#;
+        let actives: AlpacaMostActivesResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(actives.most_actives.len(), 2);
+        assert_eq!(actives.most_actives[0].symbol, "NVDA");
+        assert_eq!(actives.most_actives[0].volume, 412345678);
+        assert_eq!(actives.most_actives[1].trade_count, 1234567);
+
+        let empty: AlpacaMostActivesResponse =
+            serde_json::from_str(r#"{"most_actives":null}"#).unwrap();
+        assert!(empty.most_actives.is_empty());
+    }
+
+    #[test]
+    fn snapshot_accessors_without_quote() {
+        let json = r#"{
+            "latestTrade": {"t":"2024-06-01T14:30:00Z","p":"150.50","s":100,"x":"V","i":1,"z":"C"},
+            "latestQuote": null
+        }"#;
+        let snap: AlpacaSnapshot = serde_json::from_str(json).unwrap();
+        assert_eq!(snap.mid_price(), None);
+        assert_eq!(snap.spread(), None);
+        assert_eq!(snap.last_price(), Some(Decimal::new(15050, 2)));
+
+        let empty: AlpacaSnapshot = serde_json::from_str("{}").unwrap();
+        assert_eq!(empty.last_price(), None);
+    }
+
+    #[test]
+    fn one_sided_quote_has_no_mid() {
+        let json = r#"{"ap":"0","as":0,"ax":"","bp":"150.98","bs":100,"bx":"Q","t":"2024-06-01T23:30:00Z","z":"C"}"#;
+        let quote: AlpacaQuote = serde_json::from_str(json).unwrap();
+        assert_eq!(quote.mid_price(), None);
+        assert_eq!(quote.spread(), None);
+    }
+
+    #[test]
+    fn deserialize_account_activities() {
+        let json = r#"[
+            {"id":"20240603093000000::8efc7b9a","activity_type":"FILL","transaction_time":"2024-06-03T13:30:00.123Z","type":"fill","price":"150.25","qty":"10","side":"buy","symbol":"AAPL","leaves_qty":"0","order_id":"ord-1","cum_qty":"10"},
+            {"id":"20240601000000000::045b3b8d","activity_type":"DIV","date":"2024-06-01","net_amount":"12.34","symbol":"AAPL","qty":"10","per_share_amount":"1.234"}
+        ]"#;
+        let activities: Vec<AlpacaAccountActivity> = serde_json::from_str(json).unwrap();
+        assert_eq!(activities[0].activity_type, "FILL");
+        assert_eq!(activities[0].price, Some(Decimal::new(15025, 2)));
+        assert!(activities[0].transaction_time.is_some());
+        assert_eq!(activities[1].net_amount, Some(Decimal::new(1234, 2)));
+        assert_eq!(activities[1].date, NaiveDate::from_ymd_opt(2024, 6, 1));
+        assert_eq!(activities[1].price, None);
+    }
+
+    #[test]
+    fn serialize_vertical_spread() {
+        let leg = |symbol: &str, side: &str, intent: &str| OptionLeg {
+            symbol: symbol.to_string(),
+            ratio_qty: 1,
+            side: side.to_string(),
+            position_intent: Some(intent.to_string()),
+        };
+        let req = AlpacaMultilegOrderRequest {
+            order_class: "mleg".to_string(),
+            qty: 1,
+            order_type: "limit".to_string(),
+            time_in_force: "day".to_string(),
+            limit_price: Some(Decimal::new(150, 2)),
+            legs: vec![
+                leg("AAPL240621C00190000", "buy", "buy_to_open"),
+                leg("AAPL240621C00195000", "sell", "sell_to_open"),
+            ],
+        };
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "order_class": "mleg",
+                "qty": 1,
+                "type": "limit",
+                "time_in_force": "day",
+                "limit_price": "1.50",
+                "legs": [
+                    {"symbol": "AAPL240621C00190000", "ratio_qty": 1, "side": "buy",
+                     "position_intent": "buy_to_open"},
+                    {"symbol": "AAPL240621C00195000", "ratio_qty": 1, "side": "sell",
+                     "position_intent": "sell_to_open"}
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn deserialize_fractional_crypto_sizes() {
+        let trade: AlpacaTrade = serde_json::from_str(
+            r#"{"t":"2024-06-03T15:00:00Z","p":67250.5,"s":0.0015,"tks":"B","i":9001}"#,
+        )
+        .unwrap();
+        assert_eq!(trade.size, Decimal::new(15, 4));
+        assert!(trade.exchange.is_none());
+
+        let msg: AlpacaStreamMessage = serde_json::from_str(
+            r#"{"T":"t","S":"BTC/USD","p":67250.5,"s":0.0015,"t":"2024-06-03T15:00:00Z","i":9001,"tks":"S"}"#,
+        )
+        .unwrap();
+        match msg {
+            AlpacaStreamMessage::Trade(trade) => assert_eq!(trade.size, Decimal::new(15, 4)),
+            other => panic!("expected Trade, got {other:?}"),
+        }
+
+        // Equity sizes stay whole numbers.
+        let trade: AlpacaTrade = serde_json::from_str(
+            r#"{"t":"2024-06-03T15:00:00Z","p":190.1,"s":100,"x":"V","i":1,"z":"C"}"#,
+        )
+        .unwrap();
+        assert_eq!(trade.size, Decimal::from(100));
+    }
+
+    #[test]
+    fn deserialize_sparse_quotes() {
+        let quote: AlpacaQuote = serde_json::from_str(
+            r#"{"ap":"151.02","as":2,"bp":"150.98","bs":1,"t":"2024-06-03T15:00:00Z","z":"C"}"#,
+        )
+        .unwrap();
+        assert!(quote.ask_exchange.is_none());
+        assert!(quote.bid_exchange.is_none());
+        assert!(quote.conditions.is_none());
+
+        let quote: AlpacaQuote = serde_json::from_str(
+            r#"{"ap":"151.02","as":2,"bp":"150.98","bs":1,"c":[],"t":"2024-06-03T15:00:00Z","z":"C"}"#,
+        )
+        .unwrap();
+        assert_eq!(quote.conditions, Some(vec![]));
+
+        let msg: AlpacaStreamMessage = serde_json::from_str(
+            r#"{"T":"q","S":"BTC/USD","ap":67251,"as":0.25,"bp":67249,"bs":0.4,"t":"2024-06-03T15:00:00Z"}"#,
+        )
+        .unwrap();
+        match msg {
+            AlpacaStreamMessage::Quote(quote) => {
+                assert!(quote.ask_exchange.is_none());
+                assert!(quote.tape.is_none());
+                assert!(quote.conditions.is_none());
+                assert_eq!(quote.bid_size, Decimal::new(4, 1));
+            }
+            other => panic!("expected Quote, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn deserialize_crypto_orderbook() {
+        let resp: AlpacaCryptoOrderbooksResponse = serde_json::from_str(
+            r#"{"orderbooks":{"BTC/USD":{
+                "a":[{"p":67251.2,"s":0.25},{"p":67252,"s":1.1},{"p":67260.5,"s":3}],
+                "b":[{"p":67249.8,"s":0.4},{"p":67249,"s":0.0015}],
+                "t":"2024-06-03T15:00:00.123Z"
+            }}}"#,
+        )
+        .unwrap();
+        let book = &resp.orderbooks["BTC/USD"];
+        assert_eq!(book.asks.len(), 3);
+        assert_eq!(book.bids.len(), 2);
+        assert_eq!(
+            book.asks[0],
+            AlpacaOrderbookLevel {
+                price: Decimal::new(672512, 1),
+                size: Decimal::new(25, 2),
+            }
+        );
+        assert_eq!(book.bids[1].size, Decimal::new(15, 4));
+        assert!(book.bids[0].price < book.asks[0].price);
+    }
+
+    #[test]
+    fn partially_filled_order_quantities() {
+        let order: AlpacaOrderResponse = serde_json::from_str(
+            r#"{"id":"ord-1","created_at":"2024-06-03T13:30:00Z","symbol":"AAPL",
+                "qty":"10","filled_qty":"3.5","filled_avg_price":"190.125",
+                "side":"buy","status":"partially_filled","extended_hours":false}"#,
+        )
+        .unwrap();
+        assert_eq!(order.filled_qty_decimal(), Decimal::new(35, 1));
+        assert_eq!(order.remaining_qty_decimal(), Decimal::new(65, 1));
+        assert_eq!(
+            order.filled_avg_price_decimal(),
+            Some(Decimal::new(190125, 3))
+        );
+        assert!(!order.is_terminal());
+
+        let fresh: AlpacaOrderResponse = serde_json::from_str(
+            r#"{"id":"ord-2","created_at":"2024-06-03T13:30:00Z","symbol":"AAPL",
+                "qty":"10","filled_qty":"0","filled_avg_price":null,
+                "side":"buy","status":"new","extended_hours":false}"#,
+        )
+        .unwrap();
+        assert_eq!(fresh.filled_qty_decimal(), Decimal::ZERO);
+        assert_eq!(fresh.remaining_qty_decimal(), Decimal::from(10));
+        assert_eq!(fresh.filled_avg_price_decimal(), None);
+    }
+
+    #[test]
+    fn qty_available_excludes_shares_held_by_orders() {
+        let position = |qty_available: &str| {
+            serde_json::from_str::<AlpacaPositionResponse>(&format!(
+                r#"{{"asset_id":"a-1","symbol":"AAPL","exchange":"NASDAQ",
+                    "asset_class":"us_equity","qty":"100","avg_entry_price":"190",
+                    "side":"long","cost_basis":"19000"{qty_available}}}"#
+            ))
+            .unwrap()
+        };
+        // 60 shares are tied up in an open sell order.
+        let pos = position(r#","qty_available":"40""#);
+        assert_eq!(pos.qty_available_decimal(), Decimal::from(40));
+        let pos = position("");
+        assert_eq!(pos.qty_available_decimal(), Decimal::from(100));
+    }
 }
@@ -19,4 +19,7 @@ pub enum ApiClientError {
 
     #[error("WebSocket error: {0}")]
     WebSocket(String),
+
+    #[error("WebSocket closed by server ({code}): {reason}")]
+    Closed { code: u16, reason: String },
 }
@@ -1,9 +1,21 @@
 pub mod client;
 pub mod error;
+pub mod observer;
 pub mod pagination;
+pub mod rate_limit;
+pub mod transport;
 pub mod websocket;
 
-pub use client::{RestClient, RestClientBuilder};
+pub use client::{encode_path_segment, push_query_param, ParseMode, RestClient, RestClientBuilder};
 pub use error::ApiClientError;
-pub use pagination::paginate;
-pub use websocket::WebSocketClient;
+pub use observer::{NoopObserver, RequestObserver};
+pub use pagination::{
+    paginate, paginate_by_last_id, paginate_limited, paginate_page, paginate_stream,
+};
+pub use rate_limit::RateLimiter;
+#[cfg(any(test, feature = "testing"))]
+pub use transport::MockTransport;
+pub use transport::{HttpRequest, HttpResponse, HttpTransport, ReqwestTransport};
+pub use websocket::{
+    ConnectOptions, Keepalive, OverflowPolicy, WebSocketClient, WebSocketConfig, WsFrame,
+};
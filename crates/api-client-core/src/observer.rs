@@ -0,0 +1,31 @@
+use std::time::Duration;
+
+use reqwest::Method;
+
+/// Hooks a [`RestClient`](crate::RestClient) calls around every request, e.g.
+/// to feed request counts, latency and error rates into a metrics system.
+///
+/// Both methods default to doing nothing. They run inline on the request
+/// path, so keep them cheap.
+pub trait RequestObserver: Send + Sync {
+    /// Called just before the request is handed to the transport (after any
+    /// rate-limiter wait).
+    fn on_request_start(&self, _method: &Method, _path: &str) {}
+
+    /// Called once the request finishes. `status` is `None` when no response
+    /// arrived (timeout, connection failure).
+    fn on_request_end(
+        &self,
+        _method: &Method,
+        _path: &str,
+        _status: Option<u16>,
+        _duration: Duration,
+    ) {
+    }
+}
+
+/// [`RequestObserver`] that ignores every request; the default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopObserver;
+
+impl RequestObserver for NoopObserver {}
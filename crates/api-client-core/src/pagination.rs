@@ -1,5 +1,7 @@
 use std::future::Future;
 
+use futures_util::stream::{self, Stream, StreamExt, TryStreamExt};
+
 use crate::error::ApiClientError;
 
 /// Generic pagination helper that collects all pages into a single Vec.
@@ -31,10 +33,214 @@ where
     Ok(all_items)
 }
 
+/// Paginate an id-cursor endpoint, where the next page is requested by
+/// passing the id of the last item received as the page token.
+///
+/// - `fetch_page`: async function taking the cursor (`None` for the first page)
+///   and returning that page's items
+/// - `id_of`: extracts the cursor id from an item
+///
+/// Stops after a page shorter than `page_size` (including an empty one),
+/// which signals that nothing follows.
+pub async fn paginate_by_last_id<T, F, Fut, I>(
+    fetch_page: F,
+    id_of: I,
+    page_size: usize,
+) -> Result<Vec<T>, ApiClientError>
+where
+    F: Fn(Option<String>) -> Fut,
+    Fut: Future<Output = Result<Vec<T>, ApiClientError>>,
+    I: Fn(&T) -> String,
+{
+    let mut all_items = Vec::new();
+    let mut page_token: Option<String> = None;
+
+    loop {
+        let items = fetch_page(page_token).await?;
+        let full_page = page_size > 0 && items.len() >= page_size;
+        page_token = items.last().map(&id_of);
+        all_items.extend(items);
+
+        if !full_page || page_token.is_none() {
+            break;
+        }
+    }
+
+    Ok(all_items)
+}
+
+/// Fetch exactly one page starting at `page_token`, returning its items and
+/// the token for the following page.
+///
+/// An empty next token is normalized to `None`. Persist the returned token to
+/// resume a long pull later by passing it back in.
+pub async fn paginate_page<T, F, Fut>(
+    fetch_page: F,
+    page_token: Option<String>,
+) -> Result<(Vec<T>, Option<String>), ApiClientError>
+where
+    F: FnOnce(Option<String>) -> Fut,
+    Fut: Future<Output = Result<(Vec<T>, Option<String>), ApiClientError>>,
+{
+    let (items, next_token) = fetch_page(page_token).await?;
+    Ok((items, next_token.filter(|token| !token.is_empty())))
+}
+
+/// Like [`paginate`], but stops once `max_items` have been collected.
+///
+/// The page that crosses the cap is truncated, and no further pages are
+/// requested. A cap of zero returns immediately without fetching.
+pub async fn paginate_limited<T, F, Fut>(
+    fetch_page: F,
+    max_items: usize,
+) -> Result<Vec<T>, ApiClientError>
+where
+    F: Fn(Option<String>) -> Fut,
+    Fut: Future<Output = Result<(Vec<T>, Option<String>), ApiClientError>>,
+{
+    let mut all_items = Vec::new();
+    let mut page_token: Option<String> = None;
+
+    while all_items.len() < max_items {
+        let (items, next_token) = fetch_page(page_token).await?;
+        all_items.extend(items);
+
+        match next_token {
+            Some(token) if !token.is_empty() => {
+                page_token = Some(token);
+            }
+            _ => break,
+        }
+    }
+
+    all_items.truncate(max_items);
+    Ok(all_items)
+}
+
+/// Lazy counterpart to [`paginate`] that yields items as each page arrives.
+///
+/// Pages are only fetched when the consumer polls past the end of the current
+/// page, so memory use is bounded by a single page rather than the full result
+/// set. An error from `fetch_page` is yielded once and ends the stream.
+pub fn paginate_stream<T, F, Fut>(fetch_page: F) -> impl Stream<Item = Result<T, ApiClientError>>
+where
+    F: Fn(Option<String>) -> Fut,
+    Fut: Future<Output = Result<(Vec<T>, Option<String>), ApiClientError>>,
+{
+    // `None` means pagination is finished; `Some(token)` is the next page to fetch.
+    let initial: Option<Option<String>> = Some(None);
+
+    stream::unfold((fetch_page, initial), |(fetch_page, next)| async move {
+        let page_token = next?;
+        match fetch_page(page_token).await {
+            Ok((items, next_token)) => {
+                let next = match next_token {
+                    Some(token) if !token.is_empty() => Some(Some(token)),
+                    _ => None,
+                };
+                Some((Ok(items), (fetch_page, next)))
+            }
+            Err(e) => Some((Err(e), (fetch_page, None))),
+        }
+    })
+    .map_ok(|items| stream::iter(items).map(Ok))
+    .try_flatten()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Ids 1..=5 served two at a time, keyed by the last id seen.
+    async fn activities(after: Option<String>) -> Result<Vec<u32>, ApiClientError> {
+        let start = after.map(|id| id.parse::<u32>().unwrap()).unwrap_or(0);
+        Ok(((start + 1)..=5).take(2).collect())
+    }
+
+    #[tokio::test]
+    async fn paginate_by_last_id_follows_item_cursor() {
+        let tokens = std::sync::Mutex::new(Vec::new());
+        let items = paginate_by_last_id(
+            |token| {
+                tokens.lock().unwrap().push(token.clone());
+                activities(token)
+            },
+            |id| id.to_string(),
+            2,
+        )
+        .await
+        .unwrap();
+        assert_eq!(items, vec![1, 2, 3, 4, 5]);
+        assert_eq!(
+            *tokens.lock().unwrap(),
+            vec![None, Some("2".to_string()), Some("4".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn paginate_by_last_id_stops_on_short_page() {
+        let calls = std::sync::atomic::AtomicU32::new(0);
+        let items = paginate_by_last_id(
+            |token| {
+                calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                activities(token)
+            },
+            |id| id.to_string(),
+            3,
+        )
+        .await
+        .unwrap();
+        // Pages of 2 are already short of 3, so only one request is made.
+        assert_eq!(items, vec![1, 2]);
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        // A full final page costs one extra (empty) request.
+        let items = paginate_by_last_id(
+            |token| async move {
+                let start = token.map(|t| t.parse::<u32>().unwrap()).unwrap_or(0);
+                Ok(((start + 1)..=4).take(2).collect::<Vec<u32>>())
+            },
+            |id| id.to_string(),
+            2,
+        )
+        .await
+        .unwrap();
+        assert_eq!(items, vec![1, 2, 3, 4]);
+    }
+
+    #[tokio::test]
+    async fn paginate_page_returns_next_token() {
+        let (items, next) = paginate_page(three_pages, None).await.unwrap();
+        assert_eq!(items, vec![1, 2]);
+        assert_eq!(next.as_deref(), Some("page2"));
+
+        let (items, next) = paginate_page(three_pages, Some("page3".into()))
+            .await
+            .unwrap();
+        assert_eq!(items, vec![5]);
+        assert_eq!(next, None);
+
+        let (_, next) = paginate_page(|_| async { Ok((vec![1], Some(String::new()))) }, None)
+            .await
+            .unwrap();
+        assert_eq!(next, None, "empty token means no more pages");
+    }
+
+    #[tokio::test]
+    async fn paginate_page_resumes_from_saved_token() {
+        let (_, saved) = paginate_page(three_pages, None).await.unwrap();
+
+        // Simulate a restart: resume from the persisted token.
+        let mut token = saved;
+        let mut resumed = Vec::new();
+        while let Some(t) = token {
+            let (items, next) = paginate_page(three_pages, Some(t)).await.unwrap();
+            resumed.extend(items);
+            token = next;
+        }
+        assert_eq!(resumed, vec![3, 4, 5]);
+    }
+
     #[tokio::test]
     async fn paginate_single_page() {
         let result = paginate(|_token| async { Ok((vec![1, 2, 3], None)) }).await;
@@ -90,4 +296,99 @@ mod tests {
         .await;
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn paginate_limited_stops_mid_page() {
+        let call_count = std::sync::Arc::new(std::sync::atomic::AtomicU32::new(0));
+        let cc = call_count.clone();
+
+        let result = paginate_limited(
+            move |token| {
+                cc.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                three_pages(token)
+            },
+            3,
+        )
+        .await;
+
+        assert_eq!(result.unwrap(), vec![1, 2, 3]);
+        assert_eq!(call_count.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn paginate_limited_cap_larger_than_data() {
+        let result = paginate_limited(three_pages, 100).await;
+        assert_eq!(result.unwrap(), vec![1, 2, 3, 4, 5]);
+    }
+
+    #[tokio::test]
+    async fn paginate_limited_zero_cap_fetches_nothing() {
+        let call_count = std::sync::Arc::new(std::sync::atomic::AtomicU32::new(0));
+        let cc = call_count.clone();
+
+        let result = paginate_limited(
+            move |token| {
+                cc.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                three_pages(token)
+            },
+            0,
+        )
+        .await;
+
+        assert!(result.unwrap().is_empty());
+        assert_eq!(call_count.load(std::sync::atomic::Ordering::SeqCst), 0);
+    }
+
+    async fn three_pages(
+        token: Option<String>,
+    ) -> Result<(Vec<i32>, Option<String>), ApiClientError> {
+        match token.as_deref() {
+            None => Ok((vec![1, 2], Some("page2".to_string()))),
+            Some("page2") => Ok((vec![3, 4], Some("page3".to_string()))),
+            Some("page3") => Ok((vec![5], None)),
+            Some(other) => panic!("unexpected token {other}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn paginate_stream_matches_eager() {
+        let eager = paginate(three_pages).await.unwrap();
+        let streamed: Vec<i32> = paginate_stream(three_pages).try_collect().await.unwrap();
+        assert_eq!(streamed, eager);
+        assert_eq!(streamed, vec![1, 2, 3, 4, 5]);
+    }
+
+    #[tokio::test]
+    async fn paginate_stream_is_lazy() {
+        let call_count = std::sync::Arc::new(std::sync::atomic::AtomicU32::new(0));
+        let cc = call_count.clone();
+
+        let stream = paginate_stream(move |token| {
+            cc.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            three_pages(token)
+        });
+        let first_two: Vec<_> = stream.take(2).collect().await;
+
+        assert_eq!(first_two.len(), 2);
+        assert_eq!(call_count.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn paginate_stream_error_ends_stream() {
+        let items: Vec<Result<i32, ApiClientError>> = paginate_stream(|token| async move {
+            match token {
+                None => Ok((vec![1], Some("page2".to_string()))),
+                Some(_) => Err(ApiClientError::Api {
+                    status: 500,
+                    body: "server error".to_string(),
+                }),
+            }
+        })
+        .collect()
+        .await;
+
+        assert_eq!(items.len(), 2);
+        assert!(matches!(items[0], Ok(1)));
+        assert!(items[1].is_err());
+    }
 }
@@ -0,0 +1,98 @@
+use std::time::Duration;
+
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+/// Token-bucket request limiter.
+///
+/// Holds up to `capacity` tokens, refilled evenly over `period`. Each request
+/// takes one token, waiting for a refill when the bucket is empty. Wrap it in
+/// an `Arc` to share one budget between several clients.
+#[derive(Debug)]
+pub struct RateLimiter {
+    capacity: f64,
+    refill_per_token: Duration,
+    state: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    /// Allow `requests` per `period`, starting with a full bucket.
+    pub fn new(requests: u32, period: Duration) -> Self {
+        let requests = requests.max(1);
+        Self {
+            capacity: requests as f64,
+            refill_per_token: period / requests,
+            state: Mutex::new(Bucket {
+                tokens: requests as f64,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    /// Allow `requests` per minute, e.g. Alpaca's 200 req/min trading limit.
+    pub fn per_minute(requests: u32) -> Self {
+        Self::new(requests, Duration::from_secs(60))
+    }
+
+    /// Wait until a request may be sent, then consume one token.
+    ///
+    /// Waiters are served in order: the lock is held while sleeping, so later
+    /// callers queue behind the one waiting for the next token.
+    pub async fn acquire(&self) {
+        let mut bucket = self.state.lock().await;
+        self.refill(&mut bucket);
+        if bucket.tokens < 1.0 {
+            let wait = self.refill_per_token.mul_f64(1.0 - bucket.tokens);
+            tokio::time::sleep(wait).await;
+            self.refill(&mut bucket);
+        }
+        bucket.tokens = (bucket.tokens - 1.0).max(0.0);
+    }
+
+    fn refill(&self, bucket: &mut Bucket) {
+        let now = Instant::now();
+        let earned = now.duration_since(bucket.last_refill).as_secs_f64()
+            / self.refill_per_token.as_secs_f64();
+        bucket.tokens = (bucket.tokens + earned).min(self.capacity);
+        bucket.last_refill = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn burst_up_to_capacity_is_immediate() {
+        let limiter = RateLimiter::new(5, Duration::from_secs(60));
+        let start = std::time::Instant::now();
+        for _ in 0..5 {
+            limiter.acquire().await;
+        }
+        assert!(start.elapsed() < Duration::from_millis(50));
+    }
+
+    #[tokio::test]
+    async fn caps_throughput_across_tasks() {
+        // 2 per 50ms: after the initial burst of 2, each token takes 25ms.
+        let limiter = Arc::new(RateLimiter::new(2, Duration::from_millis(50)));
+        let start = std::time::Instant::now();
+        let tasks: Vec<_> = (0..6)
+            .map(|_| {
+                let limiter = Arc::clone(&limiter);
+                tokio::spawn(async move { limiter.acquire().await })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+        assert!(start.elapsed() >= Duration::from_millis(95));
+    }
+}
@@ -0,0 +1,201 @@
+use std::time::Duration;
+
+use futures_util::future::BoxFuture;
+use reqwest::header::HeaderMap;
+use reqwest::Method;
+
+use crate::error::ApiClientError;
+
+/// A request as handed to an [`HttpTransport`].
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    pub method: Method,
+    /// Absolute URL; `query` holds any parameters added separately.
+    pub url: String,
+    pub headers: HeaderMap,
+    pub query: Vec<(String, String)>,
+    /// JSON body, if any.
+    pub body: Option<serde_json::Value>,
+    /// Overrides the transport's default timeout for this request.
+    pub timeout: Option<Duration>,
+}
+
+/// A fully read response returned by an [`HttpTransport`].
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub headers: HeaderMap,
+    pub body: String,
+}
+
+/// Sends requests on behalf of a [`RestClient`](crate::RestClient).
+///
+/// The default is [`ReqwestTransport`]; tests can swap in a transport that
+/// never touches the network (see `MockTransport` behind the `testing` feature).
+pub trait HttpTransport: Send + Sync {
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, ApiClientError>>;
+}
+
+/// [`HttpTransport`] backed by a `reqwest::Client`.
+#[derive(Debug, Clone)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    pub fn new(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+impl HttpTransport for ReqwestTransport {
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, ApiClientError>> {
+        Box::pin(async move {
+            let mut req = self
+                .client
+                .request(request.method, &request.url)
+                .headers(request.headers);
+            if !request.query.is_empty() {
+                req = req.query(&request.query);
+            }
+            if let Some(body) = &request.body {
+                req = req.json(body);
+            }
+            if let Some(timeout) = request.timeout {
+                req = req.timeout(timeout);
+            }
+            let resp = req.send().await?;
+            let status = resp.status().as_u16();
+            let headers = resp.headers().clone();
+            let body = resp.text().await?;
+            Ok(HttpResponse {
+                status,
+                headers,
+                body,
+            })
+        })
+    }
+}
+
+#[cfg(any(test, feature = "testing"))]
+pub use mock::MockTransport;
+
+#[cfg(any(test, feature = "testing"))]
+mod mock {
+    use std::collections::{HashMap, VecDeque};
+    use std::sync::Mutex;
+
+    use super::*;
+
+    /// In-memory [`HttpTransport`] returning canned responses keyed by method
+    /// and URL path (query string and host ignored).
+    ///
+    /// Responses registered for the same route are returned in order; the last
+    /// one repeats. Unmatched requests get a 404. Every request is recorded.
+    #[derive(Debug, Default)]
+    pub struct MockTransport {
+        routes: Mutex<HashMap<(Method, String), VecDeque<HttpResponse>>>,
+        requests: Mutex<Vec<HttpRequest>>,
+    }
+
+    impl MockTransport {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Queue a JSON response for `method path`.
+        pub fn respond(self, method: Method, path: &str, status: u16, body: &str) -> Self {
+            self.routes
+                .lock()
+                .unwrap()
+                .entry((method, path.to_string()))
+                .or_default()
+                .push_back(HttpResponse {
+                    status,
+                    headers: HeaderMap::new(),
+                    body: body.to_string(),
+                });
+            self
+        }
+
+        /// Requests sent so far, oldest first.
+        pub fn requests(&self) -> Vec<HttpRequest> {
+            self.requests.lock().unwrap().clone()
+        }
+
+        fn lookup(&self, method: &Method, path: &str) -> Option<HttpResponse> {
+            let mut routes = self.routes.lock().unwrap();
+            let queue = routes.get_mut(&(method.clone(), path.to_string()))?;
+            if queue.len() > 1 {
+                queue.pop_front()
+            } else {
+                queue.front().cloned()
+            }
+        }
+    }
+
+    impl HttpTransport for MockTransport {
+        fn send(
+            &self,
+            request: HttpRequest,
+        ) -> BoxFuture<'_, Result<HttpResponse, ApiClientError>> {
+            let path = url::Url::parse(&request.url)
+                .map(|url| url.path().to_string())
+                .unwrap_or_else(|_| request.url.clone());
+            let response = self
+                .lookup(&request.method, &path)
+                .unwrap_or_else(|| HttpResponse {
+                    status: 404,
+                    headers: HeaderMap::new(),
+                    body: format!("no mock response for {} {path}", request.method),
+                });
+            self.requests.lock().unwrap().push(request);
+            Box::pin(async move { Ok(response) })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RestClient;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn mock_transport_replays_routes_in_order() {
+        let mock = Arc::new(
+            MockTransport::new()
+                .respond(Method::GET, "/v2/clock", 503, "busy")
+                .respond(Method::GET, "/v2/clock", 200, r#"{"is_open":true}"#),
+        );
+        let client = RestClient::builder("https://api.example.com")
+            .transport(mock.clone())
+            .build()
+            .unwrap();
+
+        let first: Result<serde_json::Value, _> = client.get("/v2/clock").await;
+        assert!(matches!(
+            first,
+            Err(ApiClientError::Api { status: 503, .. })
+        ));
+        for _ in 0..2 {
+            let ok: serde_json::Value = client.get("/v2/clock").await.unwrap();
+            assert_eq!(ok["is_open"], true);
+        }
+
+        let missing: Result<serde_json::Value, _> = client
+            .get_with_query("/v2/orders", &[("status", "open")])
+            .await;
+        assert!(matches!(
+            missing,
+            Err(ApiClientError::Api { status: 404, .. })
+        ));
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 4);
+        assert_eq!(
+            requests[3].query,
+            vec![("status".to_string(), "open".to_string())]
+        );
+    }
+}
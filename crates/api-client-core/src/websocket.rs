@@ -1,23 +1,236 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
 use futures_util::{SinkExt, StreamExt};
-use tokio::sync::mpsc;
-use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::{mpsc, Mutex};
+use tokio::time::Instant;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::{HeaderName, HeaderValue};
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+pub use tokio_tungstenite::tungstenite::protocol::WebSocketConfig;
+use tokio_tungstenite::{
+    client_async_tls_with_config, connect_async_with_config, tungstenite::Message,
+};
 use tracing::{debug, error, warn};
 
 use crate::error::ApiClientError;
 
+type WsSink = Arc<
+    Mutex<
+        futures_util::stream::SplitSink<
+            tokio_tungstenite::WebSocketStream<
+                tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
+            >,
+            Message,
+        >,
+    >,
+>;
+
+type WsReceiver = mpsc::Receiver<Result<WsFrame, ApiClientError>>;
+
+/// The receive buffer, shared with the reader task so it can evict the
+/// oldest frame under [`OverflowPolicy::DropOldest`].
+type SharedReceiver = Arc<std::sync::Mutex<WsReceiver>>;
+
+/// Receive buffer size when [`ConnectOptions::channel_capacity`] isn't set.
+const DEFAULT_CHANNEL_CAPACITY: usize = 256;
+
+/// Handshake deadline when [`ConnectOptions::connect_timeout`] isn't set.
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A data frame received from the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WsFrame {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+impl WsFrame {
+    /// The frame as text, decoding binary frames as UTF-8.
+    ///
+    /// Returns `None` for binary frames that aren't valid UTF-8.
+    pub fn into_text(self) -> Option<String> {
+        match self {
+            WsFrame::Text(text) => Some(text),
+            WsFrame::Binary(data) => match String::from_utf8(data) {
+                Ok(text) => Some(text),
+                Err(e) => {
+                    warn!("Non-UTF8 binary message: {e}");
+                    None
+                }
+            },
+        }
+    }
+}
+
+/// Client-initiated heartbeat settings.
+///
+/// A ping is sent after `interval` without traffic; if nothing (pong or any
+/// other message) arrives within `timeout` of the ping, the connection is
+/// treated as dead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Keepalive {
+    pub interval: Duration,
+    pub timeout: Duration,
+}
+
+/// What the reader does when the receive buffer is full because the
+/// consumer is falling behind.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Wait for the consumer to make room. Nothing is lost, but the reader
+    /// stops servicing the socket (including heartbeats) while it waits.
+    #[default]
+    Block,
+    /// Discard the oldest buffered frame to make room, counting it in
+    /// [`WebSocketClient::dropped_frames`]. Keeps the reader live at the cost
+    /// of data, which suits feeds where only the latest values matter.
+    DropOldest,
+}
+
+/// Options applied to every (re)connect of a [`WebSocketClient`].
+#[derive(Debug, Clone, Default)]
+pub struct ConnectOptions {
+    /// Extra HTTP headers sent with the WebSocket handshake request.
+    pub headers: Vec<(String, String)>,
+    /// Heartbeat settings; `None` disables client pings.
+    pub keepalive: Option<Keepalive>,
+    /// `http://host:port` proxy to tunnel through with `CONNECT`.
+    pub proxy: Option<String>,
+    /// Frames buffered between the reader and the consumer; 256 if unset.
+    pub channel_capacity: Option<usize>,
+    /// What to do when the buffer is full.
+    pub overflow: OverflowPolicy,
+    /// Protocol limits and buffer sizes for the connection, e.g. a larger
+    /// `max_message_size`; tungstenite's defaults if unset.
+    ///
+    /// This does not enable compression: as of tungstenite 0.26, the version
+    /// this crate depends on, no WebSocket extensions are implemented, so
+    /// `permessage-deflate` is never offered and frames are always exchanged
+    /// uncompressed.
+    pub websocket_config: Option<WebSocketConfig>,
+    /// Deadline for the TCP connect, TLS and WebSocket handshakes and the
+    /// auth message together; 10s if unset.
+    pub connect_timeout: Option<Duration>,
+}
+
+impl ConnectOptions {
+    /// Add a header to the handshake request.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Send a ping every `interval` of silence and fail the connection with a
+    /// `heartbeat timeout` error if no reply arrives within `timeout`.
+    pub fn keepalive(mut self, interval: Duration, timeout: Duration) -> Self {
+        self.keepalive = Some(Keepalive { interval, timeout });
+        self
+    }
+
+    /// Connect through an HTTP proxy, e.g. `http://proxy.corp:3128`.
+    pub fn proxy(mut self, url: impl Into<String>) -> Self {
+        self.proxy = Some(url.into());
+        self
+    }
+
+    /// Buffer up to `capacity` received frames (at least one).
+    pub fn channel_capacity(mut self, capacity: usize) -> Self {
+        self.channel_capacity = Some(capacity);
+        self
+    }
+
+    /// Choose what happens when the consumer lags and the buffer fills.
+    pub fn overflow(mut self, policy: OverflowPolicy) -> Self {
+        self.overflow = policy;
+        self
+    }
+
+    /// Apply `config` to the WebSocket protocol layer.
+    pub fn websocket_config(mut self, config: WebSocketConfig) -> Self {
+        self.websocket_config = Some(config);
+        self
+    }
+
+    /// Fail a (re)connect with a `connect timeout` error if it takes longer
+    /// than `timeout`.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+}
+
+/// Open a TCP tunnel to `host:port` through the HTTP proxy at `proxy`.
+async fn connect_via_proxy(
+    proxy: &str,
+    host: &str,
+    port: u16,
+) -> Result<TcpStream, ApiClientError> {
+    let invalid =
+        |reason: String| ApiClientError::Config(format!("invalid proxy URL `{proxy}`: {reason}"));
+    let proxy_url = url::Url::parse(proxy).map_err(|e| invalid(e.to_string()))?;
+    if proxy_url.scheme() != "http" {
+        return Err(invalid(format!(
+            "unsupported scheme `{}`, expected http",
+            proxy_url.scheme()
+        )));
+    }
+    let proxy_host = proxy_url
+        .host_str()
+        .ok_or_else(|| invalid("missing host".into()))?;
+    let proxy_port = proxy_url.port_or_known_default().unwrap_or(80);
+
+    let failed =
+        |e: std::io::Error| ApiClientError::WebSocket(format!("Proxy connection failed: {e}"));
+    let mut stream = TcpStream::connect((proxy_host, proxy_port))
+        .await
+        .map_err(failed)?;
+    stream
+        .write_all(
+            format!("CONNECT {host}:{port} HTTP/1.1\r\nHost: {host}:{port}\r\n\r\n").as_bytes(),
+        )
+        .await
+        .map_err(failed)?;
+
+    // Read the response head byte by byte so nothing past it is consumed.
+    let mut head = Vec::new();
+    while !head.ends_with(b"\r\n\r\n") {
+        if head.len() > 8192 {
+            return Err(ApiClientError::WebSocket(
+                "Proxy response header too large".into(),
+            ));
+        }
+        head.push(stream.read_u8().await.map_err(failed)?);
+    }
+    let status_line = String::from_utf8_lossy(&head);
+    let status_line = status_line.lines().next().unwrap_or_default();
+    match status_line.split_whitespace().nth(1) {
+        Some(status) if status.starts_with('2') => Ok(stream),
+        _ => Err(ApiClientError::WebSocket(format!(
+            "Proxy refused CONNECT: {status_line}"
+        ))),
+    }
+}
+
 /// Generic WebSocket client for streaming APIs.
 ///
 /// Connects to a WebSocket endpoint, optionally sends an authentication message,
 /// and provides a channel-based interface for receiving messages.
 pub struct WebSocketClient {
-    write: futures_util::stream::SplitSink<
-        tokio_tungstenite::WebSocketStream<
-            tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
-        >,
-        Message,
-    >,
-    receiver: mpsc::Receiver<Result<String, ApiClientError>>,
-    _reader_handle: tokio::task::JoinHandle<()>,
+    url: String,
+    auth_message: Option<serde_json::Value>,
+    options: ConnectOptions,
+    write: WsSink,
+    receiver: SharedReceiver,
+    reader_handle: tokio::task::JoinHandle<()>,
+    dropped: Arc<AtomicU64>,
+    /// Set once a write fails; cleared by a successful reconnect.
+    write_failed: AtomicBool,
 }
 
 impl WebSocketClient {
@@ -28,91 +241,240 @@ impl WebSocketClient {
         url: &str,
         auth_message: Option<serde_json::Value>,
     ) -> Result<Self, ApiClientError> {
-        debug!("WebSocket connecting to {url}");
+        Self::connect_with_options(url, auth_message, ConnectOptions::default()).await
+    }
 
-        let (ws_stream, _) = connect_async(url)
-            .await
-            .map_err(|e| ApiClientError::WebSocket(format!("Connection failed: {e}")))?;
+    /// Connect to a WebSocket endpoint with custom handshake options.
+    pub async fn connect_with_options(
+        url: &str,
+        auth_message: Option<serde_json::Value>,
+        options: ConnectOptions,
+    ) -> Result<Self, ApiClientError> {
+        let dropped = Arc::new(AtomicU64::new(0));
+        let (write, receiver, reader_handle) =
+            Self::open(url, auth_message.as_ref(), &options, &dropped).await?;
 
-        let (mut write, read) = ws_stream.split();
+        Ok(Self {
+            url: url.to_string(),
+            auth_message,
+            options,
+            write,
+            receiver,
+            reader_handle,
+            dropped,
+            write_failed: AtomicBool::new(false),
+        })
+    }
 
-        if let Some(auth) = auth_message {
-            let msg = serde_json::to_string(&auth)
-                .map_err(|e| ApiClientError::WebSocket(format!("Auth serialization: {e}")))?;
-            write
-                .send(Message::Text(msg.into()))
-                .await
-                .map_err(|e| ApiClientError::WebSocket(format!("Auth send failed: {e}")))?;
-            debug!("WebSocket auth message sent");
-        }
+    /// Re-establish the connection to the same URL, re-sending the auth message.
+    ///
+    /// The previous connection's reader is stopped and any messages it had not
+    /// yet delivered are discarded.
+    pub async fn reconnect(&mut self) -> Result<(), ApiClientError> {
+        debug!("WebSocket reconnecting to {}", self.url);
+        let (write, receiver, reader_handle) = Self::open(
+            &self.url,
+            self.auth_message.as_ref(),
+            &self.options,
+            &self.dropped,
+        )
+        .await?;
 
-        let (tx, rx) = mpsc::channel(256);
+        self.reader_handle.abort();
+        self.write = write;
+        self.receiver = receiver;
+        self.reader_handle = reader_handle;
+        self.write_failed.store(false, Ordering::Relaxed);
+        Ok(())
+    }
 
-        let reader_handle = tokio::spawn(async move {
-            let mut read = read;
-            while let Some(msg_result) = read.next().await {
-                match msg_result {
-                    Ok(Message::Text(text)) => {
-                        if tx.send(Ok(text.to_string())).await.is_err() {
-                            break;
-                        }
-                    }
-                    Ok(Message::Binary(data)) => match String::from_utf8(data.to_vec()) {
-                        Ok(text) => {
-                            if tx.send(Ok(text)).await.is_err() {
-                                break;
-                            }
-                        }
-                        Err(e) => {
-                            warn!("Non-UTF8 binary message: {e}");
-                        }
-                    },
-                    Ok(Message::Ping(_)) | Ok(Message::Pong(_)) => {}
-                    Ok(Message::Close(_)) => {
-                        debug!("WebSocket closed by server");
-                        break;
-                    }
-                    Ok(Message::Frame(_)) => {}
-                    Err(e) => {
-                        error!("WebSocket read error: {e}");
-                        let _ = tx
-                            .send(Err(ApiClientError::WebSocket(format!("Read error: {e}"))))
-                            .await;
-                        break;
-                    }
+    /// The URL this client connects to.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// The auth message sent after each (re)connect, if any.
+    pub fn auth_message(&self) -> Option<&serde_json::Value> {
+        self.auth_message.as_ref()
+    }
+
+    /// The options applied to each (re)connect.
+    pub fn options(&self) -> &ConnectOptions {
+        &self.options
+    }
+
+    /// Whether the connection is still usable: no write has failed and the
+    /// reader hasn't seen the socket close. Once this is `false`, [`send`]
+    /// fails immediately and [`recv`] drains what was buffered, then returns
+    /// `None`; call [`reconnect`] to recover.
+    ///
+    /// [`send`]: Self::send
+    /// [`recv`]: Self::recv
+    /// [`reconnect`]: Self::reconnect
+    pub fn is_connected(&self) -> bool {
+        !self.write_failed.load(Ordering::Relaxed) && !self.reader_handle.is_finished()
+    }
+
+    /// Frames discarded under [`OverflowPolicy::DropOldest`] since the
+    /// client was created, across reconnects.
+    pub fn dropped_frames(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    async fn open(
+        url: &str,
+        auth_message: Option<&serde_json::Value>,
+        options: &ConnectOptions,
+        dropped: &Arc<AtomicU64>,
+    ) -> Result<(WsSink, SharedReceiver, tokio::task::JoinHandle<()>), ApiClientError> {
+        debug!("WebSocket connecting to {url}");
+
+        let mut request = url
+            .into_client_request()
+            .map_err(|e| ApiClientError::Config(format!("Invalid WebSocket URL: {e}")))?;
+        for (name, value) in &options.headers {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|e| ApiClientError::Config(e.to_string()))?;
+            let value =
+                HeaderValue::from_str(value).map_err(|e| ApiClientError::Config(e.to_string()))?;
+            request.headers_mut().insert(name, value);
+        }
+
+        let handshake = async {
+            let connected = match &options.proxy {
+                Some(proxy) => {
+                    let uri = request.uri();
+                    let host = uri.host().unwrap_or_default().to_string();
+                    let port = uri
+                        .port_u16()
+                        .unwrap_or(if uri.scheme_str() == Some("wss") {
+                            443
+                        } else {
+                            80
+                        });
+                    let tunnel = connect_via_proxy(proxy, &host, port).await?;
+                    client_async_tls_with_config(request, tunnel, options.websocket_config, None)
+                        .await
                 }
+                None => connect_async_with_config(request, options.websocket_config, false).await,
+            };
+            let (ws_stream, _) = connected
+                .map_err(|e| ApiClientError::WebSocket(format!("Connection failed: {e}")))?;
+
+            let (mut write, read) = ws_stream.split();
+
+            if let Some(auth) = auth_message {
+                let msg = serde_json::to_string(auth)
+                    .map_err(|e| ApiClientError::WebSocket(format!("Auth serialization: {e}")))?;
+                write
+                    .send(Message::Text(msg.into()))
+                    .await
+                    .map_err(|e| ApiClientError::WebSocket(format!("Auth send failed: {e}")))?;
+                debug!("WebSocket auth message sent");
             }
-        });
+            Ok::<_, ApiClientError>((write, read))
+        };
+        let connect_timeout = options.connect_timeout.unwrap_or(DEFAULT_CONNECT_TIMEOUT);
+        let (write, read) = tokio::time::timeout(connect_timeout, handshake)
+            .await
+            .map_err(|_| {
+                ApiClientError::WebSocket(format!("connect timeout after {connect_timeout:?}"))
+            })??;
 
-        Ok(Self {
-            write,
-            receiver: rx,
-            _reader_handle: reader_handle,
-        })
+        let write = Arc::new(Mutex::new(write));
+        let capacity = options
+            .channel_capacity
+            .unwrap_or(DEFAULT_CHANNEL_CAPACITY)
+            .max(1);
+        let (tx, rx) = mpsc::channel(capacity);
+        let rx = Arc::new(std::sync::Mutex::new(rx));
+        let outbox = Outbox {
+            tx,
+            receiver: Arc::downgrade(&rx),
+            overflow: options.overflow,
+            dropped: Arc::clone(dropped),
+        };
+        let reader_handle = tokio::spawn(read_loop(read, write.clone(), outbox, options.keepalive));
+
+        Ok((write, rx, reader_handle))
     }
 
     /// Send a JSON message over the WebSocket.
+    ///
+    /// Fails without writing once the connection is known to be dead (see
+    /// [`is_connected`](Self::is_connected)). A failed write marks it dead and
+    /// stops the reader, so pending [`recv`](Self::recv) calls end too.
     pub async fn send(&mut self, message: &serde_json::Value) -> Result<(), ApiClientError> {
         let text = serde_json::to_string(message)
             .map_err(|e| ApiClientError::WebSocket(format!("Serialization: {e}")))?;
-        self.write
-            .send(Message::Text(text.into()))
+        if !self.is_connected() {
+            return Err(ApiClientError::WebSocket(
+                "Send failed: connection is closed".into(),
+            ));
+        }
+        let sent = self
+            .write
+            .lock()
             .await
-            .map_err(|e| ApiClientError::WebSocket(format!("Send failed: {e}")))
+            .send(Message::Text(text.into()))
+            .await;
+        sent.map_err(|e| {
+            warn!("WebSocket write failed, marking connection closed: {e}");
+            self.write_failed.store(true, Ordering::Relaxed);
+            self.reader_handle.abort();
+            ApiClientError::WebSocket(format!("Send failed: {e}"))
+        })
     }
 
-    /// Receive the next message from the WebSocket.
+    /// Receive the next text message from the WebSocket.
     ///
-    /// Returns `None` if the connection has been closed.
+    /// Binary frames are decoded as UTF-8; frames that aren't valid UTF-8 are
+    /// skipped. Returns `None` if the connection has been closed.
     pub async fn recv(&mut self) -> Option<Result<String, ApiClientError>> {
-        self.receiver.recv().await
+        std::future::poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    /// Poll for the next text message, for use in manual `Stream` implementations.
+    ///
+    /// Returns `Poll::Ready(None)` once the connection has been closed.
+    pub fn poll_recv(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<String, ApiClientError>>> {
+        loop {
+            match futures_util::ready!(self.poll_recv_frame(cx)) {
+                Some(Ok(frame)) => {
+                    if let Some(text) = frame.into_text() {
+                        return Poll::Ready(Some(Ok(text)));
+                    }
+                }
+                Some(Err(e)) => return Poll::Ready(Some(Err(e))),
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+
+    /// Receive the next raw frame, leaving binary payloads undecoded.
+    pub async fn recv_frame(&mut self) -> Option<Result<WsFrame, ApiClientError>> {
+        std::future::poll_fn(|cx| self.poll_recv_frame(cx)).await
+    }
+
+    /// Poll for the next raw frame, leaving binary payloads undecoded.
+    pub fn poll_recv_frame(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<WsFrame, ApiClientError>>> {
+        self.receiver
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .poll_recv(cx)
     }
 
     /// Receive and parse the next message as a typed JSON value.
     pub async fn recv_json<T: serde::de::DeserializeOwned>(
         &mut self,
     ) -> Option<Result<T, ApiClientError>> {
-        match self.receiver.recv().await {
+        match self.recv().await {
             Some(Ok(text)) => {
                 Some(serde_json::from_str(&text).map_err(ApiClientError::Deserialize))
             }
@@ -122,10 +484,493 @@ impl WebSocketClient {
     }
 
     /// Close the WebSocket connection.
-    pub async fn close(mut self) -> Result<(), ApiClientError> {
+    pub async fn close(self) -> Result<(), ApiClientError> {
         self.write
+            .lock()
+            .await
             .send(Message::Close(None))
             .await
             .map_err(|e| ApiClientError::WebSocket(format!("Close failed: {e}")))
     }
 }
+
+type WsRead = futures_util::stream::SplitStream<
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>,
+>;
+
+/// The reader task's side of the receive buffer.
+struct Outbox {
+    tx: mpsc::Sender<Result<WsFrame, ApiClientError>>,
+    /// Weak so a dropped client still closes the channel and stops the reader.
+    receiver: Weak<std::sync::Mutex<WsReceiver>>,
+    overflow: OverflowPolicy,
+    dropped: Arc<AtomicU64>,
+}
+
+impl Outbox {
+    /// Queue `frame` per the overflow policy; `false` once the consumer is gone.
+    async fn deliver(&self, frame: WsFrame) -> bool {
+        if self.overflow == OverflowPolicy::Block {
+            return self.tx.send(Ok(frame)).await.is_ok();
+        }
+        let mut item = Ok(frame);
+        loop {
+            match self.tx.try_send(item) {
+                Ok(()) => return true,
+                Err(TrySendError::Closed(_)) => return false,
+                Err(TrySendError::Full(back)) => {
+                    item = back;
+                    let Some(receiver) = self.receiver.upgrade() else {
+                        return false;
+                    };
+                    let evicted = receiver
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .try_recv()
+                        .is_ok();
+                    if evicted {
+                        self.dropped.fetch_add(1, Ordering::Relaxed);
+                        debug!("WebSocket receive buffer full, dropped oldest frame");
+                    }
+                }
+            }
+        }
+    }
+
+    /// Report a terminal error, waiting for room even when dropping frames.
+    async fn fail(&self, error: ApiClientError) {
+        let _ = self.tx.send(Err(error)).await;
+    }
+}
+
+/// Forward incoming frames to the receiver, sending heartbeat pings if enabled.
+async fn read_loop(mut read: WsRead, write: WsSink, outbox: Outbox, keepalive: Option<Keepalive>) {
+    let mut next_ping = keepalive.map(|k| Instant::now() + k.interval);
+    let mut pong_deadline: Option<Instant> = None;
+
+    loop {
+        let msg_result = tokio::select! {
+            msg = read.next() => match msg {
+                Some(msg) => msg,
+                None => break,
+            },
+            _ = sleep_until(next_ping), if pong_deadline.is_none() => {
+                debug!("WebSocket sending heartbeat ping");
+                if let Err(e) = write.lock().await.send(Message::Ping(Vec::new().into())).await {
+                    outbox
+                        .fail(ApiClientError::WebSocket(format!("Ping failed: {e}")))
+                        .await;
+                    break;
+                }
+                pong_deadline = keepalive.map(|k| Instant::now() + k.timeout);
+                continue;
+            }
+            _ = sleep_until(pong_deadline) => {
+                warn!("WebSocket heartbeat timed out");
+                outbox
+                    .fail(ApiClientError::WebSocket("heartbeat timeout".into()))
+                    .await;
+                break;
+            }
+        };
+
+        // Any traffic proves the connection is alive.
+        pong_deadline = None;
+        next_ping = keepalive.map(|k| Instant::now() + k.interval);
+
+        let frame = match msg_result {
+            Ok(Message::Text(text)) => WsFrame::Text(text.to_string()),
+            Ok(Message::Binary(data)) => WsFrame::Binary(data.to_vec()),
+            Ok(Message::Ping(_)) | Ok(Message::Pong(_)) => continue,
+            Ok(Message::Close(frame)) => {
+                debug!("WebSocket closed by server: {frame:?}");
+                // A bare or normal close ends the stream quietly; anything else
+                // is reported so callers can tell why they were disconnected.
+                if let Some(frame) = frame {
+                    let code = u16::from(frame.code);
+                    if frame.code != CloseCode::Normal || !frame.reason.is_empty() {
+                        outbox
+                            .fail(ApiClientError::Closed {
+                                code,
+                                reason: frame.reason.to_string(),
+                            })
+                            .await;
+                    }
+                }
+                break;
+            }
+            Ok(Message::Frame(_)) => continue,
+            Err(e) => {
+                error!("WebSocket read error: {e}");
+                outbox
+                    .fail(ApiClientError::WebSocket(format!("Read error: {e}")))
+                    .await;
+                break;
+            }
+        };
+        if !outbox.deliver(frame).await {
+            break;
+        }
+    }
+}
+
+/// Sleep until `deadline`, or forever if there is none.
+async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn reconnect_resends_auth() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (auth_tx, mut auth_rx) = mpsc::channel(2);
+
+        tokio::spawn(async move {
+            for _ in 0..2 {
+                let (tcp, _) = listener.accept().await.unwrap();
+                let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+                if let Some(Ok(Message::Text(auth))) = ws.next().await {
+                    auth_tx.send(auth.to_string()).await.unwrap();
+                }
+                ws.close(None).await.unwrap();
+            }
+        });
+
+        let auth = serde_json::json!({"action": "auth", "key": "k"});
+        let mut client = WebSocketClient::connect(&url, Some(auth)).await.unwrap();
+        assert_eq!(client.url(), url);
+        assert!(auth_rx.recv().await.unwrap().contains("\"auth\""));
+        assert!(client.recv().await.is_none());
+
+        client.reconnect().await.unwrap();
+        assert!(auth_rx.recv().await.unwrap().contains("\"auth\""));
+        assert!(client.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn handshake_headers_and_binary_frames() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (header_tx, mut header_rx) = mpsc::channel(1);
+
+        tokio::spawn(async move {
+            use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+
+            let (tcp, _) = listener.accept().await.unwrap();
+            #[allow(clippy::result_large_err)]
+            let callback = |req: &Request, resp: Response| {
+                let value = req
+                    .headers()
+                    .get("content-type")
+                    .map(|v| v.to_str().unwrap().to_string());
+                header_tx.try_send(value).unwrap();
+                Ok(resp)
+            };
+            let mut ws = tokio_tungstenite::accept_hdr_async(tcp, callback)
+                .await
+                .unwrap();
+            ws.send(Message::Binary(vec![0xc0, 0xff].into()))
+                .await
+                .unwrap();
+            ws.send(Message::Binary(b"hello".to_vec().into()))
+                .await
+                .unwrap();
+            ws.close(None).await.unwrap();
+        });
+
+        let options = ConnectOptions::default().header("Content-Type", "application/msgpack");
+        let mut client = WebSocketClient::connect_with_options(&url, None, options)
+            .await
+            .unwrap();
+        assert_eq!(
+            header_rx.recv().await.unwrap().as_deref(),
+            Some("application/msgpack")
+        );
+
+        assert_eq!(
+            client.recv_frame().await.unwrap().unwrap(),
+            WsFrame::Binary(vec![0xc0, 0xff])
+        );
+        // Text reads decode valid UTF-8 binary frames.
+        assert_eq!(client.recv().await.unwrap().unwrap(), "hello");
+        assert!(client.recv().await.is_none());
+    }
+
+    #[test]
+    fn invalid_utf8_frame_has_no_text() {
+        assert_eq!(WsFrame::Binary(vec![0xff]).into_text(), None);
+        assert_eq!(
+            WsFrame::Text("hi".into()).into_text().as_deref(),
+            Some("hi")
+        );
+    }
+
+    #[tokio::test]
+    async fn keepalive_sends_pings_to_live_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (ping_tx, mut ping_rx) = mpsc::channel(8);
+
+        tokio::spawn(async move {
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+            // Reading lets tungstenite answer pings with pongs automatically.
+            while let Some(Ok(msg)) = ws.next().await {
+                if msg.is_ping() {
+                    let _ = ping_tx.send(()).await;
+                }
+            }
+        });
+
+        let options = ConnectOptions::default()
+            .keepalive(Duration::from_millis(20), Duration::from_millis(100));
+        let mut client = WebSocketClient::connect_with_options(&url, None, options)
+            .await
+            .unwrap();
+
+        for _ in 0..3 {
+            ping_rx.recv().await.unwrap();
+        }
+        // Pongs keep the connection alive, so nothing is surfaced.
+        let idle = tokio::time::timeout(Duration::from_millis(150), client.recv()).await;
+        assert!(idle.is_err());
+    }
+
+    #[tokio::test]
+    async fn missing_pong_surfaces_heartbeat_timeout() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+
+        tokio::spawn(async move {
+            let (tcp, _) = listener.accept().await.unwrap();
+            // Never read, so pings go unanswered.
+            let _ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        });
+
+        let options = ConnectOptions::default()
+            .keepalive(Duration::from_millis(20), Duration::from_millis(50));
+        let mut client = WebSocketClient::connect_with_options(&url, None, options)
+            .await
+            .unwrap();
+
+        match client.recv().await {
+            Some(Err(ApiClientError::WebSocket(msg))) => assert_eq!(msg, "heartbeat timeout"),
+            other => panic!("expected heartbeat timeout, got {other:?}"),
+        }
+        assert!(client.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn close_frame_reason_is_propagated() {
+        use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+
+        tokio::spawn(async move {
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+            ws.close(Some(CloseFrame {
+                code: CloseCode::Library(4008),
+                reason: "auth timeout".into(),
+            }))
+            .await
+            .unwrap();
+        });
+
+        let mut client = WebSocketClient::connect(&url, None).await.unwrap();
+        match client.recv().await {
+            Some(Err(ApiClientError::Closed { code, reason })) => {
+                assert_eq!(code, 4008);
+                assert_eq!(reason, "auth timeout");
+            }
+            other => panic!("expected Closed, got {other:?}"),
+        }
+        assert!(client.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn connects_through_http_proxy() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy = format!("http://{}", listener.local_addr().unwrap());
+        let (connect_tx, mut connect_rx) = mpsc::channel(1);
+
+        // A proxy that accepts the CONNECT and then serves the WebSocket itself.
+        tokio::spawn(async move {
+            let (mut tcp, _) = listener.accept().await.unwrap();
+            let mut head = Vec::new();
+            while !head.ends_with(b"\r\n\r\n") {
+                head.push(tcp.read_u8().await.unwrap());
+            }
+            let request_line = String::from_utf8(head).unwrap();
+            connect_tx
+                .send(request_line.lines().next().unwrap().to_string())
+                .await
+                .unwrap();
+            tcp.write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")
+                .await
+                .unwrap();
+            let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+            ws.send(Message::Text("tunneled".into())).await.unwrap();
+            ws.close(None).await.unwrap();
+        });
+
+        let options = ConnectOptions::default().proxy(proxy);
+        let mut client =
+            WebSocketClient::connect_with_options("ws://stream.example.com/v2/iex", None, options)
+                .await
+                .unwrap();
+        assert_eq!(
+            connect_rx.recv().await.unwrap(),
+            "CONNECT stream.example.com:80 HTTP/1.1"
+        );
+        assert_eq!(client.recv().await.unwrap().unwrap(), "tunneled");
+    }
+
+    #[tokio::test]
+    async fn invalid_proxy_url_is_a_config_error() {
+        for proxy in ["not a url", "socks5://127.0.0.1:1080"] {
+            let options = ConnectOptions::default().proxy(proxy);
+            let result =
+                WebSocketClient::connect_with_options("wss://example.com", None, options).await;
+            assert!(
+                matches!(&result, Err(ApiClientError::Config(msg)) if msg.contains("invalid proxy URL")),
+                "{proxy}: {:?}",
+                result.err()
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn drop_oldest_counts_frames_a_slow_consumer_missed() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (done_tx, mut done_rx) = mpsc::channel(1);
+
+        tokio::spawn(async move {
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+            for i in 0..10 {
+                ws.send(Message::Text(i.to_string().into())).await.unwrap();
+            }
+            ws.close(None).await.unwrap();
+            done_tx.send(()).await.unwrap();
+        });
+
+        let options = ConnectOptions::default()
+            .channel_capacity(2)
+            .overflow(OverflowPolicy::DropOldest);
+        let mut client = WebSocketClient::connect_with_options(&url, None, options)
+            .await
+            .unwrap();
+
+        // Don't read until the server has sent everything and hung up.
+        done_rx.recv().await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while client.dropped_frames() < 8 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("reader stalled instead of dropping frames");
+
+        assert_eq!(client.recv().await.unwrap().unwrap(), "8");
+        assert_eq!(client.recv().await.unwrap().unwrap(), "9");
+        assert!(client.recv().await.is_none());
+        assert_eq!(client.dropped_frames(), 8);
+    }
+
+    #[tokio::test]
+    async fn failed_write_closes_client_and_fails_fast() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (release_tx, mut release_rx) = mpsc::channel::<()>(1);
+
+        tokio::spawn(async move {
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+            ws.send(Message::Text("hello".into())).await.unwrap();
+            // Hold the socket open so only the write half is broken.
+            release_rx.recv().await;
+        });
+
+        let mut client = WebSocketClient::connect(&url, None).await.unwrap();
+        assert!(client.is_connected());
+        assert_eq!(client.recv().await.unwrap().unwrap(), "hello");
+
+        // Break the write half underneath the client.
+        client.write.lock().await.close().await.unwrap();
+
+        let msg = serde_json::json!({"action": "subscribe"});
+        let err = client.send(&msg).await.unwrap_err();
+        assert!(err.to_string().contains("Send failed"), "{err}");
+        assert!(!client.is_connected());
+
+        let err = client.send(&msg).await.unwrap_err();
+        assert!(err.to_string().contains("connection is closed"), "{err}");
+
+        // The reader was stopped, so recv ends instead of waiting on a socket
+        // the server is still holding open.
+        let next = tokio::time::timeout(Duration::from_secs(5), client.recv())
+            .await
+            .expect("recv hung after failed write");
+        assert!(next.is_none());
+        drop(release_tx);
+    }
+
+    #[tokio::test]
+    async fn websocket_config_limits_are_applied() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+
+        tokio::spawn(async move {
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+            ws.send(Message::Text("short".into())).await.unwrap();
+            ws.send(Message::Text("x".repeat(64).into())).await.unwrap();
+            let _ = ws.next().await;
+        });
+
+        let options = ConnectOptions::default()
+            .websocket_config(WebSocketConfig::default().max_message_size(Some(16)));
+        assert_eq!(options.websocket_config.unwrap().max_message_size, Some(16));
+        let mut client = WebSocketClient::connect_with_options(&url, None, options)
+            .await
+            .unwrap();
+
+        assert_eq!(client.recv().await.unwrap().unwrap(), "short");
+        let err = client.recv().await.unwrap().unwrap_err();
+        assert!(err.to_string().contains("too long"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn connect_times_out_instead_of_hanging() {
+        // Accepts the TCP connection but never answers the upgrade request.
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let (_tcp, _) = listener.accept().await.unwrap();
+            std::future::pending::<()>().await;
+        });
+
+        let options = ConnectOptions::default().connect_timeout(Duration::from_millis(100));
+        let started = std::time::Instant::now();
+        match WebSocketClient::connect_with_options(&url, None, options).await {
+            Err(ApiClientError::WebSocket(msg)) => {
+                assert!(msg.contains("connect timeout"), "{msg}")
+            }
+            Err(e) => panic!("expected connect timeout, got {e}"),
+            Ok(_) => panic!("expected connect timeout"),
+        }
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}